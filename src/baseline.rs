@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::StatusCode;
+
+/// 每个目标发送的校准探测数
+pub(crate) const CALIBRATION_PROBES: usize = 3;
+
+/// 每个目标请求首页的次数，用于估计首页大小的波动
+pub(crate) const HOMEPAGE_PROBES: usize = 2;
+
+/// 生成一个几乎不可能存在的随机路径，用于探测目标对不存在路径的响应
+pub(crate) fn random_path(rng: &mut impl Rng) -> String {
+    rng.sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// 目标对不存在路径的典型响应（404基线），也用于记录首页的指纹
+#[derive(Debug, Clone)]
+pub(crate) struct Baseline {
+    pub status: StatusCode,
+    pub size: usize,
+    // 校准探测之间的大小波动，页面回显路径等动态内容时不为0
+    pub spread: usize,
+}
+
+impl Baseline {
+    /// 由校准探测的结果确定基线：取出现最多的状态码，大小取该状态码下的最小值并记录波动
+    pub(crate) fn from_probes(probes: &[(StatusCode, usize)]) -> Option<Self> {
+        let mut counts: HashMap<StatusCode, usize> = HashMap::new();
+        for (status, _) in probes {
+            *counts.entry(*status).or_insert(0) += 1;
+        }
+        let (status, _) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+        let sizes: Vec<usize> = probes
+            .iter()
+            .filter(|(probe_status, _)| *probe_status == status)
+            .map(|(_, size)| *size)
+            .collect();
+        let min = *sizes.iter().min()?;
+        let max = *sizes.iter().max()?;
+        Some(Self {
+            status,
+            size: min,
+            spread: max - min,
+        })
+    }
+
+    /// 响应的状态码与基线相同且大小差异不超过阈值（至少为校准时观察到的波动）时视为与基线一致
+    pub(crate) fn matches(&self, status: StatusCode, size: usize, threshold: usize) -> bool {
+        let tolerance = threshold.max(self.spread);
+        status == self.status && size.abs_diff(self.size) <= tolerance
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "状态码 {}，大小 {} 字节", self.status.as_u16(), self.size)?;
+        if self.spread > 0 {
+            write!(f, "（波动 {} 字节）", self.spread)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! 基准测试模式：在进程内启动返回固定响应的HTTP服务器，对其运行完整的扫描流程并统计吞吐量
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+
+use crate::config::ScanConfigBuilder;
+use crate::report::ScanReport;
+use crate::scanner::Scanner;
+
+/// 每隔这么多个字典项安排一个存在的路径
+const HIT_INTERVAL: usize = 100;
+/// 所有 `/same-` 路径返回的相同页面，用于触发重复大小过滤
+const SAME_BODY: &str = "<html><head><title>Welcome</title></head><body>default page</body></html>";
+
+/// 一次基准测试的结果
+#[derive(Debug)]
+pub struct BenchmarkReport {
+    /// 扫描报告
+    pub report: ScanReport,
+    /// 扫描耗时（不含服务器启动）
+    pub elapsed: Duration,
+}
+
+impl BenchmarkReport {
+    /// 每秒完成的请求数
+    pub fn requests_per_sec(&self) -> f64 {
+        self.report.requests as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// 测试服务器的固定响应：`/hit-N` 返回大小各不相同的页面，`/same-N` 返回相同页面，其余返回404
+async fn respond(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let path = request.uri().path();
+    let (status, body) = if let Some(n) = path.strip_prefix("/hit-") {
+        let padding = n.parse::<usize>().unwrap_or(0);
+        (StatusCode::OK, format!("<html><body>found{}</body></html>", "#".repeat(padding)))
+    } else if path.starts_with("/same-") {
+        (StatusCode::OK, SAME_BODY.to_string())
+    } else {
+        (StatusCode::NOT_FOUND, "Not Found".to_string())
+    };
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    Ok(response)
+}
+
+/// 在本地随机端口启动测试服务器
+fn start_server() -> Result<(SocketAddr, tokio::task::JoinHandle<()>)> {
+    let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(respond)) });
+    let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .map_err(|e| anyhow!("基准测试服务器启动失败: {}", e))?
+        .serve(make_service);
+    let addr = server.local_addr();
+    let handle = tokio::spawn(async move {
+        if let Err(e) = server.await {
+            tracing::error!(error = %e, "基准测试服务器出错");
+        }
+    });
+    Ok((addr, handle))
+}
+
+/// 生成基准测试字典：约1%存在的路径、1%返回相同页面的路径，其余都不存在
+fn benchmark_paths(requests: usize) -> Vec<String> {
+    (0..requests)
+        .map(|i| match i % HIT_INTERVAL {
+            0 => format!("hit-{}", i / HIT_INTERVAL),
+            1 => format!("same-{}", i),
+            _ => format!("miss-{}", i),
+        })
+        .collect()
+}
+
+/// 对进程内测试服务器发送 `requests` 个请求，测量整个扫描流程的吞吐量
+///
+/// 目标URL由测试服务器决定，其余配置（并发、过滤、输出等）沿用 `builder`；
+/// 基准测试不会等待交互确认
+pub async fn run_benchmark(builder: ScanConfigBuilder, requests: usize) -> Result<BenchmarkReport> {
+    let (addr, server) = start_server()?;
+    let config = builder
+        .url(format!("http://{}/", addr))
+        .interactive(false)
+        .build()?;
+    let scanner = Scanner::new(config)?;
+
+    let started = Instant::now();
+    let report = scanner.run(benchmark_paths(requests)).await;
+    let elapsed = started.elapsed();
+    server.abort();
+
+    Ok(BenchmarkReport { report: report?, elapsed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScanConfig;
+    use crate::finding::FilterReason;
+
+    #[tokio::test]
+    async fn benchmark_runs_full_pipeline() {
+        let result = run_benchmark(ScanConfig::builder().threads(20), 1000).await.unwrap();
+        let report = &result.report;
+        assert_eq!(report.requests, 1000);
+        assert_eq!(report.errors, 0);
+        // 每个 hit 路径大小都不同，必然全部报告
+        let hits = report.findings.iter().filter(|f| f.url.path().starts_with("/hit-") && !f.filtered).count();
+        assert_eq!(hits, 10);
+        // 相同页面超过阈值后被重复大小过滤
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.url.path().starts_with("/same-") && f.reason == Some(FilterReason::RepeatedSize)));
+        assert!(result.requests_per_sec() > 0.0);
+    }
+}
@@ -0,0 +1,120 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use time::OffsetDateTime;
+use tokio::net::TcpStream;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// 距离过期不足这么多天时提示续期
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// `--show-cert`：目标HTTPS服务返回的叶子证书的主要信息
+#[derive(Debug, Clone)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// 主题备用名称中的域名和IP地址，常暴露出同一证书覆盖的其他主机
+    pub sans: Vec<String>,
+    pub not_before: OffsetDateTime,
+    pub not_after: OffsetDateTime,
+}
+
+impl CertificateInfo {
+    /// 距离过期的天数，已过期时为负数
+    pub fn days_remaining(&self) -> i64 {
+        (self.not_after - OffsetDateTime::now_utc()).whole_days()
+    }
+
+    fn from_der(der: &[u8]) -> Result<Self> {
+        let (_, cert) = X509Certificate::from_der(der)
+            .map_err(|e| anyhow!("解析证书失败: {}", e))?;
+        let sans = match cert.subject_alternative_name() {
+            Ok(Some(extension)) => extension.value.general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(name) => Some(name.to_string()),
+                    GeneralName::IPAddress(bytes) => ip_from_bytes(bytes),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            sans,
+            not_before: cert.validity().not_before.to_datetime(),
+            not_after: cert.validity().not_after.to_datetime(),
+        })
+    }
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => Some(std::net::Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).to_string()),
+        16 => Some(std::net::Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string()),
+        _ => None,
+    }
+}
+
+/// 与 `addr` 完成一次TLS握手并取回服务器证书，`server_name` 作为SNI发送
+///
+/// 只为读取证书，不校验证书链和主机名，过期或自签名的证书同样能取到
+pub(crate) async fn fetch_certificate(addr: SocketAddr, server_name: &str, timeout: Duration) -> Result<CertificateInfo> {
+    let connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .map_err(|e| anyhow!("创建TLS连接器失败: {}", e))?;
+    let connector = tokio_native_tls::TlsConnector::from(connector);
+    let handshake = async {
+        let stream = TcpStream::connect(addr).await
+            .map_err(|e| anyhow!("连接 {} 失败: {}", addr, e))?;
+        connector.connect(server_name, stream).await
+            .map_err(|e| anyhow!("与 {} 的TLS握手失败: {}", addr, e))
+    };
+    let stream = tokio::time::timeout(timeout, handshake).await
+        .map_err(|_| anyhow!("连接 {} 超时", addr))??;
+    let der = stream.get_ref()
+        .peer_certificate()
+        .map_err(|e| anyhow!("读取服务器证书失败: {}", e))?
+        .ok_or_else(|| anyhow!("服务器没有提供证书"))?
+        .to_der()
+        .map_err(|e| anyhow!("读取服务器证书失败: {}", e))?;
+    CertificateInfo::from_der(&der)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 自签名的P-256证书，有效期2024-01-01至2034-01-01，备用名称含两个域名、两个IP和一个邮箱
+    const CERT: &[u8] = include_bytes!("test_cert.der");
+
+    #[test]
+    fn parses_certificate_fields() {
+        let info = CertificateInfo::from_der(CERT).unwrap();
+        assert_eq!(info.subject, "CN=example.com, O=RDirScan Test");
+        assert_eq!(info.issuer, info.subject);
+        // 邮箱等其他类型的备用名称不列出
+        assert_eq!(info.sans, ["example.com", "www.example.com", "192.0.2.1", "2001:db8::1"]);
+        assert_eq!(info.not_before.unix_timestamp(), 1_704_067_200);
+        assert_eq!(info.not_after.unix_timestamp(), 2_019_686_400);
+
+        let err = CertificateInfo::from_der(&CERT[..100]).unwrap_err().to_string();
+        assert!(err.starts_with("解析证书失败"), "{}", err);
+    }
+
+    #[test]
+    fn formats_san_addresses() {
+        assert_eq!(ip_from_bytes(&[10, 0, 0, 1]).as_deref(), Some("10.0.0.1"));
+        let mut v6 = [0u8; 16];
+        v6[15] = 1;
+        assert_eq!(ip_from_bytes(&v6).as_deref(), Some("::1"));
+        // 带掩码的名称约束等长度不符的值忽略
+        assert_eq!(ip_from_bytes(&[10, 0, 0, 0, 255, 0, 0, 0]), None);
+        assert_eq!(ip_from_bytes(&[]), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+use url::Url;
+
+/// 小于该值的响应按固定宽度分桶
+const SMALL_SIZE: usize = 128;
+/// 小响应的桶宽
+const SMALL_BUCKET: usize = 16;
+/// 较大的响应在每个2的幂区间内分成的桶数，相当于按约12%的相对误差归类
+const BUCKETS_PER_OCTAVE: usize = 8;
+
+/// 大小所在桶的下界，相近大小的响应（例如回显了路径的同一页面）落在同一个桶中
+pub(crate) fn size_bucket(size: usize) -> usize {
+    let width = bucket_width(size);
+    size / width * width
+}
+
+/// 大小所在桶的宽度
+fn bucket_width(size: usize) -> usize {
+    if size < SMALL_SIZE {
+        return SMALL_BUCKET;
+    }
+    let octave = 1usize << (usize::BITS - 1 - size.leading_zeros());
+    octave / BUCKETS_PER_OCTAVE
+}
+
+/// 一类相似的响应：同一主机上状态码、大小桶和标题都相同
+#[derive(Debug, Clone)]
+pub struct ResponseCluster {
+    /// 目标主机（含端口）
+    pub host: String,
+    pub status: u16,
+    /// 大小所在桶的下界
+    pub size: usize,
+    /// 页面标题，未读取响应内容或没有标题时为 `None`
+    pub title: Option<String>,
+    /// 该类的响应数
+    pub count: usize,
+    /// 该类中最先收到的响应
+    pub example: String,
+}
+
+impl ResponseCluster {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "host": self.host,
+            "status": self.status,
+            "size": self.size,
+            "title": self.title,
+            "count": self.count,
+            "example": self.example,
+        })
+    }
+}
+
+impl fmt::Display for ResponseCluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 个  状态码 {}  ", self.count, self.status)?;
+        if self.size < 1024 {
+            write!(f, "{}-{} 字节", self.size, self.size + bucket_width(self.size) - 1)?;
+        } else {
+            write!(f, "约 {:.1} KB", self.size as f64 / 1024.0)?;
+        }
+        if let Some(ref title) = self.title {
+            write!(f, "  标题 \"{}\"", title)?;
+        }
+        write!(f, "  例如 {}", self.example)
+    }
+}
+
+type ClusterKey = (String, u16, usize, Option<String>);
+
+/// 扫描期间收集所有响应（包括被忽略的）的轻量指纹并归类，只保留计数和一个示例URL
+#[derive(Default)]
+pub(crate) struct ResponseClusters {
+    clusters: Mutex<HashMap<ClusterKey, ResponseCluster>>,
+}
+
+impl ResponseClusters {
+    /// 开始新一轮扫描时清空
+    pub(crate) fn reset(&self) {
+        self.clusters.lock().unwrap().clear();
+    }
+
+    pub(crate) fn record(&self, url: &Url, status: u16, size: usize, title: Option<&str>) {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (host, _) => host.unwrap_or_default().to_string(),
+        };
+        let size = size_bucket(size);
+        let title = title.map(str::to_string);
+        self.clusters
+            .lock()
+            .unwrap()
+            .entry((host.clone(), status, size, title.clone()))
+            .or_insert_with(|| ResponseCluster {
+                host,
+                status,
+                size,
+                title,
+                count: 0,
+                example: url.to_string(),
+            })
+            .count += 1;
+    }
+
+    /// 取出所有类别，按响应数从多到少排列
+    pub(crate) fn take(&self) -> Vec<ResponseCluster> {
+        let mut clusters: Vec<_> = self.clusters.lock().unwrap().drain().map(|(_, cluster)| cluster).collect();
+        clusters.sort_by(|a, b| {
+            b.count.cmp(&a.count)
+                .then_with(|| a.host.cmp(&b.host))
+                .then_with(|| a.status.cmp(&b.status))
+                .then_with(|| a.size.cmp(&b.size))
+        });
+        clusters
+    }
+}
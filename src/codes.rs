@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+/// `--match-codes` 的状态码规则，解析一次后在每个响应上查询
+///
+/// 逗号分隔的各项可以是单个状态码（`403`）、范围（`500-599`）或通配（`2xx`、`30x`），
+/// 以 `!` 开头的项表示排除（`2xx,!204`）；只有排除项时匹配其余所有状态码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusCodes {
+    spec: String,
+    include: Vec<(u16, u16)>,
+    exclude: Vec<(u16, u16)>,
+}
+
+impl StatusCodes {
+    pub fn matches(&self, status: u16) -> bool {
+        let within = |ranges: &[(u16, u16)]| ranges.iter().any(|(low, high)| (*low..=*high).contains(&status));
+        (self.include.is_empty() || within(&self.include)) && !within(&self.exclude)
+    }
+}
+
+impl FromStr for StatusCodes {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let mut codes = Self {
+            spec: spec.trim().to_string(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+        };
+        for (i, token) in spec.split(',').enumerate() {
+            let token = token.trim();
+            let (negated, item) = match token.strip_prefix('!') {
+                Some(item) => (true, item.trim()),
+                None => (false, token),
+            };
+            let range = parse_item(item)
+                .map_err(|e| anyhow!("状态码规则 '{}' 的第 {} 项 '{}' 无效: {}", spec, i + 1, token, e))?;
+            if negated {
+                codes.exclude.push(range);
+            } else {
+                codes.include.push(range);
+            }
+        }
+        Ok(codes)
+    }
+}
+
+impl fmt::Display for StatusCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+/// 解析一项为闭区间
+fn parse_item(item: &str) -> Result<(u16, u16)> {
+    if item.is_empty() {
+        return Err(anyhow!("为空"));
+    }
+    if let Some((low, high)) = item.split_once('-') {
+        let (low, high) = (parse_code(low.trim())?, parse_code(high.trim())?);
+        if low > high {
+            return Err(anyhow!("范围的起点 {} 大于终点 {}", low, high));
+        }
+        return Ok((low, high));
+    }
+    let lower = item.to_ascii_lowercase();
+    if lower.len() == 3 && lower.contains('x') {
+        // 通配符只能出现在末尾：`2xx`、`30x`
+        let digits = lower.trim_end_matches('x');
+        if digits.is_empty() || digits.contains('x') || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(anyhow!("通配符 x 只能出现在末尾，如 2xx、30x"));
+        }
+        let scale = 10u16.pow((3 - digits.len()) as u32);
+        let low = digits.parse::<u16>()? * scale;
+        check_range(low)?;
+        return Ok((low, low + scale - 1));
+    }
+    let code = parse_code(item)?;
+    Ok((code, code))
+}
+
+fn parse_code(value: &str) -> Result<u16> {
+    let code = value.parse::<u16>().map_err(|_| anyhow!("'{}' 不是状态码", value))?;
+    check_range(code)?;
+    Ok(code)
+}
+
+fn check_range(code: u16) -> Result<()> {
+    if !(100..=599).contains(&code) {
+        return Err(anyhow!("状态码 {} 超出 100-599", code));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_ranges_wildcards_and_exclusions() {
+        let codes: StatusCodes = "2xx,403,500-599,30x,!204".parse().unwrap();
+        for status in [200, 299, 301, 403, 500, 599] {
+            assert!(codes.matches(status), "{}", status);
+        }
+        for status in [204, 310, 404, 401] {
+            assert!(!codes.matches(status), "{}", status);
+        }
+        let all_but: StatusCodes = "!404".parse().unwrap();
+        assert!(all_but.matches(500) && !all_but.matches(404));
+
+        let err = "200,2z0".parse::<StatusCodes>().unwrap_err().to_string();
+        assert!(err.contains("第 2 项 '2z0'"), "{}", err);
+        assert!("299-200".parse::<StatusCodes>().is_err());
+        assert!("6xx".parse::<StatusCodes>().is_err());
+        assert!("x00".parse::<StatusCodes>().is_err());
+        assert!("200,".parse::<StatusCodes>().is_err());
+    }
+}
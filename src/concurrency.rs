@@ -0,0 +1,152 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// 每统计这么多个请求调整一次并发
+const WINDOW: usize = 20;
+/// 窗口内出错比例超过该值时降低并发
+const MAX_ERROR_RATE: f64 = 0.1;
+/// 平均延迟超过基线的倍数时视为延迟飙升
+const LATENCY_SPIKE: f64 = 2.0;
+/// 平均延迟不超过基线的该倍数时继续提高并发
+const LATENCY_HEADROOM: f64 = 1.3;
+
+/// 未测得目标延迟时的初始并发
+const DEFAULT_THREADS: usize = 10;
+/// 每个CPU核心能从容驱动的并发请求数
+const THREADS_PER_CPU: usize = 8;
+/// 自动选择的并发下限
+const MIN_THREADS: usize = 4;
+/// 目标延迟每增加这么多，多一个并发请求来填满等待时间
+const LATENCY_PER_THREAD: Duration = Duration::from_millis(5);
+
+/// `--threads auto` 选择的并发数：按目标延迟估算需要多少在途请求，不超过本机CPU能驱动的数量
+///
+/// 未测得延迟时使用默认的10，同样受CPU上限约束
+pub fn suggest_threads(latency: Option<Duration>) -> usize {
+    let cpus = std::thread::available_parallelism().map_or(1, |cpus| cpus.get());
+    threads_for(cpus, latency)
+}
+
+fn threads_for(cpus: usize, latency: Option<Duration>) -> usize {
+    let ceiling = (cpus * THREADS_PER_CPU).max(MIN_THREADS);
+    let wanted = match latency {
+        Some(latency) => (latency.as_secs_f64() / LATENCY_PER_THREAD.as_secs_f64()).round() as usize,
+        None => DEFAULT_THREADS,
+    };
+    wanted.clamp(MIN_THREADS, ceiling)
+}
+
+/// 根据目标延迟和出错率自动调整的并发上限
+///
+/// 从较低的并发开始，延迟稳定时先倍增后逐个增加，延迟飙升或出错增多时减半，
+/// 上限为 `--threads` 指定的线程数
+pub(crate) struct ConcurrencyController {
+    semaphore: Semaphore,
+    max: usize,
+    state: Mutex<TuneState>,
+}
+
+struct TuneState {
+    limit: usize,
+    // 降低并发后尚未回收的许可数，归还时直接丢弃
+    debt: usize,
+    // 首次回退前按倍数增长
+    slow_start: bool,
+    // 观测到的最低窗口平均延迟
+    baseline: Option<Duration>,
+    samples: usize,
+    errors: usize,
+    latency_sum: Duration,
+}
+
+impl ConcurrencyController {
+    pub(crate) fn new(max: usize) -> Self {
+        let start = max.min(4);
+        Self {
+            semaphore: Semaphore::new(start),
+            max,
+            state: Mutex::new(TuneState {
+                limit: start,
+                debt: 0,
+                slow_start: true,
+                baseline: None,
+                samples: 0,
+                errors: 0,
+                latency_sum: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// 等待一个并发许可
+    pub(crate) async fn acquire(&self) -> SemaphorePermit<'_> {
+        self.semaphore.acquire().await.expect("并发信号量不会被关闭")
+    }
+
+    /// 归还许可并记录本次请求的延迟和是否出错，窗口满时调整并发
+    pub(crate) fn release(&self, permit: SemaphorePermit<'_>, latency: Duration, failed: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.debt > 0 {
+            state.debt -= 1;
+            permit.forget();
+        } else {
+            drop(permit);
+        }
+
+        state.samples += 1;
+        state.latency_sum += latency;
+        if failed {
+            state.errors += 1;
+        }
+        if state.samples < WINDOW {
+            return;
+        }
+
+        let average = state.latency_sum / state.samples as u32;
+        let error_rate = state.errors as f64 / state.samples as f64;
+        state.samples = 0;
+        state.errors = 0;
+        state.latency_sum = Duration::ZERO;
+
+        let baseline = *state.baseline.get_or_insert(average);
+        if error_rate > MAX_ERROR_RATE || average.as_secs_f64() > baseline.as_secs_f64() * LATENCY_SPIKE {
+            let target = (state.limit / 2).max(1);
+            state.debt += state.limit - target;
+            state.limit = target;
+            state.slow_start = false;
+        } else if average.as_secs_f64() <= baseline.as_secs_f64() * LATENCY_HEADROOM && state.limit < self.max {
+            let step = if state.slow_start { state.limit } else { 1 };
+            let target = (state.limit + step).min(self.max);
+            let mut added = target - state.limit;
+            // 先抵消尚未回收的许可
+            let offset = added.min(state.debt);
+            state.debt -= offset;
+            added -= offset;
+            self.semaphore.add_permits(added);
+            state.limit = target;
+        }
+        if average < baseline {
+            state.baseline = Some(average);
+        }
+    }
+
+    /// 当前的并发上限
+    pub(crate) fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_threads_from_latency_within_cpu_ceiling() {
+        assert_eq!(threads_for(4, None), 10);
+        assert_eq!(threads_for(1, None), 8);
+        assert_eq!(threads_for(4, Some(Duration::from_millis(1))), MIN_THREADS);
+        assert_eq!(threads_for(4, Some(Duration::from_millis(100))), 20);
+        assert_eq!(threads_for(4, Some(Duration::from_secs(2))), 32);
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// 上一次扫描记录的缓存校验信息，用于发送条件请求
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Validators {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+/// 从之前的JSON或CSV结果文件读取每个URL的 `ETag`/`Last-Modified`，按扩展名判断格式
+pub(crate) fn load_validators(path: &Path) -> Result<HashMap<String, Validators>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("读取历史结果文件 {} 失败: {}", path.display(), e))?;
+    let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let entries = if is_csv { from_csv(&content)? } else { from_json(&content)? };
+    // 没有任何校验信息的URL无法发送条件请求
+    Ok(entries
+        .into_iter()
+        .filter(|(_, validators)| validators.etag.is_some() || validators.last_modified.is_some())
+        .collect())
+}
+
+fn from_json(content: &str) -> Result<HashMap<String, Validators>> {
+    let findings: Vec<Value> = serde_json::from_str(content)
+        .map_err(|e| anyhow!("历史结果文件不是有效的JSON数组: {}", e))?;
+    let field = |finding: &Value, key: &str| finding.get(key).and_then(Value::as_str).map(str::to_string);
+    Ok(findings
+        .iter()
+        .filter_map(|finding| {
+            let url = field(finding, "url")?;
+            Some((url, Validators {
+                etag: field(finding, "etag"),
+                last_modified: field(finding, "last_modified"),
+            }))
+        })
+        .collect())
+}
+
+fn from_csv(content: &str) -> Result<HashMap<String, Validators>> {
+    let mut lines = content.trim_start_matches('\u{feff}').lines();
+    let header = parse_csv_line(lines.next().unwrap_or_default());
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let url_column = column("url").ok_or_else(|| anyhow!("历史结果文件缺少 url 列"))?;
+    let (etag_column, modified_column) = (column("etag"), column("last_modified"));
+    if etag_column.is_none() && modified_column.is_none() {
+        return Err(anyhow!("历史结果文件缺少 etag/last_modified 列，请使用新版本重新生成"));
+    }
+    let value = |fields: &[String], index: Option<usize>| {
+        index.and_then(|i| fields.get(i)).filter(|value| !value.is_empty()).cloned()
+    };
+    Ok(lines
+        .map(parse_csv_line)
+        .filter_map(|fields| {
+            let url = fields.get(url_column)?.clone();
+            Some((url, Validators {
+                etag: value(&fields, etag_column),
+                last_modified: value(&fields, modified_column),
+            }))
+        })
+        .collect())
+}
+
+/// 解析一行CSV，支持双引号包裹的字段和加倍的引号
+pub(crate) fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
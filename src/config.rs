@@ -0,0 +1,1449 @@
+use std::collections::HashMap;
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use reqwest::header::HeaderName;
+use reqwest::Proxy;
+use serde_json::{json, Value};
+use url::{ParseError, Position, Url};
+
+use crate::codes::StatusCodes;
+use crate::csrf::{self, CsrfExtractor};
+use crate::output::OutputEncoding;
+use crate::severity::Severity;
+use crate::signing::SignTemplate;
+use crate::sqlite;
+use crate::state::DEFAULT_FILTER_THRESHOLD;
+use crate::template::OutputTemplate;
+use crate::unix;
+
+/// 校验目标URL，返回规范化后的URL字符串
+///
+/// IPv6 地址需要用方括号包裹，例如 `http://[::1]:8080/`
+pub fn validate_url(url_str: &str) -> Result<String> {
+    let url = Url::parse(url_str).map_err(|e| match e {
+        ParseError::InvalidIpv6Address => {
+            anyhow!("IPv6地址格式错误: '{}'，地址需用方括号完整包裹，例如 http://[::1]:8080/", url_str)
+        }
+        ParseError::InvalidPort | ParseError::EmptyHost if is_bare_ipv6(url_str) => {
+            anyhow!("URL中的IPv6地址必须用方括号包裹，例如 http://[::1]:8080/")
+        }
+        ParseError::InvalidPort => anyhow!("端口号无效：端口号必须在 0-65535 之间"),
+        e => anyhow!("URL格式错误: {}", e),
+    })?;
+    
+    if !url.scheme().starts_with("http") {
+        return Err(anyhow!("URL必须以 http:// 或 https:// 开头"));
+    }
+
+    if url.host_str().is_none() {
+        return Err(anyhow!("URL必须包含有效的主机名"));
+    }
+
+    Ok(url.to_string())
+}
+
+/// 把目标URL改到指定端口，返回规范化后的URL字符串
+///
+/// 指定了协议时使用该协议；否则443和8443端口使用https，80和8080端口使用http，其他端口保持原协议
+pub fn with_port(url_str: &str, port: u16, scheme: Option<&str>) -> Result<String> {
+    let mut url = Url::parse(&validate_url(url_str)?)?;
+    let scheme = match (scheme, port) {
+        (Some(scheme), _) => scheme,
+        (None, 443 | 8443) => "https",
+        (None, 80 | 8080) => "http",
+        (None, _) => url.scheme(),
+    }
+    .to_string();
+    if scheme != "http" && scheme != "https" {
+        return Err(anyhow!("不支持的协议 '{}'，只能是 http 或 https", scheme));
+    }
+    url.set_scheme(&scheme)
+        .map_err(|_| anyhow!("不支持的协议 '{}'，只能是 http 或 https", scheme))?;
+    url.set_port(Some(port))
+        .map_err(|_| anyhow!("无法为 {} 设置端口", url_str))?;
+    Ok(url.to_string())
+}
+
+/// 主机部分包含多个冒号却没有方括号，通常是忘记包裹的IPv6地址
+fn is_bare_ipv6(url_str: &str) -> bool {
+    let rest = url_str.split_once("://").map_or(url_str, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !authority.contains('[') && authority.matches(':').count() > 1
+}
+
+/// 主机是否匹配范围列表中的任一模式，列表为空时不限制
+pub(crate) fn host_in_scope(scope: &[String], url: &Url) -> bool {
+    if scope.is_empty() {
+        return true;
+    }
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    scope.iter().any(|pattern| {
+        let pattern = pattern.trim_start_matches('[').trim_end_matches(']');
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        }
+    })
+}
+
+/// 简单通配符匹配，`*` 匹配任意长度的任意字符，其余字符按原样匹配整个文本
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// 默认的敏感扩展名，命中且返回非空200时高亮报告
+pub const DEFAULT_SENSITIVE_EXTENSIONS: &[&str] = &["bak", "old", "swp", "~"];
+
+/// 请求模板中替换为当前字典项的占位符
+pub const WORD_PLACEHOLDER: &str = "%WORD%";
+
+/// 默认的URL长度上限，超过后许多服务器直接返回414
+pub const DEFAULT_MAX_URL_LENGTH: usize = 2048;
+
+/// 一次扫描的完整配置，通过 [`ScanConfig::builder`] 构建
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    pub(crate) base_url: Option<String>,
+    pub(crate) threads: usize,
+    pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) proxy: Option<String>,
+    pub(crate) insecure: bool,
+    pub(crate) bind_address: Option<IpAddr>,
+    pub(crate) resolve: Vec<(String, SocketAddr)>,
+    pub(crate) filter_file: Option<String>,
+    pub(crate) output_files: Vec<PathBuf>,
+    pub(crate) max_requests: Option<usize>,
+    pub(crate) max_findings: Option<usize>,
+    pub(crate) follow_redirects: bool,
+    pub(crate) max_redirects: usize,
+    pub(crate) decompress: bool,
+    pub(crate) outlier_threshold: Option<usize>,
+    pub(crate) interactive: bool,
+    pub(crate) sensitive_extensions: Vec<String>,
+    pub(crate) events_file: Option<PathBuf>,
+    pub(crate) trace_file: Option<PathBuf>,
+    pub(crate) randomize_headers: bool,
+    pub(crate) auto_concurrency: bool,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) head_only: bool,
+    pub(crate) compare_methods: bool,
+    pub(crate) sort_output: bool,
+    pub(crate) ua_per_connection: bool,
+    pub(crate) param_fuzz: bool,
+    pub(crate) smart_match: Option<usize>,
+    pub(crate) curl_file: Option<PathBuf>,
+    pub(crate) timeout_overrides: Vec<(String, Duration)>,
+    pub(crate) calibration_timeout: Option<Duration>,
+    pub(crate) allow_absolute: bool,
+    pub(crate) scope: Vec<String>,
+    pub(crate) filter_threshold: usize,
+    pub(crate) error_abort: Option<usize>,
+    pub(crate) per_host_threads: Option<usize>,
+    pub(crate) no_proxy_env: bool,
+    pub(crate) no_proxy: Vec<String>,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) data: Option<String>,
+    pub(crate) auto_upgrade: bool,
+    pub(crate) sni: Option<String>,
+    pub(crate) max_errors_per_path: usize,
+    pub(crate) collapse: bool,
+    pub(crate) output_encoding: OutputEncoding,
+    pub(crate) output_template: Option<OutputTemplate>,
+    pub(crate) conditional_file: Option<PathBuf>,
+    pub(crate) quiet: bool,
+    pub(crate) severity_tags: HashMap<String, Severity>,
+    pub(crate) min_severity: Option<Severity>,
+    pub(crate) max_body_size: Option<usize>,
+    pub(crate) skip_large: Option<usize>,
+    pub(crate) show_forbidden: bool,
+    pub(crate) save_filters: Option<PathBuf>,
+    pub(crate) append_output: bool,
+    pub(crate) follow_rate_limit: bool,
+    pub(crate) shuffle_headers: bool,
+    pub(crate) errors_file: Option<PathBuf>,
+    pub(crate) sqlite: Option<PathBuf>,
+    pub(crate) digest_auth: Option<(String, String)>,
+    pub(crate) unique_titles: bool,
+    pub(crate) max_per_ip: Option<usize>,
+    pub(crate) sign_secret: Option<String>,
+    pub(crate) sign_header: Option<String>,
+    pub(crate) sign_timestamp_header: Option<String>,
+    pub(crate) sign_template: Option<SignTemplate>,
+    pub(crate) login_url: Option<String>,
+    pub(crate) login_data: Option<String>,
+    pub(crate) login_success: Option<String>,
+    pub(crate) login_failure: Option<String>,
+    pub(crate) login_page: Option<String>,
+    pub(crate) csrf: Option<CsrfExtractor>,
+    pub(crate) csrf_field: Option<String>,
+    pub(crate) static_ua: bool,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) min_content_length: Option<usize>,
+    pub(crate) timestamps: bool,
+    pub(crate) on_finding: Option<String>,
+    pub(crate) webhook: Option<String>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) proxy_pool: Vec<String>,
+    pub(crate) proxy_check_url: Option<String>,
+    pub(crate) proxy_check_interval: Duration,
+    pub(crate) path_headers: HashMap<String, Vec<(String, String)>>,
+    pub(crate) tcp_nodelay: Option<bool>,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) cap_per_code: Option<usize>,
+    pub(crate) output_header: Vec<String>,
+    pub(crate) ordered_output: bool,
+    pub(crate) unix_socket: Option<PathBuf>,
+    pub(crate) max_url_length: usize,
+    pub(crate) recursion_depth: usize,
+    pub(crate) recursion_paths: Vec<String>,
+    pub(crate) match_codes: Option<StatusCodes>,
+    pub(crate) ca_certs: Vec<PathBuf>,
+    pub(crate) match_paths: Vec<String>,
+    pub(crate) filter_paths: Vec<String>,
+    pub(crate) delay_on_match: Option<Duration>,
+    pub(crate) pause_on_match: Vec<String>,
+}
+
+/// 字典项附带请求头的查找键，去掉首尾的斜杠
+fn path_key(path: &str) -> &str {
+    path.trim_matches('/')
+}
+
+impl ScanConfig {
+    /// 请求路径附带的请求头：`--add-slash` 派生的 `admin/` 与 `admin` 相同，
+    /// 递归扫描的子路径（如 `admin/x`）沿用最近的上级目录的请求头
+    pub(crate) fn headers_for_path(&self, path: &str) -> Option<&[(String, String)]> {
+        let mut key = path_key(path);
+        loop {
+            if let Some(extra) = self.path_headers.get(key) {
+                return Some(extra);
+            }
+            key = key.rsplit_once('/')?.0;
+        }
+    }
+
+    /// 判断URL路径是否以敏感扩展名结尾
+    pub(crate) fn is_sensitive_path(&self, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or_default().to_lowercase();
+        self.sensitive_extensions.iter().any(|ext| {
+            if ext.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+                name.ends_with(&format!(".{}", ext.to_lowercase()))
+            } else {
+                name.ends_with(ext.as_str())
+            }
+        })
+    }
+
+    /// 字典项的严重性：优先使用字典中的标签，否则按URL路径中的关键字推断
+    pub(crate) fn severity_for(&self, entry: &str, url: &Url) -> Severity {
+        let untagged = entry.trim_end_matches('/');
+        self.severity_tags
+            .get(entry)
+            .or_else(|| self.severity_tags.get(untagged))
+            .copied()
+            .unwrap_or_else(|| Severity::infer(url.path()))
+    }
+
+    /// 按 `--match-path`/`--filter-path` 判断是否请求该URL，只看路径和查询字符串
+    pub(crate) fn path_selected(&self, url: &Url) -> bool {
+        if self.match_paths.is_empty() && self.filter_paths.is_empty() {
+            return true;
+        }
+        let path = url[Position::BeforePath..].to_lowercase();
+        (self.match_paths.is_empty() || self.match_paths.iter().any(|text| path.contains(text.as_str())))
+            && !self.filter_paths.iter().any(|text| path.contains(text.as_str()))
+    }
+
+    /// 路径（含查询字符串）包含的第一个 `--pause-on-match` 子串，不区分大小写
+    pub(crate) fn pause_pattern(&self, url: &Url) -> Option<&str> {
+        if self.pause_on_match.is_empty() {
+            return None;
+        }
+        let path = url[Position::BeforePath..].to_lowercase();
+        self.pause_on_match.iter().find(|text| path.contains(text.as_str())).map(String::as_str)
+    }
+
+    /// 判断URL的主机是否在扫描范围内
+    pub(crate) fn in_scope(&self, url: &Url) -> bool {
+        host_in_scope(&self.scope, url)
+    }
+
+    /// 指定协议实际使用的代理：`--proxy` 优先于环境变量，`--no-proxy-env` 时忽略环境变量；
+    /// 使用代理池时为共享客户端所用的第一个代理
+    pub fn effective_proxy(&self, scheme: &str) -> Option<String> {
+        if self.proxy.is_some() {
+            return self.proxy.clone();
+        }
+        if let Some(first) = self.proxy_pool.first() {
+            return Some(first.clone());
+        }
+        if self.no_proxy_env {
+            return None;
+        }
+        // 与reqwest读取环境变量的顺序一致
+        let names: &[&str] = match scheme {
+            "https" => &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+            _ => &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+        };
+        names
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+    }
+
+    /// 不经过代理的主机：`--no-proxy` 指定的主机，使用环境变量中的代理时再加上 NO_PROXY
+    pub(crate) fn proxy_exclusions(&self) -> Vec<String> {
+        let mut hosts = self.no_proxy.clone();
+        if self.proxy.is_none() && !self.no_proxy_env {
+            if let Some(value) = ["NO_PROXY", "no_proxy"].iter().find_map(|name| env::var(name).ok()) {
+                hosts.extend(value.split(',').map(|host| host.trim().to_string()).filter(|host| !host.is_empty()));
+            }
+        }
+        hosts
+    }
+
+    /// 请求该URL时是否经过代理
+    pub(crate) fn proxied(&self, url: &Url) -> bool {
+        if self.unix_socket.is_some() || self.effective_proxy(url.scheme()).is_none() {
+            return false;
+        }
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+        !self.proxy_exclusions().iter().any(|entry| {
+            let entry = entry.trim_start_matches("*.").trim_start_matches('.').to_lowercase();
+            entry == "*" || host == entry || host.ends_with(&format!(".{}", entry))
+        })
+    }
+
+    /// 指定了SNI时https请求实际使用的URL：主机换成SNI名称，由解析器解析到原主机
+    pub(crate) fn sni_url(&self, url: &Url) -> Option<Url> {
+        let sni = self.sni.as_deref()?;
+        if url.scheme() != "https" || url.host_str() == Some(sni) {
+            return None;
+        }
+        let mut rewritten = url.clone();
+        rewritten.set_host(Some(sni)).ok()?;
+        Some(rewritten)
+    }
+
+    /// 是否跟随跳转；通过Unix域套接字扫描时总是停在3xx响应上
+    pub(crate) fn follows_redirects(&self) -> bool {
+        self.follow_redirects && self.unix_socket.is_none()
+    }
+
+    /// 请求头或请求体中是否含有 `%WORD%` 占位符
+    pub(crate) fn templated_request(&self) -> bool {
+        self.headers.iter().any(|(_, value)| value.contains(WORD_PLACEHOLDER))
+            || self.data.as_deref().is_some_and(|data| data.contains(WORD_PLACEHOLDER))
+    }
+
+    /// 字典项对应的请求总超时，命中 `timeout_overrides` 时使用其中的值
+    pub(crate) fn timeout_for(&self, entry: &str) -> Duration {
+        self.timeout_overrides
+            .iter()
+            .find(|(pattern, _)| wildcard_match(pattern, entry))
+            .map_or(self.timeout, |(_, timeout)| *timeout)
+    }
+
+    /// 创建配置构建器，各选项使用与命令行一致的默认值
+    pub fn builder() -> ScanConfigBuilder {
+        ScanConfigBuilder {
+            config: ScanConfig {
+                base_url: None,
+                threads: 10,
+                timeout: Duration::from_secs(10),
+                connect_timeout: Duration::from_secs(5),
+                proxy: None,
+                insecure: false,
+                bind_address: None,
+                resolve: Vec::new(),
+                filter_file: None,
+                output_files: Vec::new(),
+                max_requests: None,
+                max_findings: None,
+                follow_redirects: true,
+                max_redirects: 10,
+                decompress: true,
+                outlier_threshold: None,
+                interactive: true,
+                sensitive_extensions: DEFAULT_SENSITIVE_EXTENSIONS.iter().map(|ext| ext.to_string()).collect(),
+                events_file: None,
+                trace_file: None,
+                randomize_headers: false,
+                auto_concurrency: false,
+                pool_max_idle_per_host: None,
+                pool_idle_timeout: None,
+                head_only: false,
+                compare_methods: false,
+                sort_output: false,
+                ua_per_connection: false,
+                param_fuzz: false,
+                smart_match: None,
+                curl_file: None,
+                timeout_overrides: Vec::new(),
+                calibration_timeout: None,
+                allow_absolute: false,
+                scope: Vec::new(),
+                filter_threshold: DEFAULT_FILTER_THRESHOLD,
+                error_abort: None,
+                per_host_threads: None,
+                no_proxy_env: false,
+                no_proxy: Vec::new(),
+                headers: Vec::new(),
+                data: None,
+                auto_upgrade: false,
+                sni: None,
+                max_errors_per_path: 1,
+                collapse: false,
+                output_encoding: OutputEncoding::default(),
+                output_template: None,
+                conditional_file: None,
+                quiet: false,
+                severity_tags: HashMap::new(),
+                min_severity: None,
+                max_body_size: None,
+                skip_large: None,
+                show_forbidden: false,
+                save_filters: None,
+                append_output: false,
+                follow_rate_limit: false,
+                shuffle_headers: false,
+                errors_file: None,
+                sqlite: None,
+                digest_auth: None,
+                unique_titles: false,
+                max_per_ip: None,
+                sign_secret: None,
+                sign_header: None,
+                sign_timestamp_header: None,
+                sign_template: None,
+                login_url: None,
+                login_data: None,
+                login_success: None,
+                login_failure: None,
+                login_page: None,
+                csrf: None,
+                csrf_field: None,
+                static_ua: false,
+                user_agent: None,
+                min_content_length: None,
+                timestamps: false,
+                on_finding: None,
+                webhook: None,
+                seed: None,
+                proxy_pool: Vec::new(),
+                proxy_check_url: None,
+                proxy_check_interval: Duration::from_secs(30),
+                path_headers: HashMap::new(),
+                tcp_nodelay: None,
+                tcp_keepalive: None,
+                cap_per_code: None,
+                output_header: Vec::new(),
+                ordered_output: false,
+                unix_socket: None,
+                max_url_length: DEFAULT_MAX_URL_LENGTH,
+                recursion_depth: 0,
+                recursion_paths: Vec::new(),
+                match_codes: None,
+                ca_certs: Vec::new(),
+                match_paths: Vec::new(),
+                filter_paths: Vec::new(),
+                delay_on_match: None,
+                pause_on_match: Vec::new(),
+            },
+        }
+    }
+
+    /// 主要配置项的JSON形式，用于事件流中的 `start` 事件
+    pub fn to_json(&self) -> Value {
+        // 分段构造，字段过多时 json! 宏的展开会超出默认的递归深度
+        let mut value = json!({
+            "url": self.base_url,
+            "threads": self.threads,
+            "timeout_ms": self.timeout.as_millis() as u64,
+            "connect_timeout_ms": self.connect_timeout.as_millis() as u64,
+            "proxy": self.proxy,
+            "insecure": self.insecure,
+            "bind_address": self.bind_address.map(|addr| addr.to_string()),
+            "filter_file": self.filter_file,
+            "output_files": self.output_files,
+            "max_requests": self.max_requests,
+            "max_findings": self.max_findings,
+            "follow_redirects": self.follow_redirects,
+            "max_redirects": self.max_redirects,
+            "decompress": self.decompress,
+            "outlier_threshold": self.outlier_threshold,
+            "sensitive_extensions": self.sensitive_extensions,
+            "randomize_headers": self.randomize_headers,
+            "auto_concurrency": self.auto_concurrency,
+            "pool_max_idle_per_host": self.pool_max_idle_per_host,
+            "pool_idle_timeout_ms": self.pool_idle_timeout.map(|timeout| timeout.as_millis() as u64),
+            "head_only": self.head_only,
+            "compare_methods": self.compare_methods,
+            "sort_output": self.sort_output,
+            "ua_per_connection": self.ua_per_connection,
+            "param_fuzz": self.param_fuzz,
+            "smart_match": self.smart_match,
+            "calibration_timeout_ms": self.calibration_timeout.map(|timeout| timeout.as_millis() as u64),
+            "allow_absolute": self.allow_absolute,
+            "scope": self.scope,
+            "filter_threshold": self.filter_threshold,
+            "error_abort": self.error_abort,
+            "per_host_threads": self.per_host_threads,
+            "no_proxy_env": self.no_proxy_env,
+            "no_proxy": self.no_proxy,
+        });
+        let rest = json!({
+            "headers": self.headers.iter().map(|(name, value)| format!("{}: {}", name, value)).collect::<Vec<_>>(),
+            "data": self.data,
+            "auto_upgrade": self.auto_upgrade,
+            "sni": self.sni,
+            "max_errors_per_path": self.max_errors_per_path,
+            "collapse": self.collapse,
+            "output_encoding": self.output_encoding.to_string(),
+            "output_template": self.output_template.as_ref().map(ToString::to_string),
+            "conditional_file": self.conditional_file,
+            "quiet": self.quiet,
+            "severity_tags": self.severity_tags.len(),
+            "min_severity": self.min_severity.map(|severity| severity.to_string()),
+            "max_body_size": self.max_body_size,
+            "skip_large": self.skip_large,
+            "show_forbidden": self.show_forbidden,
+            "save_filters": self.save_filters,
+            "append_output": self.append_output,
+            "follow_rate_limit": self.follow_rate_limit,
+            "shuffle_headers": self.shuffle_headers,
+            "errors_file": self.errors_file,
+            "sqlite": self.sqlite,
+            "digest_auth_user": self.digest_auth.as_ref().map(|(username, _)| username),
+            "unique_titles": self.unique_titles,
+            "max_per_ip": self.max_per_ip,
+            "request_signing": self.sign_secret.is_some(),
+            "sign_header": self.sign_header,
+            "sign_timestamp_header": self.sign_timestamp_header,
+            "sign_template": self.sign_template.as_ref().map(ToString::to_string),
+        });
+        let login = json!({
+            "login_url": self.login_url,
+            "login_success": self.login_success,
+            "login_failure": self.login_failure,
+            "login_page": self.login_page,
+            "csrf": self.csrf.as_ref().map(|extractor| format!("{:?}", extractor)),
+            "csrf_field": self.csrf_field,
+            "static_ua": self.static_ua,
+            "user_agent": self.user_agent,
+            "min_content_length": self.min_content_length,
+            "timestamps": self.timestamps,
+            "on_finding": self.on_finding,
+            "webhook": self.webhook,
+            "seed": self.seed,
+            "proxy_pool": self.proxy_pool.len(),
+            "proxy_check_url": self.proxy_check_url,
+            "proxy_check_interval_ms": self.proxy_check_interval.as_millis() as u64,
+            "path_headers": self.path_headers.len(),
+            "tcp_nodelay": self.tcp_nodelay,
+            "tcp_keepalive": self.tcp_keepalive.map(|interval| interval.as_secs_f64()),
+            "cap_per_code": self.cap_per_code,
+            "ordered_output": self.ordered_output,
+            "unix_socket": self.unix_socket.as_ref().map(|path| path.display().to_string()),
+            "max_url_length": self.max_url_length,
+            "recursion_depth": self.recursion_depth,
+            "match_codes": self.match_codes.as_ref().map(ToString::to_string),
+            "ca_certs": self.ca_certs.iter().map(|path| path.display().to_string()).collect::<Vec<_>>(),
+            "match_paths": self.match_paths,
+            "filter_paths": self.filter_paths,
+            "delay_on_match": self.delay_on_match.map(|delay| delay.as_secs_f64()),
+            "pause_on_match": self.pause_on_match,
+        });
+        if let Value::Object(fields) = &mut value {
+            for part in [rest, login] {
+                if let Value::Object(part) = part {
+                    fields.extend(part);
+                }
+            }
+        }
+        value
+    }
+
+    /// 校验后的目标URL，多目标模式下为 `None`
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+}
+
+/// [`ScanConfig`] 的构建器
+#[derive(Debug, Clone)]
+pub struct ScanConfigBuilder {
+    config: ScanConfig,
+}
+
+impl ScanConfigBuilder {
+    /// 目标URL，使用 [`Scanner::run_targets`](crate::Scanner::run_targets) 扫描多个目标时可不设置
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.config.base_url = Some(url.into());
+        self
+    }
+
+    /// 并发请求数
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        self
+    }
+
+    /// 请求总超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    /// 连接超时时间
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    /// 代理服务器地址，支持 http/https/socks5
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.config.proxy = Some(proxy.into());
+        self
+    }
+
+    /// 使用多个代理轮流发送请求，并定期检查各代理是否可用（不能与 `proxy` 同时使用）
+    pub fn proxy_pool(mut self, proxies: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.config.proxy_pool = proxies.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 代理池健康检查请求的地址，默认为目标URL
+    pub fn proxy_check_url(mut self, url: impl Into<String>) -> Self {
+        self.config.proxy_check_url = Some(url.into());
+        self
+    }
+
+    /// 代理池健康检查的间隔
+    pub fn proxy_check_interval(mut self, interval: Duration) -> Self {
+        self.config.proxy_check_interval = interval;
+        self
+    }
+
+    /// 是否跳过SSL证书验证
+    pub fn insecure(mut self, insecure: bool) -> Self {
+        self.config.insecure = insecure;
+        self
+    }
+
+    /// 绑定本地出口地址
+    pub fn bind_address(mut self, addr: IpAddr) -> Self {
+        self.config.bind_address = Some(addr);
+        self
+    }
+
+    /// 将主机名固定解析到指定地址，可多次调用
+    pub fn resolve(mut self, host: impl Into<String>, addr: SocketAddr) -> Self {
+        self.config.resolve.push((host.into(), addr));
+        self
+    }
+
+    /// 误报过滤规则文件
+    pub fn filter_file(mut self, path: impl Into<String>) -> Self {
+        self.config.filter_file = Some(path.into());
+        self
+    }
+
+    /// 添加结果输出文件，格式按扩展名推断（`.json`、`.csv`，其余为纯文本），可多次调用；不设置则不写文件
+    pub fn output_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.output_files.push(path.into());
+        self
+    }
+
+    /// 最多发送的请求数，达到后停止派发新请求
+    pub fn max_requests(mut self, max: usize) -> Self {
+        self.config.max_requests = Some(max);
+        self
+    }
+
+    /// 最多记录的发现数，达到后停止派发新请求
+    pub fn max_findings(mut self, max: usize) -> Self {
+        self.config.max_findings = Some(max);
+        self
+    }
+
+    /// 是否自动跟随跳转（默认跟随），不跟随时3xx响应会作为发现报告
+    pub fn follow_redirects(mut self, follow: bool) -> Self {
+        self.config.follow_redirects = follow;
+        self
+    }
+
+    /// 跟随跳转时的最大跳转次数，超过后停止并标记该结果
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.config.max_redirects = max;
+        self
+    }
+
+    /// 是否自动解压 gzip/deflate/brotli 响应，关闭后内容过滤作用于原始响应体
+    pub fn decompress(mut self, decompress: bool) -> Self {
+        self.config.decompress = decompress;
+        self
+    }
+
+    /// 启用异常值模式：响应大小与主导大小相差超过 `threshold` 字节时高亮报告，不论状态码
+    pub fn outlier_threshold(mut self, threshold: usize) -> Self {
+        self.config.outlier_threshold = Some(threshold);
+        self
+    }
+
+    /// 遇到需要确认的过滤时是否询问用户，关闭后自动确认
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.config.interactive = interactive;
+        self
+    }
+
+    /// 敏感扩展名列表，字母数字开头的按 `.扩展名` 匹配，其余（如 `~`）按后缀匹配
+    pub fn sensitive_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.sensitive_extensions = extensions.into_iter()
+            .map(Into::into)
+            .map(|ext: String| ext.trim().trim_start_matches('.').to_string())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        self
+    }
+
+    /// 将扫描生命周期事件以NDJSON写入文件
+    pub fn events_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.events_file = Some(path.into());
+        self
+    }
+
+    /// 将每个请求的方法、URL、UA、状态码、大小、耗时和过滤判定写入跟踪文件
+    pub fn trace_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.trace_file = Some(path.into());
+        self
+    }
+
+    /// 每个请求随机生成与 User-Agent 风格一致的 Accept、Accept-Language 和 Accept-Encoding
+    pub fn randomize_headers(mut self, randomize: bool) -> Self {
+        self.config.randomize_headers = randomize;
+        self
+    }
+
+    /// 根据延迟和出错率自动调整并发，`threads` 作为上限
+    pub fn auto_concurrency(mut self, auto: bool) -> Self {
+        self.config.auto_concurrency = auto;
+        self
+    }
+
+    /// 每个主机保留的最大空闲连接数，高并发扫描单个主机时可调大以提高连接复用
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.config.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// 空闲连接在连接池中保留的时长
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// 使用HEAD请求且不下载响应体，大小取自 `Content-Length`，此时内容特征过滤不可用
+    pub fn head_only(mut self, head_only: bool) -> Self {
+        self.config.head_only = head_only;
+        self
+    }
+
+    /// 每个路径额外用 GET、HEAD、POST、OPTIONS 各请求一次，报告状态码因方法而不同的路径
+    pub fn compare_methods(mut self, compare_methods: bool) -> Self {
+        self.config.compare_methods = compare_methods;
+        self
+    }
+
+    /// 扫描结束时按URL排序并去除重复行后再写入输出文件，扫描过程中结果缓存在内存
+    pub fn sort_output(mut self, sort: bool) -> Self {
+        self.config.sort_output = sort;
+        self
+    }
+
+    /// 为每个并发槽位固定一个UA并使用独立的连接，同一连接上不再切换UA
+    pub fn ua_per_connection(mut self, pinned: bool) -> Self {
+        self.config.ua_per_connection = pinned;
+        self
+    }
+
+    /// 参数模式：字典项作为参数名，以 `?名称=test` 追加到目标URL，而不是拼接为路径
+    pub fn param_fuzz(mut self, param_fuzz: bool) -> Self {
+        self.config.param_fuzz = param_fuzz;
+        self
+    }
+
+    /// 智能匹配模式：扫描前对每个目标请求随机路径建立404基线，状态码不同或大小相差超过 `threshold` 字节的响应
+    /// 即视为发现，不论状态码，适用于对任何路径都返回200的服务器
+    pub fn smart_match(mut self, threshold: usize) -> Self {
+        self.config.smart_match = Some(threshold);
+        self
+    }
+
+    /// 为每条有效发现写入等价的 curl 命令（包含UA、请求头、代理和证书校验等设置），便于手工复核
+    pub fn curl_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.curl_file = Some(path.into());
+        self
+    }
+
+    /// 为匹配通配符模式（`*` 匹配任意字符）的字典项单独设置请求总超时，先添加的规则优先
+    pub fn timeout_override(mut self, pattern: impl Into<String>, timeout: Duration) -> Self {
+        self.config.timeout_overrides.push((pattern.into(), timeout));
+        self
+    }
+
+    /// 基线校准探测的超时时间，未设置时与请求超时相同
+    pub fn calibration_timeout(mut self, timeout: Duration) -> Self {
+        self.config.calibration_timeout = Some(timeout);
+        self
+    }
+
+    /// 允许字典中的完整URL按原样扫描，默认拒绝以免请求发往其他主机
+    pub fn allow_absolute(mut self, allow: bool) -> Self {
+        self.config.allow_absolute = allow;
+        self
+    }
+
+    /// 添加允许扫描的主机，`*.example.com` 匹配所有子域名；未添加任何主机时不限制
+    pub fn scope(mut self, host: impl Into<String>) -> Self {
+        let host: String = host.into();
+        self.config.scope.push(host.trim().trim_end_matches('.').to_lowercase());
+        self
+    }
+
+    /// 相同响应大小出现多少次后询问是否将其过滤，调高可避免误过滤常见的页面大小，调低则更快识别WAF拦截页
+    pub fn filter_threshold(mut self, threshold: usize) -> Self {
+        self.config.filter_threshold = threshold;
+        self
+    }
+
+    /// 连续 `limit` 个请求出现网络错误时询问是否中止该目标，非交互模式下直接中止
+    pub fn error_abort(mut self, limit: usize) -> Self {
+        self.config.error_abort = Some(limit);
+        self
+    }
+
+    /// 按主机限制并发：每个主机最多 `threads` 个并发请求，多个目标交替推进，全局并发仍不超过总线程数
+    pub fn per_host_threads(mut self, threads: usize) -> Self {
+        self.config.per_host_threads = Some(threads);
+        self
+    }
+
+    /// 是否忽略环境变量（HTTP_PROXY/HTTPS_PROXY/ALL_PROXY）中的代理
+    pub fn no_proxy_env(mut self, ignore: bool) -> Self {
+        self.config.no_proxy_env = ignore;
+        self
+    }
+
+    /// 不经过代理直接连接的主机，匹配主机本身及其子域名，`*` 表示所有主机
+    pub fn no_proxy(mut self, host: impl Into<String>) -> Self {
+        self.config.no_proxy.push(host.into());
+        self
+    }
+
+    /// 附加到每个请求的请求头，值中的 `%WORD%` 会替换为当前字典项
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// 请求体，设置后使用POST请求，其中的 `%WORD%` 会替换为当前字典项
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.config.data = Some(data.into());
+        self
+    }
+
+    /// 目标把http请求统一跳转到https时，不经询问直接改用https扫描
+    pub fn auto_upgrade(mut self, enabled: bool) -> Self {
+        self.config.auto_upgrade = enabled;
+        self
+    }
+
+    /// TLS握手时使用的服务器名称（SNI），连接仍发往目标主机，Host头保持不变
+    pub fn sni(mut self, name: impl Into<String>) -> Self {
+        self.config.sni = Some(name.into());
+        self
+    }
+
+    /// 同一路径因网络错误失败多少次后放弃，默认1即不重试
+    pub fn max_errors_per_path(mut self, max: usize) -> Self {
+        self.config.max_errors_per_path = max;
+        self
+    }
+
+    /// 折叠模式：终端中不逐条打印被过滤的结果，只按响应大小原地更新计数
+    pub fn collapse(mut self, enabled: bool) -> Self {
+        self.config.collapse = enabled;
+        self
+    }
+
+    /// 文本和CSV输出文件的编码
+    pub fn output_encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.config.output_encoding = encoding;
+        self
+    }
+
+    /// 控制台和文本输出文件中每条发现的行格式，替代默认的 `[+] 发现: ...` 格式
+    pub fn output_template(mut self, template: OutputTemplate) -> Self {
+        self.config.output_template = Some(template);
+        self
+    }
+
+    /// 之前扫描的JSON/CSV结果文件，对其中记录了 `ETag`/`Last-Modified` 的URL发送条件请求，
+    /// 返回304的视为未修改
+    pub fn conditional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.conditional_file = Some(path.into());
+        self
+    }
+
+    /// 不在终端逐条打印发现和请求错误，由界面等通过 [`Scanner::stats`] 展示
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.config.quiet = quiet;
+        self
+    }
+
+    /// 设置字典项的严重性标签
+    pub fn severity_tags(mut self, tags: HashMap<String, Severity>) -> Self {
+        self.config.severity_tags = tags;
+        self
+    }
+
+    /// 低于该严重性的发现视为过滤，不写入输出文件
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.config.min_severity = Some(severity);
+        self
+    }
+
+    /// 每个响应最多读取的字节数，超出部分不再读取，大小只计已读取的部分
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.config.max_body_size = Some(bytes);
+        self
+    }
+
+    /// `Content-Length` 或已读取的字节数超过该值时不再读取响应体，发现仍然报告并标记为大文件
+    pub fn skip_large(mut self, bytes: usize) -> Self {
+        self.config.skip_large = Some(bytes);
+        self
+    }
+
+    /// 把401/403作为受保护的资源报告，与2xx发现分开统计
+    pub fn show_forbidden(mut self, show: bool) -> Self {
+        self.config.show_forbidden = show;
+        self
+    }
+
+    /// 扫描结束时把内容特征和确认过滤的响应大小保存到文件，之后可用 `filter_file` 读回
+    pub fn save_filters(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.save_filters = Some(path.into());
+        self
+    }
+
+    /// 追加到已有的输出文件而不是覆盖
+    pub fn append_output(mut self, append: bool) -> Self {
+        self.config.append_output = append;
+        self
+    }
+
+    /// 是否按服务器通告的 `RateLimit-Remaining`/`RateLimit-Reset` 以及429/503响应的 `Retry-After` 放慢请求
+    ///
+    /// 库调用默认关闭；命令行默认开启，`--ignore-rate-limit` 时关闭
+    pub fn follow_rate_limit(mut self, follow: bool) -> Self {
+        self.config.follow_rate_limit = follow;
+        self
+    }
+
+    /// 每个请求随机打乱请求头的发送顺序
+    pub fn shuffle_headers(mut self, shuffle: bool) -> Self {
+        self.config.shuffle_headers = shuffle;
+        self
+    }
+
+    /// 扫描结束时把出错的路径（URL、字典项、类别、错误信息）以JSON数组写入文件
+    pub fn errors_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.errors_file = Some(path.into());
+        self
+    }
+
+    /// 使用HTTP Digest认证，收到质询后计算认证头重新请求，之后同一主机的请求直接带上认证头
+    pub fn digest_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.config.digest_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// 用该密钥对每个请求计算 HMAC-SHA256 签名，放入签名头（默认 `X-Signature`）
+    pub fn sign_secret(mut self, secret: impl Into<String>) -> Self {
+        self.config.sign_secret = Some(secret.into());
+        self
+    }
+
+    /// 存放请求签名的请求头名称
+    pub fn sign_header(mut self, name: impl Into<String>) -> Self {
+        self.config.sign_header = Some(name.into());
+        self
+    }
+
+    /// 签名模板用到 `{timestamp}` 时，存放时间戳的请求头名称（默认 `X-Timestamp`）
+    pub fn sign_timestamp_header(mut self, name: impl Into<String>) -> Self {
+        self.config.sign_timestamp_header = Some(name.into());
+        self
+    }
+
+    /// 待签名内容的模板，默认为方法、路径和时间戳各占一行
+    pub fn sign_template(mut self, template: SignTemplate) -> Self {
+        self.config.sign_template = Some(template);
+        self
+    }
+
+    /// 把本次扫描和有效发现写入SQLite数据库（需使用 sqlite 特性编译），数据库已存在时追加新的扫描记录
+    pub fn sqlite(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.sqlite = Some(path.into());
+        self
+    }
+
+    /// 扫描前向该地址提交登录表单，之后的请求带上获得的会话Cookie
+    pub fn login(mut self, url: impl Into<String>, data: impl Into<String>) -> Self {
+        self.config.login_url = Some(url.into());
+        self.config.login_data = Some(data.into());
+        self
+    }
+
+    /// 登录响应内容或跳转地址中出现该文本时视为登录成功
+    pub fn login_success(mut self, text: impl Into<String>) -> Self {
+        self.config.login_success = Some(text.into());
+        self
+    }
+
+    /// 登录响应内容中出现该文本时视为登录失败
+    pub fn login_failure(mut self, text: impl Into<String>) -> Self {
+        self.config.login_failure = Some(text.into());
+        self
+    }
+
+    /// 提交登录表单前先请求登录页，从中提取CSRF令牌加入表单
+    pub fn csrf(mut self, extractor: CsrfExtractor) -> Self {
+        self.config.csrf = Some(extractor);
+        self
+    }
+
+    /// CSRF令牌在登录表单中的字段名，默认取选择器匹配元素的 `name` 属性
+    pub fn csrf_field(mut self, name: impl Into<String>) -> Self {
+        self.config.csrf_field = Some(name.into());
+        self
+    }
+
+    /// 提取CSRF令牌的登录页地址，默认与登录地址相同
+    pub fn login_page(mut self, url: impl Into<String>) -> Self {
+        self.config.login_page = Some(url.into());
+        self
+    }
+
+    /// 扫描开始时选定一个UA并设置在客户端上，整个扫描期间不再变化
+    pub fn static_ua(mut self, static_ua: bool) -> Self {
+        self.config.static_ua = static_ua;
+        self
+    }
+
+    /// 使用指定的UA代替随机UA，隐含 `static_ua`
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// 主机解析到多个地址时，每个后端地址上同时进行的请求不超过该数
+    pub fn max_per_ip(mut self, limit: usize) -> Self {
+        self.config.max_per_ip = Some(limit);
+        self
+    }
+
+    /// 每个目标上同一标题（忽略大小写和空白差异）只报告第一条发现，之后的视为过滤
+    pub fn unique_titles(mut self, enabled: bool) -> Self {
+        self.config.unique_titles = enabled;
+        self
+    }
+
+    /// 2xx响应小于该字节数时过滤，用于隐藏空白或几乎为空的页面
+    pub fn min_content_length(mut self, bytes: usize) -> Self {
+        self.config.min_content_length = Some(bytes);
+        self
+    }
+
+    /// 文本输出文件的每行末尾附上收到响应的时间（JSON和CSV输出总是包含时间）
+    pub fn timestamps(mut self, timestamps: bool) -> Self {
+        self.config.timestamps = timestamps;
+        self
+    }
+
+    /// 每条有效发现都在后台执行一次该shell命令，URL、状态码、大小通过参数和环境变量传入
+    pub fn on_finding(mut self, command: impl Into<String>) -> Self {
+        self.config.on_finding = Some(command.into());
+        self
+    }
+
+    /// 固定随机种子，使UA、校准路径、请求头等随机选择可以复现
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// 有效发现在后台批量POST到该地址（JSON），失败时重试，不阻塞扫描
+    pub fn webhook(mut self, url: impl Into<String>) -> Self {
+        self.config.webhook = Some(url.into());
+        self
+    }
+
+    /// 设置只在请求对应字典项时附加的请求头，字典项首尾的斜杠不影响匹配
+    pub fn path_headers(mut self, headers: HashMap<String, Vec<(String, String)>>) -> Self {
+        self.config.path_headers.clear();
+        for (path, extra) in headers {
+            self.config.path_headers.entry(path_key(&path).to_string()).or_default().extend(extra);
+        }
+        self
+    }
+
+    /// 是否在连接上设置 TCP_NODELAY（关闭Nagle算法），未设置时沿用HTTP客户端的默认值（开启）
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.config.tcp_nodelay = Some(nodelay);
+        self
+    }
+
+    /// 启用TCP keepalive，连接空闲这么久后开始发送探测包
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.config.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// 每个状态码最多写入输出文件的发现数，超出的发现仍会计数
+    pub fn cap_per_code(mut self, cap: usize) -> Self {
+        self.config.cap_per_code = Some(cap);
+        self
+    }
+
+    /// 设置写在文本输出文件开头的说明行（不含 `#` 前缀），记录本次扫描的参数
+    pub fn output_header(mut self, lines: Vec<String>) -> Self {
+        self.config.output_header = lines;
+        self
+    }
+
+    /// 扫描结束后按字典顺序写入输出文件，而不是按完成顺序边扫描边写入
+    pub fn ordered_output(mut self, ordered: bool) -> Self {
+        self.config.ordered_output = ordered;
+        self
+    }
+
+    /// 通过Unix域套接字发送请求，目标URL只用于Host头和请求路径，必须使用http
+    pub fn unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.unix_socket = Some(path.into());
+        self
+    }
+
+    /// 拼接后的URL超过该长度（字节）时跳过请求，0表示不限制
+    pub fn max_url_length(mut self, max: usize) -> Self {
+        self.config.max_url_length = max;
+        self
+    }
+
+    /// 递归扫描发现的子目录的最大层数，0表示不递归
+    pub fn recursion_depth(mut self, depth: usize) -> Self {
+        self.config.recursion_depth = depth;
+        self
+    }
+
+    /// 递归进入子目录时使用的字典，为空时沿用主字典
+    pub fn recursion_paths(mut self, paths: Vec<String>) -> Self {
+        self.config.recursion_paths = paths;
+        self
+    }
+
+    /// 只报告匹配这些状态码的响应，替代默认的2xx（及 `show_forbidden` 时的401/403）和跳转
+    pub fn match_codes(mut self, codes: StatusCodes) -> Self {
+        self.config.match_codes = Some(codes);
+        self
+    }
+
+    /// 额外信任的CA证书（PEM文件，可含多个证书），可多次调用，用于验证内部PKI签发的证书
+    pub fn ca_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.ca_certs.push(path.into());
+        self
+    }
+
+    /// 只请求路径（含查询字符串）包含该子串的URL，不区分大小写，可多次调用，满足任意一个即可
+    pub fn match_path(mut self, text: impl Into<String>) -> Self {
+        self.config.match_paths.push(text.into().to_lowercase());
+        self
+    }
+
+    /// 不请求路径（含查询字符串）包含该子串的URL，不区分大小写，可多次调用，包含任意一个即跳过
+    pub fn filter_path(mut self, text: impl Into<String>) -> Self {
+        self.config.filter_paths.push(text.into().to_lowercase());
+        self
+    }
+
+    /// 每个有效发现之后暂缓派发新请求这么久，在途请求照常完成
+    pub fn delay_on_match(mut self, delay: Duration) -> Self {
+        self.config.delay_on_match = Some(delay);
+        self
+    }
+
+    /// 有效发现的路径（含查询字符串）包含该子串时暂停扫描，不区分大小写，可多次调用
+    ///
+    /// 交互模式下在终端询问后继续，否则需要通过 [`Scanner::pause_control`](crate::Scanner::pause_control) 继续
+    pub fn pause_on_match(mut self, text: impl Into<String>) -> Self {
+        self.config.pause_on_match.push(text.into().to_lowercase());
+        self
+    }
+
+    /// 校验并生成配置
+    pub fn build(mut self) -> Result<ScanConfig> {
+        if let Some(ref url) = self.config.base_url {
+            self.config.base_url = Some(validate_url(url)?);
+        }
+        if self.config.threads == 0 {
+            return Err(anyhow!("并发线程数必须大于0"));
+        }
+        if self.config.per_host_threads == Some(0) {
+            return Err(anyhow!("每主机并发数必须大于0"));
+        }
+        if self.config.error_abort == Some(0) {
+            return Err(anyhow!("连续出错上限必须大于0"));
+        }
+        if self.config.max_errors_per_path == 0 {
+            return Err(anyhow!("每个路径的出错上限必须大于0"));
+        }
+        if let Some(ref url) = self.config.login_url {
+            self.config.login_url = Some(validate_url(url).map_err(|e| anyhow!("登录地址无效: {}", e))?);
+        }
+        if !self.config.proxy_pool.is_empty() {
+            if self.config.proxy.is_some() {
+                return Err(anyhow!("代理池不能与单个代理同时使用"));
+            }
+            if self.config.unix_socket.is_some() || self.config.max_per_ip.is_some() || self.config.ua_per_connection {
+                return Err(anyhow!("代理池不能与Unix域套接字、每地址并发上限或每连接固定UA同时使用"));
+            }
+            for proxy in &self.config.proxy_pool {
+                Proxy::all(proxy).map_err(|e| anyhow!("代理池中的代理 '{}' 无效: {}", proxy, e))?;
+            }
+        }
+        if let Some(ref url) = self.config.proxy_check_url {
+            self.config.proxy_check_url = Some(validate_url(url).map_err(|e| anyhow!("代理检查地址无效: {}", e))?);
+        }
+        if self.config.proxy_check_interval.is_zero() {
+            return Err(anyhow!("代理检查间隔必须大于0"));
+        }
+        if let Some(ref url) = self.config.webhook {
+            self.config.webhook = Some(validate_url(url).map_err(|e| anyhow!("webhook地址无效: {}", e))?);
+        }
+        if let Some(ref url) = self.config.login_page {
+            self.config.login_page = Some(validate_url(url).map_err(|e| anyhow!("登录页地址无效: {}", e))?);
+        }
+        if self.config.sqlite.is_some() {
+            sqlite::check_available()?;
+        }
+        if let Some(ref extractor) = self.config.csrf {
+            if self.config.login_url.is_none() {
+                return Err(anyhow!("提取CSRF令牌需要同时设置登录地址"));
+            }
+            csrf::validate(extractor)?;
+            if matches!(extractor, CsrfExtractor::Regex(_)) && self.config.csrf_field.is_none() {
+                return Err(anyhow!("使用正则表达式提取CSRF令牌时需要指定字段名"));
+            }
+        }
+        if self.config.max_body_size == Some(0) {
+            return Err(anyhow!("响应体大小上限必须大于0"));
+        }
+        if self.config.max_per_ip == Some(0) {
+            return Err(anyhow!("每个地址的并发数必须大于0"));
+        }
+        if self.config.max_per_ip.is_some() && self.config.unix_socket.is_some() {
+            return Err(anyhow!("通过Unix域套接字扫描时不能限制每个地址的并发"));
+        }
+        if self.config.max_per_ip.is_some() {
+            // 经过代理时由代理连接目标，固定的地址不起作用
+            let proxied = match self.config.base_url.as_deref().map(Url::parse) {
+                Some(Ok(url)) => self.config.proxied(&url),
+                _ => ["http", "https"].iter().any(|scheme| self.config.effective_proxy(scheme).is_some()),
+            };
+            if proxied {
+                return Err(anyhow!("请求经过代理时不能限制每个地址的并发（环境变量中的代理可用 --no-proxy-env 忽略）"));
+            }
+        }
+        if self.config.sign_secret.is_none()
+            && (self.config.sign_header.is_some() || self.config.sign_timestamp_header.is_some() || self.config.sign_template.is_some())
+        {
+            return Err(anyhow!("设置签名头或签名模板时需要同时指定签名密钥"));
+        }
+        for name in [&self.config.sign_header, &self.config.sign_timestamp_header].into_iter().flatten() {
+            HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| anyhow!("签名请求头名称无效: '{}'", name))?;
+        }
+        if self.config.skip_large == Some(0) {
+            return Err(anyhow!("大文件阈值必须大于0"));
+        }
+        if self.config.filter_threshold == 0 {
+            return Err(anyhow!("过滤阈值必须大于0"));
+        }
+        for (name, _) in &self.config.headers {
+            HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| anyhow!("请求头名称无效: '{}'", name))?;
+        }
+        if self.config.sni.is_some() {
+            if self.config.base_url.is_none() {
+                return Err(anyhow!("自定义SNI只能用于单个目标，请通过 --url 指定"));
+            }
+            if self.config.proxy.is_some() {
+                return Err(anyhow!("自定义SNI需要直接连接目标，不能与代理同时使用"));
+            }
+        }
+        if self.config.unix_socket.is_some() {
+            unix::check_available()?;
+            if self.config.base_url.as_deref().is_some_and(|url| !url.starts_with("http://")) {
+                return Err(anyhow!("通过Unix套接字扫描时目标URL必须使用http"));
+            }
+            if self.config.proxy.is_some() {
+                return Err(anyhow!("Unix套接字与代理不能同时使用"));
+            }
+            if self.config.login_url.is_some() {
+                return Err(anyhow!("Unix套接字模式不支持表单登录"));
+            }
+        }
+        if self.config.ua_per_connection && (self.config.static_ua || self.config.user_agent.is_some()) {
+            return Err(anyhow!("固定UA与按连接固定UA不能同时使用"));
+        }
+        let url_template = self.config.base_url.as_deref().is_some_and(|url| url.contains(WORD_PLACEHOLDER));
+        if self.config.recursion_depth > 0 && (url_template || self.config.templated_request() || self.config.param_fuzz) {
+            return Err(anyhow!("递归扫描不能用于请求模板或参数模式"));
+        }
+        if self.config.insecure && !self.config.ca_certs.is_empty() {
+            return Err(anyhow!("已禁用证书验证（--insecure），额外的CA证书不会生效"));
+        }
+        if self.config.data.is_some() && self.config.head_only {
+            return Err(anyhow!("仅状态行模式使用HEAD请求，不能同时指定请求体"));
+        }
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_ipv6_literals() {
+        assert_eq!(validate_url("http://[::1]:8080/").unwrap(), "http://[::1]:8080/");
+        assert_eq!(validate_url("https://[2001:db8::1]").unwrap(), "https://[2001:db8::1]/");
+        // 默认端口会被规范化掉
+        assert_eq!(validate_url("http://[::1]:80/").unwrap(), "http://[::1]/");
+    }
+
+    #[test]
+    fn joins_paths_onto_ipv6_base() {
+        let base = Url::parse(&validate_url("http://[::1]:8080/").unwrap()).unwrap();
+        let url = base.join("admin/config").unwrap();
+        assert_eq!(url.as_str(), "http://[::1]:8080/admin/config");
+        assert_eq!(url.host_str(), Some("[::1]"));
+        assert_eq!(url.port(), Some(8080));
+    }
+
+    #[test]
+    fn rejects_malformed_ipv6() {
+        let err = validate_url("http://[::1/").unwrap_err().to_string();
+        assert!(err.contains("IPv6地址格式错误"), "{}", err);
+        let err = validate_url("http://[::zz]:8080/").unwrap_err().to_string();
+        assert!(err.contains("IPv6地址格式错误"), "{}", err);
+        let err = validate_url("http://::1:8080/").unwrap_err().to_string();
+        assert!(err.contains("方括号"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_out_of_range_port() {
+        let err = validate_url("http://[::1]:65536/").unwrap_err().to_string();
+        assert!(err.contains("0-65535"), "{}", err);
+        assert!(validate_url("http://[::1]:65535/").is_ok());
+    }
+
+    #[test]
+    fn rejects_max_per_ip_behind_proxy() {
+        let err = ScanConfig::builder()
+            .url("http://example.com/")
+            .max_per_ip(2)
+            .proxy("http://127.0.0.1:8080")
+            .build()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("代理"), "{}", err);
+        assert!(ScanConfig::builder()
+            .url("http://example.com/")
+            .max_per_ip(2)
+            .no_proxy_env(true)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn selects_paths_by_substring() {
+        let config = ScanConfig::builder()
+            .url("http://admin.example.com/")
+            .match_path("admin")
+            .match_path("login")
+            .filter_path(".BAK")
+            .build()
+            .unwrap();
+        let selected = |url: &str| config.path_selected(&Url::parse(url).unwrap());
+        assert!(selected("http://admin.example.com/Admin/index.php"));
+        assert!(selected("http://admin.example.com/index.php?next=login"));
+        // 主机名不参与匹配
+        assert!(!selected("http://admin.example.com/backup.zip"));
+        assert!(!selected("http://admin.example.com/admin.php.bak"));
+    }
+
+    #[test]
+    fn finds_path_headers_for_derived_paths() {
+        let header = |name: &str| vec![(name.to_string(), "1".to_string())];
+        let headers = HashMap::from([
+            ("admin".to_string(), header("X-Admin")),
+            ("/admin/users/".to_string(), header("X-Users")),
+            ("api?debug=1".to_string(), header("X-Debug")),
+        ]);
+        let config = ScanConfig::builder().url("http://example.com/").path_headers(headers).build().unwrap();
+        let names = |path: &str| config.headers_for_path(path).map(|extra| extra[0].0.clone());
+        assert_eq!(names("admin").as_deref(), Some("X-Admin"));
+        // --add-slash 派生的路径
+        assert_eq!(names("admin/").as_deref(), Some("X-Admin"));
+        // 递归扫描的子路径沿用上级目录，自己附带的请求头优先
+        assert_eq!(names("admin/logs/x").as_deref(), Some("X-Admin"));
+        assert_eq!(names("admin/users/edit").as_deref(), Some("X-Users"));
+        assert_eq!(names("api?debug=1").as_deref(), Some("X-Debug"));
+        assert_eq!(names("administrator"), None);
+        assert_eq!(names("api"), None);
+    }
+}
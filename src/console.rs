@@ -0,0 +1,165 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+/// 终端输出队列：扫描期间的终端输出都交给一个专门的打印线程，按收到的顺序逐条写出
+///
+/// 高并发时各请求的输出不会在行中交错；交互询问也由打印线程完成，询问期间其他输出排队等候，
+/// 不会打断提示或混进用户的输入
+enum Message {
+    /// 原样写出的文本，不自动换行（折叠模式原地更新的计数行）
+    Text(String),
+    /// 打印提示并读取一行输入，读到的内容通过 `reply` 返回
+    Prompt { prompt: String, reply: Sender<String> },
+    /// 之前排队的输出都已写出
+    Flush(Sender<()>),
+}
+
+static QUEUE: OnceLock<Sender<Message>> = OnceLock::new();
+
+fn queue() -> &'static Sender<Message> {
+    QUEUE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for message in receiver {
+                match message {
+                    Message::Text(text) => {
+                        let mut stdout = io::stdout().lock();
+                        let _ = stdout.write_all(text.as_bytes());
+                        let _ = stdout.flush();
+                    }
+                    Message::Prompt { prompt, reply } => {
+                        {
+                            let mut stdout = io::stdout().lock();
+                            let _ = stdout.write_all(prompt.as_bytes());
+                            let _ = stdout.flush();
+                        }
+                        let _ = reply.send(read_answer());
+                    }
+                    Message::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        sender
+    })
+}
+
+/// 排队打印一行
+pub fn print_line(line: impl fmt::Display) {
+    print_text(format!("{}\n", line));
+}
+
+/// 排队原样打印文本，不换行
+pub(crate) fn print_text(text: String) {
+    let _ = queue().send(Message::Text(text));
+}
+
+/// 打印提示并等待用户输入一行，返回去掉首尾空白的内容；之前排队的输出会先写出
+pub(crate) fn prompt(prompt: &str) -> String {
+    let (reply, answer) = mpsc::channel();
+    let _ = queue().send(Message::Prompt { prompt: prompt.to_string(), reply });
+    answer.recv().unwrap_or_default().trim().to_string()
+}
+
+/// 等待已排队的输出全部写出，之后直接写终端的内容不会跑到它们前面
+pub(crate) fn flush() {
+    let (done, finished) = mpsc::channel();
+    if queue().send(Message::Flush(done)).is_ok() {
+        let _ = finished.recv();
+    }
+}
+
+type Handler = Box<dyn Fn(&str) + Send>;
+
+/// 终端输入：只有一个线程读取标准输入，读到的行优先回答正在进行的询问，否则交给命令处理函数
+///
+/// 询问和扫描期间的命令（如暂停/继续）因此可以同时使用终端，不会争抢同一行输入
+#[derive(Default)]
+struct Input {
+    // 正在等待回答的询问
+    pending: Option<Sender<String>>,
+    // 不是回答的输入行交给它处理
+    handler: Option<Handler>,
+    // 标准输入已关闭，之后的询问直接得到空回答
+    closed: bool,
+}
+
+static INPUT: OnceLock<Mutex<Input>> = OnceLock::new();
+
+fn input() -> &'static Mutex<Input> {
+    INPUT.get_or_init(|| {
+        thread::spawn(|| {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                dispatch(input(), line);
+            }
+            let mut input = input().lock().unwrap();
+            input.closed = true;
+            // 丢弃等待中的询问，使其得到空回答
+            input.pending = None;
+        });
+        Mutex::default()
+    })
+}
+
+/// 把一行输入交给正在等待的询问，没有询问时交给命令处理函数
+fn dispatch(input: &Mutex<Input>, line: String) {
+    let mut input = input.lock().unwrap();
+    if let Some(reply) = input.pending.take() {
+        let _ = reply.send(line);
+    } else if let Some(ref handler) = input.handler {
+        handler(line.trim());
+    }
+}
+
+/// 等待终端输入的下一行作为询问的回答
+fn read_answer() -> String {
+    let (reply, answer) = mpsc::channel();
+    {
+        let mut input = input().lock().unwrap();
+        if input.closed {
+            return String::new();
+        }
+        input.pending = Some(reply);
+    }
+    answer.recv().unwrap_or_default()
+}
+
+/// 开始读取终端输入，不是询问回答的每一行（去掉首尾空白）交给 `handler` 处理
+///
+/// 标准输入是终端时才应调用，输入由 `--stdin-urls` 等用途占用时不要调用
+pub fn on_input_line(handler: impl Fn(&str) + Send + 'static) {
+    input().lock().unwrap().handler = Some(Box::new(handler));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn answers_pending_prompts_before_commands() {
+        let input = Mutex::new(Input::default());
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        input.lock().unwrap().handler = Some(Box::new({
+            let commands = Arc::clone(&commands);
+            move |line: &str| commands.lock().unwrap().push(line.to_string())
+        }));
+
+        dispatch(&input, " p ".to_string());
+        let (reply, answer) = mpsc::channel();
+        input.lock().unwrap().pending = Some(reply);
+        dispatch(&input, "y".to_string());
+        dispatch(&input, "r".to_string());
+
+        assert_eq!(answer.recv().unwrap(), "y");
+        assert_eq!(*commands.lock().unwrap(), ["p", "r"]);
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{anyhow, Result};
+
+/// 从登录页中提取CSRF令牌的方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsrfExtractor {
+    /// CSS选择器，令牌取匹配元素的 `value`（表单字段）或 `content`（meta标签）属性，都没有时取文本
+    Selector(String),
+    /// 正则表达式，令牌取第一个捕获组（没有捕获组时取整个匹配）
+    Regex(String),
+}
+
+/// 检查选择器或正则表达式能否解析，在配置校验时调用
+#[cfg(feature = "csrf")]
+pub(crate) fn validate(extractor: &CsrfExtractor) -> Result<()> {
+    match extractor {
+        CsrfExtractor::Selector(selector) => {
+            scraper::Selector::parse(selector).map_err(|e| anyhow!("CSRF选择器 '{}' 无效: {}", selector, e))?;
+        }
+        CsrfExtractor::Regex(pattern) => {
+            regex::Regex::new(pattern).map_err(|e| anyhow!("CSRF正则表达式 '{}' 无效: {}", pattern, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "csrf"))]
+pub(crate) fn validate(_extractor: &CsrfExtractor) -> Result<()> {
+    Err(anyhow!("当前版本未包含CSRF令牌提取功能，请使用 cargo build --features csrf 重新编译"))
+}
+
+/// 从登录页HTML中提取令牌，返回 (字段名, 令牌)；字段名取自匹配元素的 `name` 属性，正则方式下为 `None`
+#[cfg(feature = "csrf")]
+pub(crate) fn extract(html: &str, extractor: &CsrfExtractor) -> Result<(Option<String>, String)> {
+    match extractor {
+        CsrfExtractor::Selector(selector) => {
+            let parsed = scraper::Selector::parse(selector).map_err(|e| anyhow!("CSRF选择器 '{}' 无效: {}", selector, e))?;
+            let document = scraper::Html::parse_document(html);
+            let element = document
+                .select(&parsed)
+                .next()
+                .ok_or_else(|| anyhow!("登录页中没有匹配 '{}' 的元素", selector))?;
+            let value = element.value();
+            let token = value
+                .attr("value")
+                .or_else(|| value.attr("content"))
+                .map(str::to_string)
+                .unwrap_or_else(|| element.text().collect::<String>().trim().to_string());
+            Ok((value.attr("name").map(str::to_string), token))
+        }
+        CsrfExtractor::Regex(pattern) => {
+            let regex = regex::Regex::new(pattern).map_err(|e| anyhow!("CSRF正则表达式 '{}' 无效: {}", pattern, e))?;
+            let captures = regex
+                .captures(html)
+                .ok_or_else(|| anyhow!("登录页中没有匹配 '{}' 的内容", pattern))?;
+            let token = captures.get(1).or_else(|| captures.get(0)).map_or("", |m| m.as_str());
+            Ok((None, token.to_string()))
+        }
+    }
+}
+
+#[cfg(not(feature = "csrf"))]
+pub(crate) fn extract(_html: &str, _extractor: &CsrfExtractor) -> Result<(Option<String>, String)> {
+    Err(anyhow!("当前版本未包含CSRF令牌提取功能，请使用 cargo build --features csrf 重新编译"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "csrf")]
+    const LOGIN_PAGE: &str = r#"<html><head><meta name="csrf-token" content="meta-123"></head>
+        <body><form><input type="hidden" name="_token" value="form-456"><span id="nonce"> text-789 </span></form>
+        <script>window.csrf = "script-abc";</script></body></html>"#;
+
+    #[cfg(feature = "csrf")]
+    #[test]
+    fn extracts_tokens_by_selector() {
+        let selector = |selector: &str| extract(LOGIN_PAGE, &CsrfExtractor::Selector(selector.into())).unwrap();
+        assert_eq!(selector("input[name=_token]"), (Some("_token".into()), "form-456".into()));
+        assert_eq!(selector("meta[name=csrf-token]"), (Some("csrf-token".into()), "meta-123".into()));
+        // 既没有 value 也没有 content 时取文本
+        assert_eq!(selector("#nonce"), (None, "text-789".into()));
+
+        let err = extract(LOGIN_PAGE, &CsrfExtractor::Selector("input[name=csrf]".into())).unwrap_err().to_string();
+        assert!(err.contains("没有匹配"), "{}", err);
+    }
+
+    #[cfg(feature = "csrf")]
+    #[test]
+    fn extracts_tokens_by_regex() {
+        let regex = |pattern: &str| extract(LOGIN_PAGE, &CsrfExtractor::Regex(pattern.into())).unwrap();
+        assert_eq!(regex(r#"window\.csrf = "([^"]+)""#), (None, "script-abc".into()));
+        // 没有捕获组时取整个匹配
+        assert_eq!(regex(r"form-\d+"), (None, "form-456".into()));
+        assert!(extract(LOGIN_PAGE, &CsrfExtractor::Regex("missing-(\\w+)".into())).is_err());
+    }
+
+    #[cfg(feature = "csrf")]
+    #[test]
+    fn validates_extractors() {
+        assert!(validate(&CsrfExtractor::Selector("input[name=_token]".into())).is_ok());
+        assert!(validate(&CsrfExtractor::Regex("token=(\\w+)".into())).is_ok());
+        let err = validate(&CsrfExtractor::Selector("input[".into())).unwrap_err().to_string();
+        assert!(err.starts_with("CSRF选择器 'input[' 无效"), "{}", err);
+        let err = validate(&CsrfExtractor::Regex("token=(".into())).unwrap_err().to_string();
+        assert!(err.starts_with("CSRF正则表达式 'token=(' 无效"), "{}", err);
+    }
+
+    #[cfg(not(feature = "csrf"))]
+    #[test]
+    fn requires_the_csrf_feature() {
+        let extractor = CsrfExtractor::Regex("token=(\\w+)".into());
+        assert!(validate(&extractor).unwrap_err().to_string().contains("--features csrf"));
+        assert!(extract("token=1", &extractor).unwrap_err().to_string().contains("--features csrf"));
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use reqwest::Method;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::config::ScanConfig;
+
+/// 为每条发现写入等价的 curl 命令，便于手工复核
+pub(crate) struct CurlLog {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl CurlLog {
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow!("创建curl命令文件失败: {}", e))?;
+        Ok(Self { writer: Mutex::new(BufWriter::new(file)) })
+    }
+
+    pub(crate) async fn record(&self, command: &str) {
+        let mut writer = self.writer.lock().await;
+        let _ = writeln!(writer, "{}", command);
+    }
+
+    pub(crate) async fn flush(&self) -> Result<()> {
+        self.writer.lock().await.flush()
+            .map_err(|e| anyhow!("写入curl命令文件失败: {}", e))
+    }
+}
+
+/// 按扫描配置和实际发送的请求头生成等价的 curl 命令
+pub(crate) fn curl_command(
+    config: &ScanConfig,
+    method: &Method,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+) -> String {
+    let mut args: Vec<String> = vec!["curl".to_string(), "-i".to_string()];
+    if let Some(body) = body {
+        // --data-raw 隐含POST，且不会把 @ 开头的内容当作文件名
+        args.push("--data-raw".to_string());
+        args.push(quote(body));
+    }
+    if *method == Method::HEAD {
+        args.push("-I".to_string());
+    } else if *method != Method::GET && !(*method == Method::POST && body.is_some()) {
+        args.push("-X".to_string());
+        args.push(method.to_string());
+    }
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case("user-agent") {
+            args.push("-A".to_string());
+            args.push(quote(value));
+        } else {
+            args.push("-H".to_string());
+            args.push(quote(&format!("{}: {}", name, value)));
+        }
+    }
+    if config.decompress {
+        args.push("--compressed".to_string());
+    }
+    if config.follows_redirects() {
+        args.push("-L".to_string());
+        args.push("--max-redirs".to_string());
+        args.push(config.max_redirects.to_string());
+    }
+    if config.insecure {
+        args.push("-k".to_string());
+    }
+    if let Some((ref username, ref password)) = config.digest_auth {
+        args.push("--digest".to_string());
+        args.push("-u".to_string());
+        args.push(quote(&format!("{}:{}", username, password)));
+    }
+    // curl只接受一个CA文件，多个时需要自行合并
+    if let [ca_cert] = config.ca_certs.as_slice() {
+        args.push("--cacert".to_string());
+        args.push(quote(&ca_cert.display().to_string()));
+    }
+    // 显式给出实际使用的代理，避免curl按自身规则读取环境变量；Unix套接字模式下不经过代理
+    match config.effective_proxy(url.scheme()) {
+        _ if config.unix_socket.is_some() => {}
+        Some(proxy) => {
+            args.push("-x".to_string());
+            args.push(quote(&proxy));
+            let exclusions = config.proxy_exclusions();
+            if !exclusions.is_empty() {
+                args.push("--noproxy".to_string());
+                args.push(quote(&exclusions.join(",")));
+            }
+        }
+        None if config.no_proxy_env => {
+            args.push("--noproxy".to_string());
+            args.push(quote("*"));
+        }
+        None => {}
+    }
+    if let Some(ref path) = config.unix_socket {
+        args.push("--unix-socket".to_string());
+        args.push(quote(&path.display().to_string()));
+    }
+    if let Some(addr) = config.bind_address {
+        args.push("--interface".to_string());
+        args.push(addr.to_string());
+    }
+    // curl 的 --resolve 需要端口，使用请求URL的端口
+    let port = url.port_or_known_default().unwrap_or(80);
+    for (host, addr) in &config.resolve {
+        let ip = match addr.ip() {
+            IpAddr::V6(ip) => format!("[{}]", ip),
+            ip => ip.to_string(),
+        };
+        args.push("--resolve".to_string());
+        args.push(quote(&format!("{}:{}:{}", host, port, ip)));
+    }
+    // 自定义SNI：请求SNI名称，连接转到原主机
+    let sni_url = config.sni_url(url);
+    if let (Some(ref sni_url), Some(host)) = (&sni_url, url.host_str()) {
+        args.push("--connect-to".to_string());
+        args.push(quote(&format!("{}:{}:{}:{}", sni_url.host_str().unwrap_or_default(), port, host, port)));
+    }
+    args.push("--connect-timeout".to_string());
+    args.push(config.connect_timeout.as_secs().max(1).to_string());
+    args.push("-m".to_string());
+    args.push(config.timeout.as_secs().max(1).to_string());
+    args.push(quote(sni_url.as_ref().unwrap_or(url).as_str()));
+    args.join(" ")
+}
+
+/// 按 POSIX shell 规则给参数加单引号
+fn quote(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@,%+".contains(c)) {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,370 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+
+use anyhow::{anyhow, Result};
+use flate2::read::MultiGzDecoder;
+use reqwest::header::HeaderName;
+use url::Url;
+
+use crate::config::wildcard_match;
+use crate::severity::Severity;
+
+/// 内置的常用路径字典，编译时嵌入二进制
+const BUILTIN: &str = include_str!("builtin.txt");
+
+/// gzip文件开头的魔数
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// 读取字典文件，跳过空行和 `#` 注释（包括行内注释）并展开数字范围
+///
+/// gzip压缩的字典（按文件开头的魔数识别，与扩展名无关）边读边解压，不需要先解压到磁盘
+pub fn load_dictionary(path: &str) -> Result<Vec<String>> {
+    let file = File::open(path)
+        .map_err(|e| anyhow!("打开字典文件失败: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let gzip = reader.fill_buf()
+        .map_err(|e| anyhow!("读取字典文件失败: {}", e))?
+        .starts_with(&GZIP_MAGIC);
+    if !gzip && path.ends_with(".gz") {
+        return Err(anyhow!("字典文件 {} 的扩展名为 .gz，但内容不是gzip格式", path));
+    }
+    let reader: Box<dyn Read> = if gzip { Box::new(MultiGzDecoder::new(reader)) } else { Box::new(reader) };
+    let paths = parse_lines(read_lines(BufReader::new(reader), gzip)?.into_iter())?;
+
+    if paths.is_empty() {
+        return Err(anyhow!("字典文件为空或格式错误"));
+    }
+    Ok(paths)
+}
+
+/// 逐行读取字典内容，`gzip` 表示读取器在边读边解压
+///
+/// 读取或解压出错（文件损坏或被截断）或某行不是有效的UTF-8时报错，而不是悄悄只扫描已读出的部分
+fn read_lines(mut reader: impl BufRead, gzip: bool) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer).map_err(|e| match gzip {
+            true => anyhow!("解压字典文件失败: {}", e),
+            false => anyhow!("读取字典文件失败: {}", e),
+        })?;
+        if read == 0 {
+            return Ok(lines);
+        }
+        let line = std::str::from_utf8(&buffer)
+            .map_err(|_| anyhow!("字典文件第 {} 行不是有效的UTF-8文本", lines.len() + 1))?;
+        lines.push(line.trim_end_matches(['\n', '\r']).to_string());
+    }
+}
+
+/// 返回内置字典展开后的路径列表
+pub fn builtin_dictionary() -> Vec<String> {
+    parse_lines(BUILTIN.lines().map(str::to_string)).expect("内置字典格式错误")
+}
+
+pub(crate) fn parse_lines(lines: impl Iterator<Item = String>) -> Result<Vec<String>> {
+    let mut paths: Vec<String> = Vec::new();
+    for line in lines.filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+        let line = strip_inline_comment(&line);
+        if !line.is_empty() {
+            paths.extend(expand_numeric_ranges(&line)?);
+        }
+    }
+    Ok(paths)
+}
+
+/// 去掉行内注释（如 `admin  # 管理后台`）并去除首尾空白
+///
+/// 只有前面是空白的 `#` 才开始注释，`page#section` 中的片段标识保持不变；
+/// 行尾的严重性标签（`# HIGH`）在注释之后仍然保留，交给 [`extract_severity_tags`] 处理
+fn strip_inline_comment(line: &str) -> String {
+    let start = line
+        .match_indices('#')
+        .map(|(index, _)| index)
+        .find(|index| line[..*index].ends_with(char::is_whitespace));
+    let Some(start) = start else {
+        return line.trim().to_string();
+    };
+    let entry = line[..start].trim();
+    let tag = line
+        .rsplit_once('#')
+        .map(|(_, tag)| tag.trim())
+        .filter(|tag| tag.parse::<Severity>().is_ok());
+    match tag {
+        Some(tag) if !entry.is_empty() => format!("{} # {}", entry, tag),
+        _ => entry.to_string(),
+    }
+}
+
+/// 拆出字典项末尾的严重性标签（如 `admin/ # CRITICAL`），返回去掉标签的字典项和标签表
+///
+/// 只有 `#` 前有空白且其后是合法的严重性名称时才视为标签，其他 `#` 原样保留
+pub fn extract_severity_tags(paths: Vec<String>) -> (Vec<String>, HashMap<String, Severity>) {
+    let mut tags = HashMap::new();
+    let paths = paths
+        .into_iter()
+        .map(|path| match split_severity_tag(&path) {
+            Some((entry, severity)) => {
+                tags.insert(entry.to_string(), severity);
+                entry.to_string()
+            }
+            None => path,
+        })
+        .collect();
+    (paths, tags)
+}
+
+fn split_severity_tag(path: &str) -> Option<(&str, Severity)> {
+    path.rsplit_once('#')
+        .filter(|(entry, _)| entry.ends_with(char::is_whitespace))
+        .and_then(|(entry, tag)| Some((entry.trim_end(), tag.parse::<Severity>().ok()?)))
+}
+
+/// 字典项 -> 该字典项附带的请求头
+pub type PathHeaders = HashMap<String, Vec<(String, String)>>;
+
+/// 拆出字典项中 `|` 之后的请求头（如 `admin|X-Custom: 1|X-Other: 2`），返回去掉请求头的字典项和每个字典项的请求头
+///
+/// 只有 `|` 之后的每一段都是 `NAME: VALUE` 形式的合法请求头时才拆分，否则整行原样作为路径；
+/// 行尾的严重性标签保留在字典项上。同一字典项出现多次时请求头合并
+pub fn extract_path_headers(paths: Vec<String>) -> (Vec<String>, PathHeaders) {
+    let mut headers = PathHeaders::new();
+    let paths = paths
+        .into_iter()
+        .map(|path| {
+            let (body, tag) = match split_severity_tag(&path) {
+                Some((entry, severity)) => (entry, Some(severity)),
+                None => (path.as_str(), None),
+            };
+            let Some((entry, extra)) = split_path_headers(body) else {
+                return path;
+            };
+            headers.entry(entry.to_string()).or_default().extend(extra);
+            match tag {
+                Some(severity) => format!("{} # {}", entry, severity),
+                None => entry.to_string(),
+            }
+        })
+        .collect();
+    (paths, headers)
+}
+
+fn split_path_headers(line: &str) -> Option<(&str, Vec<(String, String)>)> {
+    let mut parts = line.split('|');
+    let entry = parts.next()?.trim();
+    let headers = parts
+        .map(|part| {
+            let (name, value) = part.split_once(':')?;
+            let name = name.trim();
+            HeaderName::from_bytes(name.as_bytes()).ok()?;
+            Some((name.to_string(), value.trim().to_string()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!entry.is_empty() && !headers.is_empty()).then_some((entry, headers))
+}
+
+/// 字典项的最大长度，更长的通常是误混入字典的数据
+pub const MAX_ENTRY_LENGTH: usize = 1024;
+
+/// 检查字典项是否可疑：含控制字符、反斜杠（Windows路径）或过长，返回问题说明
+pub fn entry_problem(entry: &str) -> Option<&'static str> {
+    if entry.chars().any(char::is_control) {
+        Some("含有控制字符")
+    } else if entry.contains('\\') {
+        Some("含有反斜杠")
+    } else if entry.len() > MAX_ENTRY_LENGTH {
+        Some("长度超过上限")
+    } else {
+        None
+    }
+}
+
+/// 判断字典项是否为完整URL（带主机名，或以 `//` 开头的协议相对地址）
+///
+/// 这类字典项与目标拼接时会整个替换掉目标的主机，请求发往其他站点
+pub fn is_absolute_entry(entry: &str) -> bool {
+    entry.starts_with("//")
+        || Url::parse(entry).is_ok_and(|url| url.has_host())
+}
+
+/// 展开字典行中的 `%NUM:起始-结束%` 数字范围，起始值带前导零时按其宽度补零
+pub fn expand_numeric_ranges(line: &str) -> Result<Vec<String>> {
+    const TOKEN: &str = "%NUM:";
+
+    let Some(start) = line.find(TOKEN) else {
+        return Ok(vec![line.to_string()]);
+    };
+    let spec_start = start + TOKEN.len();
+    let spec_len = line[spec_start..]
+        .find('%')
+        .ok_or_else(|| anyhow!("字典行 '{}' 中的数字范围缺少结尾的 %", line))?;
+    let spec = &line[spec_start..spec_start + spec_len];
+    let rest = &line[spec_start + spec_len + 1..];
+
+    let (from, to) = spec
+        .split_once('-')
+        .ok_or_else(|| anyhow!("字典行 '{}' 中的数字范围应为 %NUM:起始-结束%", line))?;
+    let (from, to) = (from.trim(), to.trim());
+    let first: u64 = from
+        .parse()
+        .map_err(|e| anyhow!("字典行 '{}' 中的起始值无效: {}", line, e))?;
+    let last: u64 = to
+        .parse()
+        .map_err(|e| anyhow!("字典行 '{}' 中的结束值无效: {}", line, e))?;
+    if first > last {
+        return Err(anyhow!("字典行 '{}' 中的起始值大于结束值", line));
+    }
+    let width = if from.len() > 1 && from.starts_with('0') { from.len() } else { 0 };
+
+    // 先展开剩余部分，支持一行中出现多个范围
+    let tails = expand_numeric_ranges(rest)?;
+    let mut expanded = Vec::new();
+    for n in first..=last {
+        for tail in &tails {
+            expanded.push(format!("{}{:0width$}{}", &line[..start], n, tail, width = width));
+        }
+    }
+    Ok(expanded)
+}
+
+/// 读取排除列表文件，每行一个路径或含 `*` 通配符的模式，跳过空行和 `#` 注释
+pub fn load_exclusions(path: &str) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("读取排除列表文件失败: {}", e))?;
+    let patterns: Vec<String> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect();
+    if patterns.is_empty() {
+        return Err(anyhow!("排除列表文件 {} 中没有路径", path));
+    }
+    Ok(patterns)
+}
+
+/// 剔除与排除列表匹配的字典项，返回剔除的条数
+///
+/// 比较时忽略大小写和首尾的斜杠，`logout` 同时排除 `/logout` 和 `logout/`；模式中的 `*` 匹配任意字符，
+/// 包括斜杠，如 `*shutdown*`、`admin/*`
+pub fn exclude_entries(paths: &mut Vec<String>, patterns: &[String]) -> usize {
+    let before = paths.len();
+    paths.retain(|path| {
+        let path = path.trim_matches('/').to_lowercase();
+        !patterns.iter().any(|pattern| wildcard_match(pattern.trim_matches('/'), &path))
+    });
+    before - paths.len()
+}
+
+/// 为没有结尾斜杠和扩展名的路径追加一个 `路径/` 变体，已在字典中出现的斜杠形式不会重复添加
+pub fn add_slash_variants(paths: Vec<String>) -> Vec<String> {
+    let mut seen: HashSet<String> = paths.iter().cloned().collect();
+    let mut expanded = Vec::with_capacity(paths.len() * 2);
+    for path in paths {
+        let name = path.rsplit('/').next().unwrap_or_default();
+        let wants_slash = !path.ends_with('/')
+            && !name.contains('.')
+            && !path.contains(['?', '#']);
+        let variant = format!("{}/", path);
+        expanded.push(path);
+        if wants_slash && seen.insert(variant.clone()) {
+            expanded.push(variant);
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Write};
+
+    use super::*;
+
+    /// 读到一部分内容后报错的读取器
+    struct FailingReader(Cursor<Vec<u8>>);
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(io::Error::other("设备错误")),
+                n => Ok(n),
+            }
+        }
+    }
+
+    #[test]
+    fn reads_lines() {
+        let lines = read_lines(Cursor::new(b"admin\r\nlogin\n\nbackup".to_vec()), false).unwrap();
+        assert_eq!(lines, ["admin", "login", "", "backup"]);
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let err = read_lines(Cursor::new(b"admin\n\xb9\xdc\xc0\xed\n".to_vec()), false).unwrap_err().to_string();
+        assert!(err.contains("第 2 行"), "{}", err);
+    }
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_gzip(bytes: Vec<u8>) -> Result<Vec<String>> {
+        read_lines(BufReader::new(MultiGzDecoder::new(Cursor::new(bytes))), true)
+    }
+
+    #[test]
+    fn reads_gzip_lines() {
+        assert_eq!(read_gzip(gzip(b"admin\nlogin\n")).unwrap(), ["admin", "login"]);
+    }
+
+    #[test]
+    fn reports_corrupt_gzip() {
+        let mut bytes = gzip(b"admin\nlogin\nbackup\n");
+        bytes.truncate(bytes.len() - 10);
+        let err = read_gzip(bytes).unwrap_err().to_string();
+        assert!(err.contains("解压字典文件失败"), "{}", err);
+    }
+
+    #[test]
+    fn tells_decoding_from_decompression_errors() {
+        let err = read_gzip(gzip(b"admin\n\xb9\xdc\xc0\xed\n")).unwrap_err().to_string();
+        assert!(err.contains("第 2 行") && !err.contains("解压"), "{}", err);
+    }
+
+    #[test]
+    fn reports_read_errors() {
+        let reader = BufReader::new(FailingReader(Cursor::new(b"admin\nlogin\n".to_vec())));
+        let err = read_lines(reader, false).unwrap_err().to_string();
+        assert!(err.contains("读取字典文件失败"), "{}", err);
+    }
+
+    #[test]
+    fn strips_inline_comments() {
+        assert_eq!(strip_inline_comment("admin  # 管理后台"), "admin");
+        assert_eq!(strip_inline_comment("\tapi/v1\t#接口"), "api/v1");
+        // 前面没有空白的 # 是URL片段
+        assert_eq!(strip_inline_comment("page#section"), "page#section");
+        assert_eq!(strip_inline_comment("page#section # 注释"), "page#section");
+        // 行尾的严重性标签保留
+        assert_eq!(strip_inline_comment("backup.zip # HIGH"), "backup.zip # HIGH");
+        assert_eq!(strip_inline_comment(".git/ # 版本库 # critical"), ".git/ # critical");
+        assert_eq!(strip_inline_comment("   # 只有注释 # HIGH"), "");
+    }
+
+    #[test]
+    fn parses_annotated_dictionary_lines() {
+        let lines = ["# 标题", "", "admin  # 管理后台", "  # 缩进的注释", "page#top", "log%NUM:1-2%.txt # 日志"];
+        let paths = parse_lines(lines.iter().map(|line| line.to_string())).unwrap();
+        assert_eq!(paths, ["admin", "page#top", "log1.txt", "log2.txt"]);
+
+        // 去掉注释后的范围仍会校验
+        let err = parse_lines(["log%NUM:5-1%.txt # 倒序".to_string()].into_iter()).unwrap_err().to_string();
+        assert!(err.contains("起始值大于结束值"), "{}", err);
+        let err = parse_lines(["log%NUM:1-x% # 注释".to_string()].into_iter()).unwrap_err().to_string();
+        assert!(err.contains("结束值无效"), "{}", err);
+    }
+}
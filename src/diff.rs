@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::conditional::parse_csv_line;
+use crate::finding::Finding;
+
+/// 与上一次扫描结果的差异
+#[derive(Debug, Clone, Default)]
+pub struct ScanDiff {
+    /// 本次新出现的发现：(URL, 大小)
+    pub new: Vec<(String, usize)>,
+    /// 上次有、本次没有的发现：(URL, 上次的大小)
+    pub gone: Vec<(String, usize)>,
+    /// 两次都有但大小不同的发现：(URL, 上次的大小, 本次的大小)
+    pub changed: Vec<(String, usize, usize)>,
+}
+
+impl ScanDiff {
+    /// 比较上次的结果（URL -> 大小）与本次未被过滤的发现，各部分按URL排序
+    pub fn compute(previous: &BTreeMap<String, usize>, findings: &[Finding]) -> Self {
+        let current: BTreeMap<String, usize> = findings
+            .iter()
+            .filter(|f| !f.filtered)
+            .map(|f| (f.url.to_string(), f.size))
+            .collect();
+        let mut diff = Self::default();
+        for (url, size) in &current {
+            match previous.get(url) {
+                None => diff.new.push((url.clone(), *size)),
+                Some(old) if old != size => diff.changed.push((url.clone(), *old, *size)),
+                Some(_) => {}
+            }
+        }
+        diff.gone = previous
+            .iter()
+            .filter(|(url, _)| !current.contains_key(*url))
+            .map(|(url, size)| (url.clone(), *size))
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty() && self.gone.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 之前的输出文件中记录的一条发现
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviousFinding {
+    /// 状态码，纯文本输出只在带有状态码的标记中记录
+    pub status: Option<u16>,
+    /// 大小，只有URL的纯文本行没有记录
+    pub size: Option<usize>,
+}
+
+/// 重放之前的一条发现得到的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Replayed {
+    /// 状态码和大小都没有变化：(状态码, 大小)
+    Unchanged(u16, usize),
+    /// 状态码没有变化，大小变化：(状态码, 本次的大小)
+    SizeChanged(u16, usize),
+    /// 状态码变化：(本次的状态码, 本次的大小)
+    StatusChanged(u16, usize),
+    /// 请求出错或没有得到响应
+    Failed(String),
+}
+
+impl PreviousFinding {
+    /// 与重放得到的响应比较；没有记录状态码时（纯文本输出）2xx视为状态未变，没有记录大小时不比较大小
+    pub fn compare(&self, status: u16, size: usize) -> Replayed {
+        let same_status = match self.status {
+            Some(previous) => previous == status,
+            None => (200..300).contains(&status),
+        };
+        if !same_status {
+            Replayed::StatusChanged(status, size)
+        } else if self.size.is_some_and(|previous| previous != size) {
+            Replayed::SizeChanged(status, size)
+        } else {
+            Replayed::Unchanged(status, size)
+        }
+    }
+}
+
+/// 读取之前的输出文件，返回 URL -> 大小
+pub fn load_previous(path: &Path) -> Result<BTreeMap<String, usize>> {
+    Ok(load_findings(path)?
+        .into_iter()
+        .map(|(url, finding)| (url, finding.size.unwrap_or(0)))
+        .collect())
+}
+
+/// 读取之前的输出文件中的全部发现；按扩展名识别JSON、CSV，其余按纯文本输出解析
+pub fn load_findings(path: &Path) -> Result<BTreeMap<String, PreviousFinding>> {
+    let bytes = fs::read(path)
+        .map_err(|e| anyhow!("读取上次的结果文件 {} 失败: {}", path.display(), e))?;
+    let content = String::from_utf8_lossy(&bytes);
+    let content = content.trim_start_matches('\u{feff}');
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("json") => from_json(content),
+        Some("csv") => from_csv(content),
+        _ => Ok(from_text(content)),
+    }
+}
+
+fn from_json(content: &str) -> Result<BTreeMap<String, PreviousFinding>> {
+    let findings: Vec<Value> = serde_json::from_str(content)
+        .map_err(|e| anyhow!("上次的结果文件不是有效的JSON数组: {}", e))?;
+    Ok(findings
+        .iter()
+        .filter(|finding| !finding.get("filtered").and_then(Value::as_bool).unwrap_or(false))
+        .filter_map(|finding| {
+            let url = finding.get("url")?.as_str()?.to_string();
+            let status = finding.get("status").and_then(Value::as_u64).and_then(|status| u16::try_from(status).ok());
+            let size = finding.get("size").and_then(Value::as_u64).map(|size| size as usize);
+            Some((url, PreviousFinding { status, size }))
+        })
+        .collect())
+}
+
+fn from_csv(content: &str) -> Result<BTreeMap<String, PreviousFinding>> {
+    let mut lines = content.lines();
+    let header = parse_csv_line(lines.next().unwrap_or_default());
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let url_column = column("url").ok_or_else(|| anyhow!("上次的结果文件缺少 url 列"))?;
+    let status_column = column("status");
+    let size_column = column("size");
+    Ok(lines
+        .map(parse_csv_line)
+        .filter_map(|fields| {
+            let url = fields.get(url_column).filter(|url| !url.is_empty())?.clone();
+            let status = status_column.and_then(|i| fields.get(i)).and_then(|status| status.parse().ok());
+            let size = size_column.and_then(|i| fields.get(i)).and_then(|size| size.parse().ok());
+            Some((url, PreviousFinding { status, size }))
+        })
+        .collect())
+}
+
+/// 纯文本输出每行形如 `URL (大小: N 字节) [标记]...`，只有URL的行也接受
+fn from_text(content: &str) -> BTreeMap<String, PreviousFinding> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            let Some((url, rest)) = line.split_once(" (大小: ") else {
+                let url = line.split_whitespace().next()?;
+                return (url.starts_with("http://") || url.starts_with("https://"))
+                    .then(|| (url.to_string(), PreviousFinding { status: None, size: None }));
+            };
+            let size = rest.split_once(" 字节)").and_then(|(size, _)| size.parse().ok());
+            // 异常值和受保护资源的标记中带有状态码
+            let status = rest
+                .split_once("状态码 ")
+                .and_then(|(_, status)| status.split(']').next())
+                .and_then(|status| status.parse().ok());
+            Some((url.to_string(), PreviousFinding { status, size }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_text_output_lines() {
+        let content = "\
+http://example.com/admin (大小: 120 字节)
+  http://example.com/odd (大小: 7 字节) [异常: 状态码 500]
+http://example.com/private (大小: 0 字节) [受保护: 状态码 403] [标题: 登录]
+http://example.com/plain
+https://example.com/word extra
+# 注释行
+";
+        let findings = from_text(content);
+        assert_eq!(findings["http://example.com/admin"], PreviousFinding { status: None, size: Some(120) });
+        assert_eq!(findings["http://example.com/odd"], PreviousFinding { status: Some(500), size: Some(7) });
+        assert_eq!(findings["http://example.com/private"], PreviousFinding { status: Some(403), size: Some(0) });
+        assert_eq!(findings["http://example.com/plain"], PreviousFinding { status: None, size: None });
+        assert_eq!(findings["https://example.com/word"], PreviousFinding { status: None, size: None });
+        assert_eq!(findings.len(), 5);
+    }
+
+    #[test]
+    fn compares_replayed_responses() {
+        let recorded = PreviousFinding { status: Some(200), size: Some(10) };
+        assert_eq!(recorded.compare(200, 10), Replayed::Unchanged(200, 10));
+        assert_eq!(recorded.compare(200, 12), Replayed::SizeChanged(200, 12));
+        assert_eq!(recorded.compare(404, 10), Replayed::StatusChanged(404, 10));
+
+        // 纯文本输出没有状态码：2xx视为未变
+        let text = PreviousFinding { status: None, size: Some(10) };
+        assert_eq!(text.compare(204, 10), Replayed::Unchanged(204, 10));
+        assert_eq!(text.compare(200, 11), Replayed::SizeChanged(200, 11));
+        assert_eq!(text.compare(301, 10), Replayed::StatusChanged(301, 10));
+        // 没有大小时不比较大小
+        let url_only = PreviousFinding { status: None, size: None };
+        assert_eq!(url_only.compare(200, 99), Replayed::Unchanged(200, 99));
+    }
+
+    #[test]
+    fn computes_added_removed_and_changed_findings() {
+        let previous = BTreeMap::from([
+            ("http://example.com/admin".to_string(), 100),
+            ("http://example.com/backup".to_string(), 50),
+            ("http://example.com/same".to_string(), 10),
+        ]);
+        let mut filtered = Finding::sample("http://example.com/waf", 200, 5);
+        filtered.filtered = true;
+        let findings = vec![
+            Finding::sample("http://example.com/same", 200, 10),
+            Finding::sample("http://example.com/new", 200, 30),
+            Finding::sample("http://example.com/admin", 200, 120),
+            // 被过滤的结果不算本次的发现
+            filtered,
+        ];
+        let diff = ScanDiff::compute(&previous, &findings);
+        assert_eq!(diff.new, [("http://example.com/new".to_string(), 30)]);
+        assert_eq!(diff.gone, [("http://example.com/backup".to_string(), 50)]);
+        assert_eq!(diff.changed, [("http://example.com/admin".to_string(), 100, 120)]);
+        assert!(!diff.is_empty());
+
+        let unchanged = ScanDiff::compute(&BTreeMap::from([("http://example.com/same".to_string(), 10)]), &findings[..1]);
+        assert!(unchanged.is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, WWW_AUTHENTICATE};
+use reqwest::Method;
+use url::{Position, Url};
+
+/// HTTP Digest 认证（RFC 7616），常见于路由器、摄像头等嵌入式设备的管理界面
+///
+/// 第一个请求收到 `401` 和 `WWW-Authenticate: Digest` 质询后，按质询计算 `Authorization` 头重新发送；
+/// 质询按主机缓存，之后的请求直接带上认证头，服务器返回新的 nonce（如 `stale=true`）时再更新
+pub(crate) struct DigestAuth {
+    username: String,
+    password: String,
+    // 主机:端口 -> 最近一次的质询
+    challenges: Mutex<HashMap<String, Challenge>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Challenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    // 服务器支持 qop=auth 时使用，否则按 RFC 2069 的旧格式计算
+    qop_auth: bool,
+    session: bool,
+    // 同一个 nonce 已经使用的次数
+    count: u32,
+}
+
+impl DigestAuth {
+    pub(crate) fn new(username: &str, password: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+            challenges: Mutex::default(),
+        }
+    }
+
+    /// 按该主机缓存的质询生成 `Authorization` 头，返回 (所用的 nonce, 认证头)；还没有收到过质询时返回 `None`
+    pub(crate) fn authorization(&self, method: &Method, url: &Url) -> Option<(String, String)> {
+        let mut challenges = self.challenges.lock().unwrap();
+        let challenge = challenges.get_mut(&host_key(url))?;
+        challenge.count += 1;
+        let cnonce = format!("{:016x}", rand::thread_rng().gen::<u64>());
+        let header = self.respond(challenge, method, &url[Position::BeforePath..Position::AfterQuery], &cnonce);
+        Some((challenge.nonce.clone(), header))
+    }
+
+    /// 从401响应中读取 Digest 质询并缓存，`sent` 为请求所带认证头使用的 nonce
+    ///
+    /// 返回是否值得带上认证头重试：用同一个 nonce 仍然失败说明用户名或密码错误，不再重试
+    pub(crate) fn update(&self, url: &Url, headers: &HeaderMap, sent: Option<&str>) -> bool {
+        let Some(challenge) = headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(parse_challenge)
+        else {
+            return false;
+        };
+        let retry = sent != Some(challenge.nonce.as_str());
+        let mut challenges = self.challenges.lock().unwrap();
+        // 并发请求可能收到同一个质询，已缓存时保留其计数
+        let key = host_key(url);
+        if challenges.get(&key).is_none_or(|cached| cached.nonce != challenge.nonce || cached.realm != challenge.realm) {
+            challenges.insert(key, challenge);
+        }
+        retry
+    }
+
+    fn respond(&self, challenge: &Challenge, method: &Method, uri: &str, cnonce: &str) -> String {
+        let nc = format!("{:08x}", challenge.count);
+        let mut ha1 = md5_hex(&format!("{}:{}:{}", self.username, challenge.realm, self.password));
+        if challenge.session {
+            ha1 = md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, cnonce));
+        }
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+        let response = if challenge.qop_auth {
+            md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, challenge.nonce, nc, cnonce, ha2))
+        } else {
+            md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, ha2))
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            quote(&self.username),
+            quote(&challenge.realm),
+            quote(&challenge.nonce),
+            quote(uri),
+            if challenge.session { "MD5-sess" } else { "MD5" },
+            response,
+        );
+        if let Some(ref opaque) = challenge.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", quote(opaque)));
+        }
+        if challenge.qop_auth {
+            header.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\"", nc, cnonce));
+        }
+        header
+    }
+}
+
+fn host_key(url: &Url) -> String {
+    format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default())
+}
+
+fn md5_hex(text: &str) -> String {
+    format!("{:x}", md5::compute(text.as_bytes()))
+}
+
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 解析 `WWW-Authenticate` 头中的 Digest 质询，只支持 MD5 和 MD5-sess 算法
+fn parse_challenge(header: &str) -> Option<Challenge> {
+    let (scheme, params) = header.trim().split_once(char::is_whitespace)?;
+    if !scheme.eq_ignore_ascii_case("digest") {
+        return None;
+    }
+    let params = parse_params(params);
+    let algorithm = params.get("algorithm").map_or("MD5", String::as_str);
+    let session = match algorithm.to_ascii_uppercase().as_str() {
+        "MD5" => false,
+        "MD5-SESS" => true,
+        _ => return None,
+    };
+    Some(Challenge {
+        realm: params.get("realm").cloned().unwrap_or_default(),
+        nonce: params.get("nonce")?.clone(),
+        opaque: params.get("opaque").cloned(),
+        qop_auth: params
+            .get("qop")
+            .is_some_and(|qop| qop.split(',').any(|value| value.trim().eq_ignore_ascii_case("auth"))),
+        session,
+        count: 0,
+    })
+}
+
+/// 解析逗号分隔的 `key=value` 参数，值可以带引号（引号内可以有逗号和转义字符）
+fn parse_params(text: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let key: String = chars.by_ref().take_while(|c| *c != '=').collect();
+        if key.is_empty() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    _ => value.push(c),
+                }
+            }
+        } else {
+            value = chars.by_ref().take_while(|c| *c != ',').collect();
+        }
+        params.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_rfc2617_example() {
+        let mut challenge = parse_challenge(
+            r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+        )
+        .unwrap();
+        assert!(challenge.qop_auth && !challenge.session);
+        challenge.count = 1;
+        let auth = DigestAuth::new("Mufasa", "Circle Of Life");
+        let header = auth.respond(&challenge, &Method::GET, "/dir/index.html", "0a4f113b");
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#), "{}", header);
+        assert!(header.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#), "{}", header);
+        assert!(header.ends_with(r#"qop=auth, nc=00000001, cnonce="0a4f113b""#), "{}", header);
+
+        assert!(parse_challenge(r#"Basic realm="x""#).is_none());
+        assert!(parse_challenge(r#"Digest realm="x", nonce="n", algorithm=SHA-512-256"#).is_none());
+    }
+}
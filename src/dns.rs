@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+
+type Cache = Arc<Mutex<HashMap<String, Arc<Vec<SocketAddr>>>>>;
+
+/// 带缓存的DNS解析器，同一主机在整个扫描期间只解析一次
+///
+/// 高并发下每个新连接都会触发一次系统解析，缓存后所有请求直接使用首次解析的地址
+#[derive(Default)]
+pub(crate) struct CachingResolver {
+    cache: Cache,
+    // 名称 -> 实际解析的主机，用于自定义SNI时把SNI名称解析到目标主机
+    aliases: HashMap<String, String>,
+    // 该主机固定解析到这个地址，用于 --max-per-ip 时让客户端只连接一个后端
+    pinned: Option<(String, IpAddr)>,
+}
+
+impl CachingResolver {
+    /// 解析 `name` 时改为解析 `host`
+    pub(crate) fn alias(mut self, name: &str, host: &str) -> Self {
+        self.aliases.insert(name.to_string(), host.to_string());
+        self
+    }
+
+    /// `host`（包括经别名解析到它的名称）固定解析到 `ip`，其他主机（如跟随跳转到的主机）照常解析，与原解析器共享缓存
+    pub(crate) fn pinned(&self, host: &str, ip: IpAddr) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            aliases: self.aliases.clone(),
+            pinned: Some((host.to_string(), ip)),
+        }
+    }
+
+    /// 测试用：直接写入主机的解析结果
+    #[cfg(test)]
+    pub(crate) fn with_addresses(self, host: &str, ips: &[IpAddr]) -> Self {
+        let addrs = ips.iter().map(|ip| SocketAddr::new(*ip, 0)).collect();
+        self.cache.lock().unwrap().insert(host.to_string(), Arc::new(addrs));
+        self
+    }
+
+    /// 主机解析到的所有地址（使用缓存）
+    pub(crate) async fn addresses(&self, host: &str) -> Result<Arc<Vec<SocketAddr>>> {
+        let host = self.aliases.get(host).map_or(host, String::as_str);
+        lookup(&self.cache, host).await
+            .map_err(|e| anyhow!("域名 {} 解析失败: {}", host, e))
+    }
+
+    /// 预先解析主机并写入缓存，解析失败或没有地址时返回错误
+    pub(crate) async fn prime(&self, host: &str) -> Result<()> {
+        lookup(&self.cache, host).await
+            .map_err(|e| anyhow!("域名 {} 解析失败: {}", host, e))?;
+        Ok(())
+    }
+}
+
+async fn lookup(cache: &Cache, host: &str) -> std::io::Result<Arc<Vec<SocketAddr>>> {
+    if let Some(addrs) = cache.lock().unwrap().get(host) {
+        return Ok(Arc::clone(addrs));
+    }
+    // 端口由连接器按URL重新设置，这里填0即可
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+    if addrs.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "没有可用的地址"));
+    }
+    let addrs = Arc::new(addrs);
+    cache.lock().unwrap().insert(host.to_string(), Arc::clone(&addrs));
+    Ok(addrs)
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = self.aliases.get(name.as_str()).cloned().unwrap_or_else(|| name.as_str().to_string());
+        if let Some((_, ip)) = self.pinned.as_ref().filter(|(pinned, _)| pinned.eq_ignore_ascii_case(&host)) {
+            let addrs: Addrs = Box::new(std::iter::once(SocketAddr::new(*ip, 0)));
+            return Box::pin(async move { Ok(addrs) });
+        }
+        let cache = Arc::clone(&self.cache);
+        Box::pin(async move {
+            let addrs = Vec::clone(&*lookup(&cache, &host).await?);
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    async fn resolve(resolver: &CachingResolver, name: &str) -> Vec<IpAddr> {
+        let addrs = resolver.resolve(Name::from_str(name).unwrap()).await.unwrap();
+        addrs.map(|addr| addr.ip()).collect()
+    }
+
+    #[tokio::test]
+    async fn pins_only_the_target_host() {
+        let target: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let pinned: IpAddr = "10.0.0.9".parse().unwrap();
+        let resolver = CachingResolver::default()
+            .alias("sni.example", "target.example")
+            .with_addresses("target.example", &[target])
+            .with_addresses("other.example", &[other]);
+        let resolver = resolver.pinned("target.example", pinned);
+
+        assert_eq!(resolve(&resolver, "target.example").await, vec![pinned]);
+        assert_eq!(resolve(&resolver, "sni.example").await, vec![pinned]);
+        // 跳转到的其他主机照常解析
+        assert_eq!(resolve(&resolver, "other.example").await, vec![other]);
+    }
+}
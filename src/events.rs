@@ -0,0 +1,41 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// 扫描生命周期事件流，每行一个JSON对象（NDJSON）
+///
+/// 依次产生 `start`、周期性的 `progress`、每条发现的 `finding` 以及结束时的 `end` 事件
+pub(crate) struct EventLog {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl EventLog {
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow!("创建事件文件失败: {}", e))?;
+        Ok(Self { writer: Mutex::new(BufWriter::new(file)) })
+    }
+
+    /// 写入一条事件，`data` 中的字段与事件类型、时间戳合并到同一对象
+    pub(crate) fn emit(&self, event: &str, data: Value) {
+        let mut record = json!({
+            "event": event,
+            "timestamp": SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        });
+        if let (Some(record), Value::Object(data)) = (record.as_object_mut(), data) {
+            record.extend(data);
+        }
+
+        let mut writer = self.writer.lock().unwrap();
+        // 逐条刷新，便于下游实时消费
+        let _ = writeln!(writer, "{}", record).and_then(|_| writer.flush());
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt;
+use std::time::SystemTime;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::severity::Severity;
+
+/// 一条扫描发现，被过滤的结果同样会产生 `Finding`，由 `filtered` 区分
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub url: Url,
+    pub status: StatusCode,
+    pub size: usize,
+    pub content_type: Option<String>,
+    /// 未跟随跳转（或跟随被中止）时，`Location` 指向的地址
+    pub redirect: Option<Url>,
+    /// 跟随跳转后最终到达的地址，与请求地址相同时为 `None`
+    pub final_url: Option<Url>,
+    /// 大小明显偏离主导响应大小（异常值模式）
+    pub outlier: bool,
+    /// 敏感扩展名（备份文件等）返回了非空的200响应
+    pub sensitive: bool,
+    /// 响应内容是开放的目录列表（Apache/nginx autoindex 等）
+    pub listing: bool,
+    /// 返回401/403的受保护资源（`--show-forbidden`），与2xx发现分开统计
+    pub protected: bool,
+    /// `Content-Length` 声明的长度与实际读到的字节数不一致时为 `(声明, 实际)`
+    pub length_mismatch: Option<(usize, usize)>,
+    /// 响应的 `ETag` 头，重新扫描时用于条件请求
+    pub etag: Option<String>,
+    /// 响应的 `Last-Modified` 头，重新扫描时用于条件请求
+    pub last_modified: Option<String>,
+    /// 请求头或请求体中代入的字典项（请求模板模式下URL可能相同，需要据此区分）
+    pub word: Option<String>,
+    /// 字典标签或路径关键字给出的严重性
+    pub severity: Severity,
+    /// 响应体没有读完（命中内容特征后提前停止，或超过 `--max-body-size`），大小只计已读取的部分
+    pub truncated: bool,
+    /// 响应超过 `--skip-large`，没有读取（或没有读完）响应体，大小取自 `Content-Length` 或已读取的字节数
+    pub skipped_body: bool,
+    /// 收到响应的时间
+    pub timestamp: SystemTime,
+    /// 响应的HTML `<title>`，空白已合并
+    pub title: Option<String>,
+    /// 响应头暴露的服务端技术（`Server`、`X-Powered-By`、`X-AspNet-Version`），如 `nginx/1.18.0`、`PHP/7.4.3`
+    pub technologies: Vec<String>,
+    pub filtered: bool,
+    pub reason: Option<FilterReason>,
+}
+
+impl Finding {
+    /// 发现所在的端口，URL中省略时取协议的默认端口
+    pub fn port(&self) -> Option<u16> {
+        self.url.port_or_known_default()
+    }
+
+    /// 发现是否为目录：目录列表、以斜杠结尾的地址，或跳转到加了斜杠的同一地址
+    pub fn is_directory(&self) -> bool {
+        let slashed = |url: &Url| url.path() == format!("{}/", self.url.path().trim_end_matches('/'));
+        self.listing
+            || (self.url.path().ends_with('/') && self.url.path() != "/")
+            || self.redirect.as_ref().is_some_and(slashed)
+            || self.final_url.as_ref().is_some_and(slashed)
+    }
+
+    /// 收到响应的时间，ISO-8601格式（UTC，精确到毫秒）
+    pub fn timestamp_iso(&self) -> String {
+        let time = OffsetDateTime::from(self.timestamp);
+        time.replace_nanosecond(time.millisecond() as u32 * 1_000_000)
+            .unwrap_or(time)
+            .format(&Rfc3339)
+            .unwrap_or_default()
+    }
+
+    /// 转换为JSON对象，供事件流等结构化输出使用
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url.as_str(),
+            "port": self.port(),
+            "status": self.status.as_u16(),
+            "size": self.size,
+            "content_type": self.content_type,
+            "redirect": self.redirect.as_ref().map(Url::as_str),
+            "final_url": self.final_url.as_ref().map(Url::as_str),
+            "outlier": self.outlier,
+            "sensitive": self.sensitive,
+            "listing": self.listing,
+            "protected": self.protected,
+            "length_mismatch": self.length_mismatch.map(|(declared, actual)| json!({
+                "declared": declared,
+                "actual": actual,
+            })),
+            "etag": self.etag,
+            "last_modified": self.last_modified,
+            "word": self.word,
+            "severity": self.severity.to_string(),
+            "truncated": self.truncated,
+            "skipped_body": self.skipped_body,
+            "timestamp": self.timestamp_iso(),
+            "title": self.title,
+            "technologies": self.technologies,
+            "filtered": self.filtered,
+            "reason": self.reason.as_ref().map(ToString::to_string),
+            "reason_code": self.reason.as_ref().map(FilterReason::code),
+        })
+    }
+}
+
+#[cfg(test)]
+impl Finding {
+    /// 测试用：指定地址、状态码和大小，其余字段为空的未过滤发现
+    pub(crate) fn sample(url: &str, status: u16, size: usize) -> Self {
+        Self {
+            url: Url::parse(url).unwrap(),
+            status: StatusCode::from_u16(status).unwrap(),
+            size,
+            content_type: None,
+            redirect: None,
+            final_url: None,
+            outlier: false,
+            sensitive: false,
+            listing: false,
+            protected: false,
+            length_mismatch: None,
+            etag: None,
+            last_modified: None,
+            word: None,
+            severity: Severity::default(),
+            truncated: false,
+            skipped_body: false,
+            timestamp: SystemTime::UNIX_EPOCH,
+            title: None,
+            technologies: Vec::new(),
+            filtered: false,
+            reason: None,
+        }
+    }
+}
+
+/// 结果被过滤的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterReason {
+    /// 响应内容包含过滤规则文件中的特征
+    Signature(String),
+    /// 响应大小已在过滤列表中（之前确认过的WAF/登录页面大小）
+    FilteredSize(usize),
+    /// 相同响应大小重复出现，被确认为WAF/登录页面
+    RepeatedSize,
+    /// 跳转回自身
+    SelfRedirect,
+    /// 跳转到站点根目录
+    RootRedirect,
+    /// 跳转到登录页
+    LoginRedirect,
+    /// 大量路径跳转到同一目标，被确认为登录或拦截页面
+    RepeatedRedirect,
+    /// 跟随跳转时检测到循环
+    RedirectLoop,
+    /// 跟随跳转的次数超过上限
+    TooManyRedirects,
+    /// 大量响应共用同一个登录页标题，被确认为统一登录页
+    LoginTitle,
+    /// 条件请求返回304，与上次扫描相比没有变化
+    NotModified,
+    /// 严重性低于 `--min-severity`
+    BelowSeverity(Severity),
+    /// 2xx响应小于 `--min-content-length`
+    TooSmall(usize),
+    /// 智能匹配模式下响应与目标首页一致，只是回显了首页
+    Homepage,
+    /// `--unique-titles` 模式下同一标题已经报告过
+    DuplicateTitle(String),
+}
+
+impl FilterReason {
+    /// 稳定的原因标识，供结构化输出使用
+    pub fn code(&self) -> &'static str {
+        match self {
+            FilterReason::Signature(_) => "signature",
+            FilterReason::FilteredSize(_) => "filtered_size",
+            FilterReason::RepeatedSize => "repeated_size",
+            FilterReason::SelfRedirect => "self_redirect",
+            FilterReason::RootRedirect => "root_redirect",
+            FilterReason::LoginRedirect => "login_redirect",
+            FilterReason::RepeatedRedirect => "repeated_redirect",
+            FilterReason::RedirectLoop => "redirect_loop",
+            FilterReason::TooManyRedirects => "too_many_redirects",
+            FilterReason::LoginTitle => "login_title",
+            FilterReason::NotModified => "not_modified",
+            FilterReason::BelowSeverity(_) => "below_severity",
+            FilterReason::TooSmall(_) => "too_small",
+            FilterReason::Homepage => "homepage",
+            FilterReason::DuplicateTitle(_) => "duplicate_title",
+        }
+    }
+}
+
+impl fmt::Display for FilterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterReason::Signature(signature) => write!(f, "命中内容特征 '{}'", signature),
+            FilterReason::FilteredSize(size) => write!(f, "响应大小 {} 字节在过滤列表中", size),
+            FilterReason::RepeatedSize => write!(f, "重复响应大小"),
+            FilterReason::SelfRedirect => write!(f, "跳转回自身"),
+            FilterReason::RootRedirect => write!(f, "跳转到首页"),
+            FilterReason::LoginRedirect => write!(f, "跳转到登录页"),
+            FilterReason::RepeatedRedirect => write!(f, "重复跳转目标"),
+            FilterReason::RedirectLoop => write!(f, "跳转循环"),
+            FilterReason::TooManyRedirects => write!(f, "跳转次数超过上限"),
+            FilterReason::LoginTitle => write!(f, "统一登录页标题"),
+            FilterReason::NotModified => write!(f, "与上次扫描相比未修改"),
+            FilterReason::BelowSeverity(severity) => write!(f, "严重性 {} 低于阈值", severity),
+            FilterReason::TooSmall(min) => write!(f, "响应小于 {} 字节", min),
+            FilterReason::Homepage => write!(f, "与首页相同"),
+            FilterReason::DuplicateTitle(title) => write!(f, "标题 '{}' 已报告过", title),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::finding::Finding;
+
+/// 同时运行的 `--on-finding` 命令数上限，超出的命令排队等待
+const MAX_RUNNING: usize = 4;
+
+/// 为每条有效发现执行一次外部命令，命令在后台运行，不阻塞扫描
+///
+/// 命令交给系统shell执行；发现的URL、状态码、大小作为位置参数 `$1`、`$2`、`$3` 传入，
+/// 同时设置环境变量 `RDIRSCAN_URL`、`RDIRSCAN_STATUS`、`RDIRSCAN_SIZE`、`RDIRSCAN_SEVERITY`
+pub(crate) struct FindingHook {
+    command: String,
+    limit: Arc<Semaphore>,
+    running: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl FindingHook {
+    pub(crate) fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            limit: Arc::new(Semaphore::new(MAX_RUNNING)),
+            running: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 为一条发现启动命令，不等待其结束
+    pub(crate) fn spawn(&self, finding: &Finding) {
+        let mut command = shell_command(&self.command);
+        let url = finding.url.to_string();
+        let status = finding.status.as_u16().to_string();
+        let size = finding.size.to_string();
+        if cfg!(unix) {
+            command.args([&url, &status, &size]);
+        }
+        command
+            .env("RDIRSCAN_URL", &url)
+            .env("RDIRSCAN_STATUS", &status)
+            .env("RDIRSCAN_SIZE", &size)
+            .env("RDIRSCAN_SEVERITY", finding.severity.to_string())
+            .stdin(Stdio::null());
+        let limit = Arc::clone(&self.limit);
+        let handle = tokio::spawn(async move {
+            let _permit = limit.acquire_owned().await.expect("命令信号量不会被关闭");
+            match command.status().await {
+                Ok(status) if status.success() => {}
+                Ok(status) => warn!(url = %url, status = %status, "--on-finding 命令退出状态非零"),
+                Err(e) => warn!(error = %e, "无法执行 --on-finding 命令"),
+            }
+        });
+        let mut running = self.running.lock().unwrap();
+        running.retain(|handle| !handle.is_finished());
+        running.push(handle);
+    }
+
+    /// 等待所有已启动的命令结束
+    pub(crate) async fn wait(&self) {
+        let running = std::mem::take(&mut *self.running.lock().unwrap());
+        for handle in running {
+            let _ = handle.await;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    // `$0` 为程序名，之后的参数依次成为 `$1`、`$2`……
+    shell.arg("-c").arg(command).arg("rdirscan");
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
@@ -0,0 +1,122 @@
+use encoding_rs::{Encoding, GBK, UTF_8};
+
+/// 提取HTML中 `<title>` 的文本，合并空白，找不到或为空时返回 `None`
+pub(crate) fn extract_title(content: &str) -> Option<String> {
+    // 只在开头一段内查找，避免对大文件整体转小写
+    let head: String = content.chars().take(8192).collect();
+    let lower = head.to_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    // 转小写可能改变字节长度，此时退回不截取
+    let raw = head.get(start..end).filter(|_| lower.len() == head.len())?;
+    let title = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// 查找 `<meta>` 声明的字符集时只看响应开头这么多字节
+const META_SNIFF_BYTES: usize = 1024;
+
+/// 解码响应体，字符集依次取自 `Content-Type`、HTML开头的 `<meta>` 声明；
+/// 都没有声明时按UTF-8解码，不是有效的UTF-8但能完整按GBK解码时改用GBK（常见于未声明编码的中文站点）
+pub(crate) fn decode_body(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(charset_label)
+        .or_else(|| meta_charset(bytes))
+        .unwrap_or_else(|| detect_encoding(bytes));
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// 取出 `charset=` 之后的字符集名称
+fn charset_label(value: &str) -> Option<&'static Encoding> {
+    let lower = value.to_ascii_lowercase();
+    let start = lower.find("charset=")? + "charset=".len();
+    let label: String = lower[start..]
+        .trim_start_matches(|c: char| c == '"' || c == '\'' || c.is_whitespace())
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect();
+    Encoding::for_label(label.as_bytes())
+}
+
+/// `<meta charset="gbk">` 或 `<meta http-equiv="Content-Type" content="text/html; charset=gb2312">`
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(META_SNIFF_BYTES)]).to_ascii_lowercase();
+    head.match_indices("<meta")
+        .filter_map(|(start, _)| {
+            let tag = &head[start..];
+            charset_label(&tag[..tag.find('>').unwrap_or(tag.len())])
+        })
+        .next()
+}
+
+fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => UTF_8,
+        // 只是末尾的字符被截断（提前停止读取或 --max-body-size）时仍是UTF-8
+        Err(e) if e.error_len().is_none() => UTF_8,
+        Err(_) if GBK.decode_without_bom_handling_and_without_replacement(bytes).is_some() => GBK,
+        Err(_) => UTF_8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gbk(text: &str) -> Vec<u8> {
+        GBK.encode(text).0.into_owned()
+    }
+
+    #[test]
+    fn reads_charset_labels() {
+        assert_eq!(charset_label("text/html; charset=GBK"), Some(GBK));
+        assert_eq!(charset_label("text/html; charset=\"utf-8\""), Some(UTF_8));
+        // gb2312 按WHATWG标准映射为GBK
+        assert_eq!(charset_label("text/html;charset='gb2312'"), Some(GBK));
+        assert_eq!(charset_label("text/html"), None);
+        assert_eq!(charset_label("text/html; charset=unknown-charset"), None);
+    }
+
+    #[test]
+    fn finds_meta_charsets() {
+        assert_eq!(meta_charset(b"<html><head><meta charset=\"gbk\"><title>"), Some(GBK));
+        assert_eq!(
+            meta_charset(b"<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=gb2312\">"),
+            Some(GBK)
+        );
+        // 不声明字符集的 <meta> 跳过，继续查找后面的
+        assert_eq!(meta_charset(b"<meta name=\"viewport\"><meta charset=utf-8>"), Some(UTF_8));
+        assert_eq!(meta_charset(b"<html><head><title>"), None);
+        // 只看开头一段
+        let mut late = vec![b' '; META_SNIFF_BYTES];
+        late.extend_from_slice(b"<meta charset=\"gbk\">");
+        assert_eq!(meta_charset(&late), None);
+    }
+
+    #[test]
+    fn detects_undeclared_encodings() {
+        assert_eq!(detect_encoding("<title>管理后台</title>".as_bytes()), UTF_8);
+        // 截断在多字节字符中间的UTF-8
+        let utf8 = "管理后台".as_bytes();
+        assert_eq!(detect_encoding(&utf8[..utf8.len() - 1]), UTF_8);
+        assert_eq!(detect_encoding(&gbk("<title>管理后台</title>")), GBK);
+        // 既不是UTF-8也不是GBK时按UTF-8解码（带替换字符）
+        assert_eq!(detect_encoding(&[b'a', 0xff, 0xff]), UTF_8);
+    }
+
+    #[test]
+    fn decodes_bodies_by_declared_or_detected_charset() {
+        let body = gbk("<title>管理后台</title>");
+        assert_eq!(decode_body(&body, Some("text/html; charset=gbk")), "<title>管理后台</title>");
+        assert_eq!(decode_body(&body, None), "<title>管理后台</title>");
+
+        let mut declared = b"<meta charset=\"gbk\">".to_vec();
+        declared.extend_from_slice(&gbk("后台"));
+        assert_eq!(decode_body(&declared, Some("text/html")), "<meta charset=\"gbk\">后台");
+        // Content-Type 的声明优先于 <meta>
+        let utf8 = "<meta charset=\"gbk\">后台".as_bytes();
+        assert_eq!(decode_body(utf8, Some("text/html; charset=utf-8")), "<meta charset=\"gbk\">后台");
+        assert_eq!(extract_title(&decode_body(&body, None)).as_deref(), Some("管理后台"));
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use reqwest::Client;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::dns::CachingResolver;
+
+type ClientFactory = Box<dyn Fn(Arc<CachingResolver>) -> Result<Client> + Send + Sync>;
+
+/// `--max-per-ip`：主机解析到多个地址（DNS轮询、负载均衡）时，限制每个后端地址上的并发请求数
+///
+/// 每个地址使用一个只把目标主机解析到该地址的客户端，请求前轮流挑选有空闲名额的地址；都满时等待轮到的地址
+pub(crate) struct IpPool {
+    limit: usize,
+    resolver: Arc<CachingResolver>,
+    factory: ClientFactory,
+    // 客户端按主机和地址区分，名额按地址计算，多个主机共用同一地址时共享名额
+    clients: Mutex<HashMap<(String, IpAddr), Client>>,
+    permits: Mutex<HashMap<IpAddr, Arc<Semaphore>>>,
+    next: AtomicUsize,
+}
+
+/// 选定的后端：请求期间持有名额，释放后其他请求才能使用该地址
+pub(crate) struct Backend {
+    pub(crate) client: Client,
+    pub(crate) ip: IpAddr,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl IpPool {
+    pub(crate) fn new(
+        limit: usize,
+        resolver: Arc<CachingResolver>,
+        factory: impl Fn(Arc<CachingResolver>) -> Result<Client> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            limit,
+            resolver,
+            factory: Box::new(factory),
+            clients: Mutex::default(),
+            permits: Mutex::default(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// 为发往 `host` 的请求挑选后端地址并占用一个名额
+    pub(crate) async fn acquire(&self, host: &str) -> Result<Backend> {
+        let addrs = self.resolver.addresses(host).await?;
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut candidates = Vec::with_capacity(addrs.len());
+        for i in 0..addrs.len() {
+            let ip = addrs[(start + i) % addrs.len()].ip();
+            if candidates.iter().any(|(candidate, _, _)| *candidate == ip) {
+                continue;
+            }
+            let (client, semaphore) = self.backend(host, ip)?;
+            if let Ok(permit) = Arc::clone(&semaphore).try_acquire_owned() {
+                return Ok(Backend { client, ip, _permit: permit });
+            }
+            candidates.push((ip, client, semaphore));
+        }
+        // 所有地址都已满，等待轮到的那个
+        let (ip, client, semaphore) = candidates.swap_remove(0);
+        let permit = semaphore.acquire_owned().await?;
+        Ok(Backend { client, ip, _permit: permit })
+    }
+
+    fn backend(&self, host: &str, ip: IpAddr) -> Result<(Client, Arc<Semaphore>)> {
+        let semaphore = Arc::clone(self.permits.lock().unwrap()
+            .entry(ip)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit))));
+        let mut clients = self.clients.lock().unwrap();
+        let key = (host.to_lowercase(), ip);
+        if let Some(client) = clients.get(&key) {
+            return Ok((client.clone(), semaphore));
+        }
+        let client = (self.factory)(Arc::new(self.resolver.pinned(host, ip)))?;
+        clients.insert(key, client.clone());
+        Ok((client, semaphore))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn pool(limit: usize, created: Arc<AtomicUsize>) -> IpPool {
+        let ips: Vec<IpAddr> = vec!["10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()];
+        let resolver = CachingResolver::default()
+            .with_addresses("target.example", &ips)
+            .with_addresses("mirror.example", &ips[..1]);
+        IpPool::new(limit, Arc::new(resolver), move |_| {
+            created.fetch_add(1, Ordering::SeqCst);
+            Ok(Client::new())
+        })
+    }
+
+    #[tokio::test]
+    async fn rotates_between_addresses_within_the_limit() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let pool = pool(1, Arc::clone(&created));
+
+        let first = pool.acquire("target.example").await.unwrap();
+        let second = pool.acquire("target.example").await.unwrap();
+        assert_ne!(first.ip, second.ip);
+
+        // 两个地址都已满，等到有名额释放才返回
+        let waiting = pool.acquire("target.example");
+        tokio::pin!(waiting);
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut waiting).await.is_err());
+        let released = first.ip;
+        drop(first);
+        let third = tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        assert_eq!(third.ip, released);
+
+        // 每个地址只创建一个客户端
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn hosts_on_the_same_address_share_its_limit() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let pool = pool(1, Arc::clone(&created));
+
+        let mirror = pool.acquire("mirror.example").await.unwrap();
+        assert_eq!(mirror.ip, "10.0.0.1".parse::<IpAddr>().unwrap());
+        // 10.0.0.1 的名额已被占用，目标主机只能使用另一个地址
+        for _ in 0..2 {
+            let backend = pool.acquire("target.example").await.unwrap();
+            assert_eq!(backend.ip, "10.0.0.2".parse::<IpAddr>().unwrap());
+        }
+        // 不同主机各自使用只固定自己的客户端
+        assert_eq!(created.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::parse_header;
+
+/// 任务文件中的一个扫描任务，未指定的设置沿用命令行参数
+#[derive(Debug, Clone)]
+pub(crate) struct Job {
+    pub url: String,
+    /// 字典文件，未指定时使用 `-d` 或默认字典
+    pub dict: Option<String>,
+    /// 在命令行请求头之外追加的请求头
+    pub headers: Vec<(String, String)>,
+    /// 输出文件，未指定时写入 `out-<序号>.txt`
+    pub output: Vec<String>,
+}
+
+/// `--jobs` 指定的任务文件
+#[derive(Debug, Clone)]
+pub(crate) struct JobFile {
+    pub jobs: Vec<Job>,
+    /// 同时运行所有任务，否则按顺序逐个运行
+    pub concurrent: bool,
+}
+
+impl JobFile {
+    /// 读取任务文件，扩展名为 `.json` 时按JSON解析，否则按TOML解析
+    ///
+    /// JSON文件也可以直接是任务数组
+    pub(crate) fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("读取任务文件失败: {}", e))?;
+        let is_json = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let value = if is_json {
+            serde_json::from_str(&content).map_err(|e| anyhow!("任务文件格式错误: {}", e))?
+        } else {
+            let table: toml::Table = content.parse()
+                .map_err(|e| anyhow!("任务文件格式错误: {}", e))?;
+            serde_json::to_value(table)?
+        };
+        Self::from_value(value)
+    }
+
+    fn from_value(value: Value) -> Result<Self> {
+        let (jobs, concurrent) = match value {
+            Value::Array(jobs) => (jobs, false),
+            Value::Object(mut table) => {
+                let concurrent = match table.remove("concurrent") {
+                    None => false,
+                    Some(Value::Bool(concurrent)) => concurrent,
+                    Some(_) => return Err(anyhow!("任务文件中 'concurrent' 必须是布尔值")),
+                };
+                let jobs = match table.remove("jobs") {
+                    Some(Value::Array(jobs)) => jobs,
+                    _ => return Err(anyhow!("任务文件中缺少任务列表 'jobs'")),
+                };
+                if let Some(key) = table.keys().next() {
+                    return Err(anyhow!("任务文件中存在未知选项 '{}'", key));
+                }
+                (jobs, concurrent)
+            }
+            _ => return Err(anyhow!("任务文件必须是任务列表或包含 'jobs' 的表")),
+        };
+        if jobs.is_empty() {
+            return Err(anyhow!("任务文件中没有任务"));
+        }
+        let jobs = jobs
+            .into_iter()
+            .enumerate()
+            .map(|(index, job)| parse_job(job).map_err(|e| anyhow!("第 {} 个任务: {}", index + 1, e)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { jobs, concurrent })
+    }
+}
+
+fn parse_job(value: Value) -> Result<Job> {
+    let Value::Object(table) = value else {
+        return Err(anyhow!("任务必须是表"));
+    };
+    let mut job = Job {
+        url: String::new(),
+        dict: None,
+        headers: Vec::new(),
+        output: Vec::new(),
+    };
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("url", Value::String(url)) => job.url = url,
+            ("dict", Value::String(dict)) => job.dict = Some(dict),
+            ("headers", Value::Array(headers)) => {
+                for header in headers {
+                    let Value::String(header) = header else {
+                        return Err(anyhow!("请求头必须写成 \"NAME: VALUE\" 字符串"));
+                    };
+                    job.headers.push(parse_header(&header)?);
+                }
+            }
+            ("headers", Value::Object(headers)) => {
+                for (name, value) in headers {
+                    let Value::String(value) = value else {
+                        return Err(anyhow!("请求头 '{}' 的值必须是字符串", name));
+                    };
+                    job.headers.push((name, value));
+                }
+            }
+            ("output", Value::String(output)) => job.output = vec![output],
+            ("output", Value::Array(outputs)) => {
+                for output in outputs {
+                    let Value::String(output) = output else {
+                        return Err(anyhow!("输出文件必须是字符串"));
+                    };
+                    job.output.push(output);
+                }
+            }
+            ("url" | "dict" | "headers" | "output", _) => {
+                return Err(anyhow!("选项 '{}' 的值类型不受支持", key));
+            }
+            _ => return Err(anyhow!("未知选项 '{}'", key)),
+        }
+    }
+    if job.url.is_empty() {
+        return Err(anyhow!("缺少目标URL 'url'"));
+    }
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn error(value: Value) -> String {
+        format!("{:#}", JobFile::from_value(value).unwrap_err())
+    }
+
+    #[test]
+    fn parses_job_tables_and_lists() {
+        let file = JobFile::from_value(json!({
+            "concurrent": true,
+            "jobs": [
+                {
+                    "url": "http://a.example/",
+                    "dict": "admin.txt",
+                    "headers": ["X-Token: 1", "X-Trace:2"],
+                    "output": ["a.json", "a.csv"],
+                },
+                { "url": "http://b.example/", "headers": { "Cookie": "sid=1" }, "output": "b.txt" },
+            ],
+        }))
+        .unwrap();
+        assert!(file.concurrent);
+        let [a, b] = &file.jobs[..] else { panic!("应有两个任务") };
+        assert_eq!(a.url, "http://a.example/");
+        assert_eq!(a.dict.as_deref(), Some("admin.txt"));
+        assert_eq!(a.headers, [("X-Token".to_string(), "1".to_string()), ("X-Trace".to_string(), "2".to_string())]);
+        assert_eq!(a.output, ["a.json", "a.csv"]);
+        assert_eq!(b.dict, None);
+        assert_eq!(b.headers, [("Cookie".to_string(), "sid=1".to_string())]);
+        assert_eq!(b.output, ["b.txt"]);
+
+        // JSON文件可以直接是任务数组，默认逐个运行
+        let file = JobFile::from_value(json!([{ "url": "http://a.example/" }])).unwrap();
+        assert!(!file.concurrent);
+        assert_eq!(file.jobs.len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_job_files() {
+        assert_eq!(error(json!("jobs")), "任务文件必须是任务列表或包含 'jobs' 的表");
+        assert_eq!(error(json!({ "concurrent": true })), "任务文件中缺少任务列表 'jobs'");
+        assert_eq!(error(json!({ "jobs": [], "concurrent": "yes" })), "任务文件中 'concurrent' 必须是布尔值");
+        assert_eq!(error(json!({ "jobs": [{ "url": "http://a.example/" }], "threads": 10 })), "任务文件中存在未知选项 'threads'");
+        assert_eq!(error(json!([])), "任务文件中没有任务");
+    }
+
+    #[test]
+    fn reports_which_job_is_invalid() {
+        let jobs = |job: Value| json!([{ "url": "http://a.example/" }, job]);
+        assert_eq!(error(jobs(json!("http://b.example/"))), "第 2 个任务: 任务必须是表");
+        assert_eq!(error(jobs(json!({ "dict": "a.txt" }))), "第 2 个任务: 缺少目标URL 'url'");
+        assert_eq!(error(jobs(json!({ "url": "http://b.example/", "depth": 2 }))), "第 2 个任务: 未知选项 'depth'");
+        assert_eq!(error(jobs(json!({ "url": 1 }))), "第 2 个任务: 选项 'url' 的值类型不受支持");
+        assert_eq!(
+            error(jobs(json!({ "url": "http://b.example/", "headers": [1] }))),
+            "第 2 个任务: 请求头必须写成 \"NAME: VALUE\" 字符串"
+        );
+        assert_eq!(
+            error(jobs(json!({ "url": "http://b.example/", "headers": { "X-Id": 1 } }))),
+            "第 2 个任务: 请求头 'X-Id' 的值必须是字符串"
+        );
+        assert!(error(jobs(json!({ "url": "http://b.example/", "headers": ["no-colon"] }))).contains("格式错误"));
+        assert_eq!(error(jobs(json!({ "url": "http://b.example/", "output": [true] }))), "第 2 个任务: 输出文件必须是字符串");
+    }
+}
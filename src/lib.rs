@@ -0,0 +1,94 @@
+//! RDirScan 扫描核心
+//!
+//! 命令行工具只是这里的一层薄封装，也可以直接在其他 Rust 工具中驱动扫描：
+//!
+//! ```no_run
+//! use rdirscan::{ScanConfig, Scanner};
+//!
+//! # async fn demo() -> anyhow::Result<()> {
+//! let config = ScanConfig::builder()
+//!     .url("http://example.com/")
+//!     .threads(20)
+//!     .build()?;
+//! let scanner = Scanner::new(config)?;
+//! let report = scanner.run(vec!["admin/".into(), "backup.zip".into()]).await?;
+//! for finding in report.findings.iter().filter(|f| !f.filtered) {
+//!     println!("{} {}", finding.status, finding.url);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod baseline;
+#[cfg(any(test, feature = "benchmark"))]
+mod bench;
+mod cert;
+mod cluster;
+mod codes;
+mod concurrency;
+mod conditional;
+mod config;
+mod console;
+mod csrf;
+mod curl;
+pub mod diff;
+pub mod dict;
+mod digest;
+mod dns;
+mod events;
+mod finding;
+mod hook;
+mod html;
+mod ippool;
+mod logging;
+mod login;
+mod methods;
+mod output;
+mod pause;
+mod proxypool;
+mod ratelimit;
+mod recursion;
+mod report;
+mod scanner;
+mod selftest;
+mod severity;
+mod signing;
+mod sqlite;
+mod state;
+mod stats;
+mod template;
+mod trace;
+mod tree;
+mod ua;
+mod unix;
+mod warmup;
+mod webhook;
+
+#[cfg(feature = "benchmark")]
+pub use bench::{run_benchmark, BenchmarkReport};
+pub use cert::{CertificateInfo, EXPIRY_WARNING_DAYS};
+pub use cluster::ResponseCluster;
+pub use codes::StatusCodes;
+pub use concurrency::suggest_threads;
+pub use console::{on_input_line, print_line};
+pub use config::{
+    validate_url, with_port, ScanConfig, ScanConfigBuilder, DEFAULT_MAX_URL_LENGTH, DEFAULT_SENSITIVE_EXTENSIONS, WORD_PLACEHOLDER,
+};
+pub use csrf::CsrfExtractor;
+pub use diff::{PreviousFinding, Replayed, ScanDiff};
+pub use finding::{FilterReason, Finding};
+pub use login::LoginOutcome;
+pub use logging::{init_logging, LogFormat};
+pub use methods::MethodDiscrepancy;
+pub use output::OutputEncoding;
+pub use pause::PauseControl;
+pub use report::{ErrorKind, PathError, ScanReport, StopReason};
+pub use scanner::Scanner;
+pub use selftest::{run_selftest, SelftestCheck};
+pub use severity::Severity;
+pub use state::DEFAULT_FILTER_THRESHOLD;
+pub use signing::{SignTemplate, DEFAULT_SIGN_HEADER, DEFAULT_SIGN_TEMPLATE, DEFAULT_TIMESTAMP_HEADER};
+pub use stats::{LiveStats, StatsSnapshot};
+pub use template::OutputTemplate;
+pub use warmup::WarmupOutcome;
+pub use tree::render_tree;
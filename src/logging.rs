@@ -0,0 +1,71 @@
+//! 诊断日志：`tracing_subscriber` 的格式化输出，按 `RUST_LOG` 过滤，输出到标准错误
+//!
+//! 扫描发现等面向用户的彩色输出不经过这里，只有警告、错误和调试信息使用日志
+
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+/// 未设置 `RUST_LOG` 时的日志级别
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::WARN;
+
+/// 日志的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// 按级别着色的单行文本
+    #[default]
+    Pretty,
+    /// 每行一个JSON对象，便于日志系统收集
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!("不支持的日志格式 '{}'，可选 pretty、json", s)),
+        }
+    }
+}
+
+/// 安装全局日志订阅者，级别由环境变量 `RUST_LOG` 控制（如 `debug`、`rdirscan::scanner=trace`），默认只输出警告和错误
+///
+/// 依赖库通过 `log` 输出的日志也会转交给订阅者
+pub fn init_logging(format: LogFormat) -> Result<()> {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(env_filter(std::env::var("RUST_LOG").ok().as_deref())?)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal());
+    let installed = match format {
+        LogFormat::Pretty => builder.without_time().with_target(false).try_init(),
+        LogFormat::Json => builder.json().with_current_span(false).with_span_list(true).try_init(),
+    };
+    installed.map_err(|e| anyhow!("日志初始化失败: {}", e))
+}
+
+/// 按 `RUST_LOG` 的写法构造过滤器，未设置或为空时使用默认级别
+fn env_filter(directives: Option<&str>) -> Result<EnvFilter> {
+    EnvFilter::builder()
+        .with_default_directive(DEFAULT_LEVEL.into())
+        .parse(directives.unwrap_or_default())
+        .map_err(|e| anyhow!("RUST_LOG 无效: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rust_log_directives() {
+        assert!(env_filter(Some("warn,rdirscan::scanner=trace,rdirscan=debug")).is_ok());
+        assert!(env_filter(None).is_ok());
+        assert!(env_filter(Some("")).is_ok());
+        assert!(env_filter(Some("rdirscan=loud")).is_err());
+    }
+}
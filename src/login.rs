@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{CONTENT_TYPE, LOCATION, SET_COOKIE};
+use reqwest::Client;
+use url::{form_urlencoded, Url};
+
+use crate::config::ScanConfig;
+use crate::csrf;
+use crate::html::decode_body;
+
+/// 登录成功后的结果
+#[derive(Debug, Clone)]
+pub struct LoginOutcome {
+    /// 登录请求的最终状态码
+    pub status: u16,
+    /// 目标URL可用的Cookie数
+    pub cookies: usize,
+    /// 从登录页提取并提交了CSRF令牌时，令牌所在的表单字段
+    pub csrf_field: Option<String>,
+}
+
+/// 提交登录表单，会话Cookie保存在所有客户端共享的 `jar` 中
+///
+/// 成功条件：配置了 `login_failure` 时响应中不能出现该文本；配置了 `login_success` 时
+/// 响应内容或跳转地址中必须出现该文本；都未配置时要求状态码不是4xx/5xx且拿到了Cookie
+pub(crate) async fn login(client: &Client, config: &ScanConfig, jar: &Arc<Jar>) -> Result<LoginOutcome> {
+    let login_url = config.login_url.as_deref()
+        .ok_or_else(|| anyhow!("未设置登录地址"))?;
+    let mut data = config.login_data.clone().unwrap_or_default();
+    let mut csrf_field = None;
+    if let Some(ref extractor) = config.csrf {
+        // 登录页通常同时设置会话Cookie，令牌与该会话绑定
+        let page = config.login_page.as_deref().unwrap_or(login_url);
+        let html = fetch_page(client, config, page).await?;
+        let (name, token) = csrf::extract(&html, extractor)?;
+        let field = config.csrf_field.clone().or(name)
+            .ok_or_else(|| anyhow!("无法确定CSRF令牌的字段名，请使用 --csrf-field 指定"))?;
+        if !data.is_empty() {
+            data.push('&');
+        }
+        data.push_str(&form_urlencoded::Serializer::new(String::new()).append_pair(&field, &token).finish());
+        csrf_field = Some(field);
+    }
+    let mut request = client
+        .post(login_url)
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(data);
+    for (name, value) in &config.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let resp = tokio::time::timeout(config.timeout, request.send()).await
+        .map_err(|_| anyhow!("登录请求超时"))?
+        .map_err(|e| anyhow!("登录请求失败: {}", e))?;
+
+    let status = resp.status();
+    let location = resp.headers()
+        .get(LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let set_cookies = resp.headers().get_all(SET_COOKIE).iter().count();
+    let body = tokio::time::timeout(config.timeout, read_text(resp)).await
+        .map_err(|_| anyhow!("读取登录响应超时"))?
+        .unwrap_or_default();
+
+    if let Some(ref failure) = config.login_failure {
+        if body.contains(failure.as_str()) {
+            return Err(anyhow!("登录失败: 响应中出现了 '{}'", failure));
+        }
+    }
+    if let Some(ref success) = config.login_success {
+        if !body.contains(success.as_str()) && !location.contains(success.as_str()) {
+            return Err(anyhow!("登录失败: 响应中没有出现 '{}'（状态码 {}）", success, status.as_u16()));
+        }
+    } else if status.is_client_error() || status.is_server_error() {
+        return Err(anyhow!("登录失败: 状态码 {}", status.as_u16()));
+    } else if set_cookies == 0 {
+        return Err(anyhow!("登录失败: 服务器没有设置Cookie（可用 --login-success 指定成功标志）"));
+    }
+
+    let target = config.base_url.as_deref().unwrap_or(login_url);
+    let cookies = Url::parse(target)
+        .ok()
+        .and_then(|url| jar.cookies(&url))
+        .and_then(|value| value.to_str().map(|value| value.split("; ").count()).ok())
+        .unwrap_or(0);
+    Ok(LoginOutcome {
+        status: status.as_u16(),
+        cookies,
+        csrf_field,
+    })
+}
+
+/// 请求登录页并返回其内容
+async fn fetch_page(client: &Client, config: &ScanConfig, url: &str) -> Result<String> {
+    let mut request = client.get(url);
+    for (name, value) in &config.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let resp = tokio::time::timeout(config.timeout, request.send()).await
+        .map_err(|_| anyhow!("请求登录页超时"))?
+        .map_err(|e| anyhow!("请求登录页失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("请求登录页失败: 状态码 {}", resp.status().as_u16()));
+    }
+    tokio::time::timeout(config.timeout, read_text(resp)).await
+        .map_err(|_| anyhow!("读取登录页超时"))?
+        .map_err(|e| anyhow!("读取登录页失败: {}", e))
+}
+
+/// 读取响应体，按与扫描时相同的规则识别字符集
+async fn read_text(resp: reqwest::Response) -> reqwest::Result<String> {
+    let content_type = resp.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let bytes = resp.bytes().await?;
+    Ok(decode_body(&bytes, content_type.as_deref()))
+}
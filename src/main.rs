@@ -1,19 +1,42 @@
-use std::fs::File;
-use std::io::{self, BufRead, Write, stdin};
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 use std::time::Duration;
-use std::collections::{HashSet, HashMap};
-use tokio::sync::Mutex;
 
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
 use colored::*;
-use futures::StreamExt;
-use rand::seq::SliceRandom;
-use reqwest::Client;
-use reqwest::Proxy;
+use futures::{future, StreamExt};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_stream::wrappers::LinesStream;
+use tracing::warn;
+use rdirscan::{
+    dict, diff, print_line, render_tree, with_port, CertificateInfo, CsrfExtractor, PauseControl, Replayed, ScanConfig, ScanConfigBuilder, ScanDiff, ScanReport, Scanner, WarmupOutcome,
+    DEFAULT_SIGN_HEADER, DEFAULT_SIGN_TEMPLATE, EXPIRY_WARNING_DAYS,
+};
 use url::Url;
 
+mod job;
+mod tui;
+
+use job::{Job, JobFile};
+use tui::Dashboard;
+
+/// 扫描结束时打印的响应类别数
+const SUMMARY_CLUSTERS: usize = 10;
+/// 汇总中列出的最常见响应大小数
+const SUMMARY_SIZES: usize = 5;
+/// 响应数不少于该值而大小种类不超过 `FEW_SIZES` 时提示可能是泛解析或WAF
+const FEW_SIZES_MIN_RESPONSES: usize = 20;
+const FEW_SIZES: usize = 2;
+
 /// 网站目录扫描工具
 /// 作者: TomHe
 #[derive(Parser, Debug)]
@@ -31,47 +54,99 @@ use url::Url;
                   - 支持 HTTP/HTTPS\n\
                   - 自定义字典支持\n\
                   - 支持过滤误报页面\n\
-                  - 自动检测WAF/登录页面"
+                  - 自动检测WAF/登录页面",
+    args_override_self = true,
+    subcommand_negates_reqs = true
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// 目标URL（例如：http://example.com）
-    #[arg(short, long, help = "目标URL，必须包含 http:// 或 https://")]
-    url: String,
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["stdin_urls", "list_builtin", "benchmark", "jobs", "replay"],
+        conflicts_with = "stdin_urls",
+        help = "目标URL，必须包含 http:// 或 https://"
+    )]
+    url: Option<String>,
 
     /// 字典文件路径
-    #[arg(short, long, default_value = "Dir.txt", help = "扫描字典文件路径，每行一个路径，支持自定义")]
-    dict: String,
+    #[arg(
+        short,
+        long,
+        help = "扫描字典文件路径，每行一个路径，支持自定义；未指定时使用 Dir.txt，不存在或为空则使用内置字典"
+    )]
+    dict: Option<String>,
+
+    /// 打印内置字典
+    #[arg(long = "list-builtin", help = "打印内置字典后退出")]
+    list_builtin: bool,
 
     /// 代理服务器地址
     #[arg(short, long, help = "代理服务器地址（例如：socks5://127.0.0.1:1080）")]
     proxy: Option<String>,
 
-    /// 请求超时时间（秒）
+    /// 代理池文件
+    #[arg(
+        long = "proxy-file",
+        value_name = "FILE",
+        conflicts_with_all = ["proxy", "unix_socket", "max_per_ip", "ua_per_connection"],
+        help = "代理列表文件，每行一个代理，请求在可用的代理间轮流分配；连续出错的代理暂停使用，定期健康检查后恢复"
+    )]
+    proxy_file: Option<String>,
+
+    /// 代理检查地址
+    #[arg(
+        long = "proxy-check-url",
+        value_name = "URL",
+        requires = "proxy_file",
+        help = "代理池健康检查请求的地址，收到任何HTTP响应即视为代理可用 [默认: 目标URL]"
+    )]
+    proxy_check_url: Option<String>,
+
+    /// 代理检查间隔
+    #[arg(
+        long = "proxy-check-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        requires = "proxy_file",
+        help = "代理池健康检查的间隔（如 30s、2m）[默认: 30s]"
+    )]
+    proxy_check_interval: Option<Duration>,
+
+    /// 请求超时时间
     #[arg(
         short = 'w', 
         long, 
         default_value = "10",
-        help = "请求超时时间（秒），包括连接和读取时间"
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "请求超时时间，包括连接和读取时间；支持 500ms、2s、1m 等单位，纯数字表示秒"
     )]
-    timeout: u64,
+    timeout: Duration,
 
-    /// 连接超时时间（秒）
+    /// 连接超时时间
     #[arg(
         short = 'c',
         long = "connect-timeout",
         default_value = "5",
-        help = "连接超时时间（秒）"
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "连接超时时间；支持 500ms、2s、1m 等单位，纯数字表示秒"
     )]
-    connect_timeout: u64,
+    connect_timeout: Duration,
 
     /// 并发线程数
     #[arg(
         short = 't', 
         long = "threads", 
         default_value = "10",
-        help = "并发扫描的线程数，建议根据目标网站的承受能力调整"
+        value_parser = parse_threads,
+        help = "并发扫描的线程数，建议根据目标网站的承受能力调整；auto 按CPU核心数和预热请求测得的目标延迟自动选择"
     )]
-    threads: usize,
+    threads: ThreadCount,
 
     /// 误报过滤文件
     #[arg(
@@ -89,301 +164,2377 @@ struct Args {
         default_value = "false"
     )]
     insecure: bool,
-}
 
-#[derive(Clone)]
-struct ScanState {
-    content_signatures: HashSet<String>,
-    // 记录响应大小和连续出现次数
-    size_counter: HashMap<usize, usize>,
-    // 记录已确认为WAF/登录页面的响应大小
-    filtered_sizes: HashSet<usize>,
-}
+    /// 额外信任的CA证书
+    #[arg(
+        long = "ca-cert",
+        value_name = "FILE",
+        conflicts_with = "insecure",
+        help = "额外信任的CA证书（PEM格式，可含多个证书），可重复指定；内部PKI签发的证书无需 -k 即可验证"
+    )]
+    ca_cert: Vec<String>,
 
-impl ScanState {
-    fn new() -> Self {
-        Self {
-            content_signatures: HashSet::new(),
-            size_counter: HashMap::new(),
-            filtered_sizes: HashSet::new(),
-        }
-    }
+    /// 本地出口地址
+    #[arg(
+        long = "bind-address",
+        help = "发起请求时绑定的本地IP地址，用于多网卡环境选择出口（例如：192.168.1.10）"
+    )]
+    bind_address: Option<IpAddr>,
 
-    fn from_file(path: &str) -> Result<Self> {
-        let mut state = Self::new();
-        let file = File::open(path)
-            .map_err(|e| anyhow!("打开过滤规则文件失败: {}", e))?;
-        
-        let reader = io::BufReader::new(file);
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            
-            state.content_signatures.insert(line.to_string());
-        }
-        Ok(state)
-    }
+    /// 自定义域名解析
+    #[arg(
+        long = "resolve",
+        value_name = "HOST:IP",
+        help = "将主机名固定解析到指定IP（例如：example.com:10.0.0.5），可多次指定"
+    )]
+    resolve: Vec<String>,
 
-    fn is_filtered(&self, content: &str, size: usize) -> bool {
-        // 检查内容特征
-        for signature in &self.content_signatures {
-            if content.contains(signature) {
-                return true;
-            }
-        }
-        // 检查响应大小
-        self.filtered_sizes.contains(&size)
-    }
+    /// 最大请求数
+    #[arg(
+        long = "max-requests",
+        help = "最多发送的请求数，达到后停止扫描（已发出的请求会继续完成）"
+    )]
+    max_requests: Option<usize>,
 
-    async fn check_repeated_size(&mut self, size: usize) -> bool {
-        // 更新计数器
-        let count = self.size_counter.entry(size).or_insert(0);
-        *count += 1;
+    /// 最大发现数
+    #[arg(
+        long = "max-findings",
+        help = "最多记录的有效发现数，达到后停止扫描（已发出的请求会继续完成）"
+    )]
+    max_findings: Option<usize>,
 
-        // 如果已经是已过滤的大小，直接返回true
-        if self.filtered_sizes.contains(&size) {
-            return true;
-        }
+    /// URL长度上限
+    #[arg(
+        long = "max-url-length",
+        value_name = "BYTES",
+        default_value_t = rdirscan::DEFAULT_MAX_URL_LENGTH,
+        help = "拼接后的URL超过该长度时跳过请求并计数（服务器通常以414拒绝过长的URL），0表示不限制"
+    )]
+    max_url_length: usize,
 
-        // 如果连续5次相同大小，询问用户
-        if *count >= 5 && !self.filtered_sizes.contains(&size) {
-            println!("\n{}", format!("检测到连续5次响应大小为 {} 字节的页面，这可能是WAF拦截或登录跳转页面。", size).yellow());
-            print!("是否将该响应大小添加到过滤列表？(y/n): ");
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            stdin().read_line(&mut input).unwrap();
-            if input.trim().eq_ignore_ascii_case("y") {
-                self.filtered_sizes.insert(size);
-                println!("{}", "已添加到过滤列表。".green());
-                return true;
-            } else {
-                // 如果用户选择不过滤，重置计数器
-                self.size_counter.remove(&size);
-                println!("{}", "已取消过滤。".yellow());
-            }
-        }
-        false
-    }
-}
-
-const USER_AGENTS: &[&str] = &[
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:89.0) Gecko/20100101 Firefox/89.0",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/14.1.1 Safari/605.1.15",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Edge/91.0.864.59",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 6.1; WOW64; rv:45.0) Gecko/20100101 Firefox/45.0",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; Trident/7.0; AS;.NET CLR 4.0.30319) like Gecko",
-    "Mozilla/5.0 (Windows NT 6.1; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/89.0.4389.114 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 6.1; WOW64; rv:40.0) Gecko/20100101 Firefox/40.0",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/80.0.3987.149 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 6.1; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/80.0.3987.122 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/95.0.4638.69 Safari/537.36 Edge/95.0.1020.40",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:94.0) Gecko/20100101 Firefox/94.0",
-    "Mozilla/5.0 (X11; Linux i686) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/70.0.3538.77 Safari/537.36",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_14_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/92.0.4515.159 Safari/537.36",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Firefox/90.0 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Safari/537.36 Edge/90.0.818.62",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/13.0.5 Safari/605.1.15",
-    "Mozilla/5.0 (Windows NT 6.1; rv:40.0) Gecko/20100101 Firefox/40.0",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/78.0.3904.108 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36 Edg/91.0.864.64",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_12_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/64.0.3282.140 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 6.1; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/80.0.3987.149 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/90.0.4430.212 Safari/537.36",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_11_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/87.0.4280.88 Safari/537.36",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/77.0.3865.120 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/88.0.4324.182 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 6.1; rv:43.0) Gecko/20100101 Firefox/43.0",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:100.0) Gecko/20100101 Firefox/100.0",
-    "Mozilla/5.0 (X11; Ubuntu; Linux x86_64; rv:45.0) Gecko/20100101 Firefox/45.0",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_13_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/63.0.3239.84 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 6.3; Trident/7.0; AS; .NET CLR 4.0.30319) like Gecko",
-];
-
-
-fn get_random_user_agent() -> &'static str {
-    USER_AGENTS.choose(&mut rand::thread_rng()).unwrap()
-}
-
-fn validate_url(url_str: &str) -> Result<String> {
-    let url = Url::parse(url_str).map_err(|e| anyhow!("URL格式错误: {}", e))?;
-    
-    if !url.scheme().starts_with("http") {
-        return Err(anyhow!("URL必须以 http:// 或 https:// 开头"));
-    }
-
-    if url.host_str().is_none() {
-        return Err(anyhow!("URL必须包含有效的主机名"));
-    }
-
-    if let Some(port) = url.port() {
-        let port_u32 = port as u32;
-        if port_u32 > 65535 {
-            return Err(anyhow!("端口号无效：端口号必须在 0-65535 之间"));
-        }
-    }
-
-    Ok(url.to_string())
-}
-
-async fn check_path(
-    client: &Client, 
-    base_url: &str, 
-    path: &str, 
-    output_file: Arc<Mutex<File>>,
-    scan_state: Arc<Mutex<ScanState>>
-) -> Result<bool> {
-    let base = Url::parse(base_url)
-        .map_err(|e| anyhow!("基础URL解析失败: {}", e))?;
-    
-    let url = base.join(path)
-        .map_err(|e| anyhow!("路径 '{}' 拼接失败: {}", path, e))?;
-    
-    let resp = match client
-        .get(url.as_str())
-        .header("User-Agent", get_random_user_agent())
-        .send()
-        .await {
-            Ok(resp) => resp,
-            Err(e) => {
-                if e.is_timeout() {
-                    return Err(anyhow!("请求超时"));
-                }
-                if e.is_connect() {
-                    return Err(anyhow!("连接失败"));
-                }
-                return Err(anyhow!("请求失败: {}", e));
-            }
-        };
+    /// 每个状态码的输出上限
+    #[arg(
+        long = "cap-per-code",
+        value_name = "N",
+        help = "每个状态码最多向输出文件写入N条发现，超出的仍会计数并显示在汇总中"
+    )]
+    cap_per_code: Option<usize>,
 
-    let status = resp.status();
-    
-    if status.is_success() {
-        let content = match resp.text().await {
-            Ok(content) => content,
-            Err(_) => return Err(anyhow!("读取响应内容失败")),
-        };
-        let content_length = content.len();
-        
-        // 检查是否需要过滤
-        let mut state = scan_state.lock().await;
-        let is_filtered = state.is_filtered(&content, content_length) || 
-                         state.check_repeated_size(content_length).await;
-
-        let message = format!("[+] 发现: {} (状态码: {}, 大小: {} 字节)", 
-            url.as_str(), status, content_length);
-        
-        if is_filtered {
-            println!("{}", message.purple());
-        } else {
-            println!("{}", message.red());
-            // 仅在未过滤的情况下写入输出文件
-            let mut file = output_file.lock().await;
-            writeln!(file, "{} (大小: {} 字节)", url.as_str(), content_length)
-                .map_err(|e| anyhow!("写入结果到文件失败: {}", e))?;
-        }
-        
-        return Ok(true);
-    }
-    
-    Ok(false)
-}
+    /// 不跟随跳转
+    #[arg(
+        long = "no-follow-redirects",
+        help = "不自动跟随HTTP跳转，3xx响应会连同跳转目标一起报告，并过滤跳到首页、登录页等噪音"
+    )]
+    no_follow_redirects: bool,
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+    /// 最大跳转次数
+    #[arg(
+        long = "max-redirects",
+        default_value = "10",
+        help = "跟随跳转时的最大跳转次数，超过或检测到循环时停止并标记该结果"
+    )]
+    max_redirects: usize,
 
-    // 验证URL
-    let base_url = validate_url(&args.url)
-        .context("URL验证失败")?;
+    /// 禁用响应解压
+    #[arg(
+        long = "no-decompress",
+        help = "不请求也不解压 gzip/deflate/brotli 压缩响应"
+    )]
+    no_decompress: bool,
 
-    // 初始化扫描
-    let has_filter = args.filter_file.is_some();
-    let scan_state = if let Some(ref filter_path) = args.filter_file {
-        Arc::new(Mutex::new(ScanState::from_file(filter_path)?))
-    } else {
-        Arc::new(Mutex::new(ScanState::new()))
-    };
+    /// 配置文件
+    #[arg(
+        long = "config",
+        value_name = "FILE",
+        help = "TOML格式的配置文件，键名与长参数名一致（如 threads、connect-timeout），命令行参数优先"
+    )]
+    config: Option<String>,
 
+    /// 树状结构汇总
+    #[arg(
+        long = "tree",
+        help = "扫描结束后按目录层级以树状结构打印所有有效发现"
+    )]
+    tree: bool,
 
-    let mut client_builder = Client::builder()
-        .timeout(Duration::from_secs(args.timeout))
-        .connect_timeout(Duration::from_secs(args.connect_timeout))
-        .user_agent(get_random_user_agent())
-        .danger_accept_invalid_certs(args.insecure);
+    /// 异常值模式
+    #[arg(
+        long = "outliers",
+        value_name = "BYTES",
+        help = "异常值模式：大多数响应大小相同时，报告大小相差超过该字节数的响应（不论状态码）"
+    )]
+    outliers: Option<usize>,
 
-    // 代理实现
-    if let Some(ref proxy_url) = args.proxy {
-        let proxy = Proxy::all(proxy_url)
-            .map_err(|e| anyhow!("代理设置错误: {}", e))?;
-        client_builder = client_builder.proxy(proxy);
-    }
+    /// 从标准输入读取目标
+    #[arg(
+        long = "stdin-urls",
+        help = "从标准输入逐行读取目标URL并持续扫描，所有目标共享并发线程数，读到EOF后结束"
+    )]
+    stdin_urls: bool,
 
-    let client = Arc::new(client_builder.build()
-        .map_err(|e| anyhow!("HTTP客户端创建失败: {}", e))?);
+    /// 敏感扩展名
+    #[arg(
+        long = "sensitive-ext",
+        value_name = "EXT",
+        value_delimiter = ',',
+        default_value = "bak,old,swp,~",
+        help = "敏感扩展名列表（逗号分隔），命中且返回非空200时以 [!] 高亮报告"
+    )]
+    sensitive_ext: Vec<String>,
 
-    // 创建输出文件
-    let output_file = Arc::new(Mutex::new(
-        File::create("out.txt")
-            .map_err(|e| anyhow!("创建输出文件失败: {}", e))?
-    ));
+    /// 事件流文件
+    #[arg(
+        long = "events-file",
+        value_name = "FILE",
+        help = "将扫描生命周期事件（start/progress/finding/end）以NDJSON格式写入文件"
+    )]
+    events_file: Option<String>,
 
+    /// 追加斜杠变体
+    #[arg(
+        long = "add-slash",
+        help = "对没有结尾斜杠和扩展名的字典项，同时请求带结尾斜杠的形式（如 admin 和 admin/）"
+    )]
+    add_slash: bool,
 
-    let file = File::open(&args.dict)
-        .map_err(|e| anyhow!("打开字典文件失败: {}", e))?;
-    let reader = io::BufReader::new(file);
-    let paths: Vec<String> = reader
-        .lines()
-        .filter_map(|line| line.ok())
-        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
-        .collect();
+    /// 请求跟踪文件
+    #[arg(
+        long = "trace",
+        value_name = "FILE",
+        help = "将每个请求的方法、URL、UA、状态码、大小、耗时和过滤判定写入跟踪文件"
+    )]
+    trace: Option<String>,
 
-    if paths.is_empty() {
-        return Err(anyhow!("字典文件为空或格式错误"));
-    }
+    /// 随机化请求头
+    #[arg(
+        long = "randomize-headers",
+        help = "随机生成与 User-Agent 所属浏览器一致的 Accept、Accept-Language 和 Accept-Encoding 请求头"
+    )]
+    randomize_headers: bool,
 
-    println!("{}", "开始扫描...".green());
-    println!("{}", format!("目标 URL: {}", base_url).cyan());
-    println!("{}", format!("字典文件: {}", args.dict).cyan());
-    println!("{}", format!("并发线程: {}", args.threads).cyan());
-    println!("{}", format!("连接超时: {}秒", args.connect_timeout).cyan());
-    println!("{}", format!("请求超时: {}秒", args.timeout).cyan());
-    if let Some(ref proxy) = args.proxy {
-        println!("{}", format!("使用代理: {}", proxy).cyan());
-    }
-    if has_filter {
-        println!("{}", format!("已启用误报过滤").cyan());
-    }
-    if args.insecure {
-        println!("{}", format!("已禁用SSL证书验证").yellow());
-    }
+    /// 打乱请求头顺序
+    #[arg(
+        long = "shuffle-headers",
+        help = "每个请求随机打乱请求头的发送顺序，避免按请求头顺序识别客户端"
+    )]
+    shuffle_headers: bool,
 
+    /// 自动调整并发
+    #[arg(
+        long = "auto-concurrency",
+        help = "从低并发开始，根据响应延迟和出错率自动升降并发，--threads 作为上限"
+    )]
+    auto_concurrency: bool,
 
-    let futures = paths.into_iter().map(|path| {
-        let client = Arc::clone(&client);
-        let base_url = base_url.clone();
-        let output_file = Arc::clone(&output_file);
-        let scan_state = Arc::clone(&scan_state);
-        async move {
-            if let Err(e) = check_path(&client, &base_url, &path, output_file, scan_state).await {
-                eprintln!("{}", format!("检查路径 {} 时出错: {}", path, e).yellow());
-            }
-        }
-    });
+    /// 连接池大小
+    #[arg(
+        long = "pool-size",
+        value_name = "N",
+        help = "每个主机保留的最大空闲连接数，高并发扫描单个主机时建议不小于 --threads"
+    )]
+    pool_size: Option<usize>,
 
-   
-    futures::stream::iter(futures)
-        .buffer_unordered(args.threads)
-        .collect::<Vec<()>>()
-        .await;
+    /// 空闲连接超时（秒）
+    #[arg(
+        long = "pool-idle-timeout",
+        value_name = "SECONDS",
+        help = "空闲连接在连接池中保留的秒数，超过后关闭"
+    )]
+    pool_idle_timeout: Option<u64>,
 
-    println!("{}", "\n扫描完成！结果已保存到 out.txt".green());
-    Ok(())
+    /// TCP_NODELAY
+    #[arg(
+        long = "tcp-nodelay",
+        value_name = "BOOL",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "是否关闭Nagle算法立即发送小数据包，默认开启；高延迟链路上可用 --tcp-nodelay false 对比吞吐量"
+    )]
+    tcp_nodelay: Option<bool>,
+
+    /// TCP keepalive
+    #[arg(
+        long = "tcp-keepalive",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "启用TCP keepalive，连接空闲指定时长（如 30s）后发送探测包，防止中间设备断开长时间复用的连接"
+    )]
+    tcp_keepalive: Option<Duration>,
+
+    /// 仅状态行模式
+    #[arg(
+        long = "only-status-line",
+        help = "使用HEAD请求，不下载响应体，大小取自 Content-Length（内容特征过滤不可用）"
+    )]
+    only_status_line: bool,
+
+    /// 请求方法比较
+    #[arg(
+        long = "compare-methods",
+        help = "对每条将要报告的发现再用 GET、HEAD、POST、OPTIONS 中的其余方法各请求一次，报告状态码因方法而不同的路径（如GET返回403而POST返回200）"
+    )]
+    compare_methods: bool,
+
+    /// 排序输出
+    #[arg(
+        long = "sort-output",
+        help = "扫描结束后再写入输出文件，按严重性从高到低、同级按URL排序并去除重复行"
+    )]
+    sort_output: bool,
+
+    /// 按字典顺序输出
+    #[arg(
+        long = "ordered-output",
+        conflicts_with = "sort_output",
+        help = "扫描结束后按字典顺序写入输出文件，而不是按请求完成的顺序，便于比较多次扫描的结果（发现在扫描期间缓存在内存中）"
+    )]
+    ordered_output: bool,
+
+    /// 按连接固定UA
+    #[arg(
+        long = "ua-per-connection",
+        help = "每个并发连接固定使用一个随机UA，而不是每个请求重新随机（避免同一连接上UA频繁变化）"
+    )]
+    ua_per_connection: bool,
+
+    /// 参数模式
+    #[arg(
+        long = "param-fuzz",
+        help = "将字典项作为查询参数名，以 ?名称=test 追加到目标URL上探测隐藏参数"
+    )]
+    param_fuzz: bool,
+
+    /// 智能匹配
+    #[arg(
+        long = "smart-match",
+        value_name = "BYTES",
+        num_args = 0..=1,
+        default_missing_value = "0",
+        help = "智能匹配：先请求随机路径建立404基线，状态码或大小（相差超过BYTES字节，默认0）与基线不同的响应即报告"
+    )]
+    smart_match: Option<usize>,
+
+    /// curl命令文件
+    #[arg(
+        long = "emit-curl",
+        value_name = "FILE",
+        help = "为每条有效发现写入一条等价的 curl 命令，便于手工复核"
+    )]
+    emit_curl: Option<String>,
+
+    /// 按路径设置超时
+    #[arg(
+        long = "timeout-for",
+        value_name = "PATTERN=SECONDS",
+        help = "为匹配模式（支持 * 通配符）的字典项单独设置请求超时，如 'api/*=30'，可多次指定"
+    )]
+    timeout_for: Vec<String>,
+
+    /// 基线校准超时（秒）
+    #[arg(
+        long = "calibration-timeout",
+        value_name = "SECONDS",
+        help = "智能匹配基线校准请求的超时时间（秒），默认与 --timeout 相同"
+    )]
+    calibration_timeout: Option<u64>,
+
+    /// 允许完整URL字典项
+    #[arg(
+        long = "allow-absolute",
+        help = "按原样扫描字典中的完整URL（如 http://other.com/x），默认警告并跳过，避免请求发往其他主机"
+    )]
+    allow_absolute: bool,
+
+    /// 扫描范围
+    #[arg(
+        long = "scope",
+        value_name = "HOST",
+        help = "只允许请求这些主机（*.example.com 匹配子域名），可多次指定；范围外的目标、字典项和跳转都会被拒绝"
+    )]
+    scope: Vec<String>,
+
+    /// 路径包含
+    #[arg(
+        long = "match-path",
+        value_name = "TEXT",
+        help = "只请求路径包含该子串的URL（不区分大小写），可多次指定，包含任意一个即可"
+    )]
+    match_path: Vec<String>,
+
+    /// 路径排除
+    #[arg(
+        long = "filter-path",
+        value_name = "TEXT",
+        help = "不请求路径包含该子串的URL（不区分大小写），可多次指定，包含任意一个即跳过"
+    )]
+    filter_path: Vec<String>,
+
+    /// 发现后暂缓
+    #[arg(
+        long = "delay-on-match",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "每个有效发现之后暂缓派发新请求这么久（如 2s），在途请求照常完成"
+    )]
+    delay_on_match: Option<Duration>,
+
+    /// 发现后暂停
+    #[arg(
+        long = "pause-on-match",
+        value_name = "TEXT",
+        conflicts_with_all = ["stdin_urls", "jobs"],
+        help = "有效发现的路径包含该子串（不区分大小写，如 .git）时暂停扫描，回车（--tui 或 --auto 时输入 r）继续；可多次指定"
+    )]
+    pause_on_match: Vec<String>,
+
+    /// 重复大小过滤阈值
+    #[arg(
+        long = "filter-threshold",
+        value_name = "N",
+        default_value_t = rdirscan::DEFAULT_FILTER_THRESHOLD,
+        help = "相同响应大小出现N次后询问是否过滤：调高可避免误过滤常见页面大小，调低可更快识别WAF拦截页"
+    )]
+    filter_threshold: usize,
+
+    /// 连续出错中止
+    #[arg(
+        long = "error-abort",
+        value_name = "N",
+        help = "连续N个请求出现网络错误时询问是否中止扫描（--auto 或 --stdin-urls 下直接中止该目标）"
+    )]
+    error_abort: Option<usize>,
+
+    /// 非交互模式
+    #[arg(
+        long = "auto",
+        help = "非交互模式：所有需要确认的询问自动按“是”处理，适合无人值守运行"
+    )]
+    auto: bool,
+
+    /// 输出文件
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "FILE",
+        help = "结果输出文件，按扩展名决定格式（.txt/.json/.csv），可多次指定同时输出多种格式 [默认: out.txt]"
+    )]
+    output: Vec<String>,
+
+    /// 不写入输出文件
+    #[arg(
+        long = "no-output",
+        conflicts_with_all = ["output", "sort_output", "ordered_output", "output_encoding"],
+        help = "不创建任何输出文件，结果只打印到控制台（适用于只读文件系统或临时查看）"
+    )]
+    no_output: bool,
+
+    /// 每主机并发数
+    #[arg(
+        long = "per-host-threads",
+        value_name = "N",
+        help = "多目标扫描时每个主机最多N个并发请求，各目标交替推进，总并发仍受 --threads 限制"
+    )]
+    per_host_threads: Option<usize>,
+
+    /// 忽略环境变量中的代理
+    #[arg(long = "no-proxy-env", help = "忽略 HTTP_PROXY/HTTPS_PROXY/ALL_PROXY 环境变量中的代理")]
+    no_proxy_env: bool,
+
+    /// 不经过代理的主机
+    #[arg(
+        long = "no-proxy",
+        value_name = "HOSTS",
+        value_delimiter = ',',
+        help = "这些主机不经过代理直接连接（逗号分隔，匹配子域名，* 表示所有主机），可多次指定"
+    )]
+    no_proxy: Vec<String>,
+
+    /// 基准测试
+    #[arg(
+        long = "benchmark",
+        value_name = "REQUESTS",
+        num_args = 0..=1,
+        default_missing_value = "10000",
+        help = "对进程内测试服务器发送指定数量的请求并报告吞吐量（需使用 benchmark 特性编译）[默认: 10000]"
+    )]
+    benchmark: Option<usize>,
+
+    /// 自定义请求头
+    #[arg(
+        short = 'H',
+        long = "header",
+        value_name = "NAME: VALUE",
+        help = "附加到每个请求的请求头，可多次指定；值中的 %WORD% 替换为当前字典项"
+    )]
+    header: Vec<String>,
+
+    /// 请求体
+    #[arg(
+        long = "data",
+        value_name = "BODY",
+        help = "使用POST发送的请求体，其中的 %WORD% 替换为当前字典项（默认按表单提交）"
+    )]
+    data: Option<String>,
+
+    /// 自动改用https
+    #[arg(long = "auto-upgrade", help = "目标将http统一跳转到https时自动改用https扫描，不再询问")]
+    auto_upgrade: bool,
+
+    /// 自定义SNI
+    #[arg(
+        long = "sni",
+        value_name = "NAME",
+        help = "TLS握手时使用的服务器名称，连接仍发往目标主机且Host头不变；可配合 --resolve 测试共享前端后的特定后端"
+    )]
+    sni: Option<String>,
+
+    /// 每个路径的出错上限
+    #[arg(
+        long = "max-errors-per-path",
+        value_name = "N",
+        default_value = "1",
+        help = "同一路径因网络错误失败N次后放弃并记入失败列表，大于1时自动重试"
+    )]
+    max_errors_per_path: usize,
+
+    /// 折叠被过滤的结果
+    #[arg(long = "collapse", help = "不逐条打印被过滤的结果，只在同一行按响应大小更新计数，真实发现仍完整打印")]
+    collapse: bool,
+
+    /// 按标题去重
+    #[arg(
+        long = "unique-titles",
+        help = "同一目标上相同 <title>（忽略大小写和空白差异）的页面只报告第一个，其余视为过滤，用于折叠模板化的错误页和登录页"
+    )]
+    unique_titles: bool,
+
+    /// 输出文件编码
+    #[arg(
+        long = "output-encoding",
+        value_name = "ENCODING",
+        default_value = "utf8",
+        help = "文本和CSV输出文件的编码：utf8、utf8-bom、gbk（JSON始终为UTF-8）"
+    )]
+    output_encoding: String,
+
+    /// 输出模板
+    #[arg(
+        long = "output-template",
+        value_name = "TEMPLATE",
+        help = "控制台和文本输出文件中每条发现的行格式，占位符: {url} {status} {size} {time} {content_type} {title}，\\t 表示制表符，如 '{status}\\t{size}\\t{url}'"
+    )]
+    output_template: Option<String>,
+
+    /// 基于历史结果的条件请求
+    #[arg(
+        long = "rescan",
+        value_name = "FILE",
+        help = "读取之前扫描的JSON/CSV结果，对记录了ETag/Last-Modified的URL发送条件请求，返回304的视为未修改并跳过"
+    )]
+    rescan: Option<String>,
+
+    /// 终端界面
+    #[arg(long = "tui", help = "以实时仪表盘显示扫描进度（标准输出不是终端时退回普通输出）；界面模式下不会询问，自动确认（需使用 tui 特性编译）")]
+    tui: bool,
+
+    /// 最低严重性
+    #[arg(
+        long = "min-severity",
+        value_name = "LEVEL",
+        help = "只报告不低于该严重性的发现：info、low、medium、high、critical，更低的视为过滤"
+    )]
+    min_severity: Option<String>,
+
+    /// 响应体大小上限
+    #[arg(
+        long = "max-body-size",
+        value_name = "BYTES",
+        help = "每个响应最多读取的字节数，超出部分不再读取（大小按已读取部分计算），减少大页面的内存和耗时"
+    )]
+    max_body_size: Option<usize>,
+
+    /// 大文件阈值
+    #[arg(
+        long = "skip-large",
+        value_name = "BYTES",
+        help = "Content-Length（或已读取的字节数）超过该值时不再读取响应体，仍报告该路径并标记为 [大文件: 未读取内容]，避免下载大文件"
+    )]
+    skip_large: Option<usize>,
+
+    /// 状态码规则
+    #[arg(
+        long = "match-codes",
+        value_name = "CODES",
+        help = "只报告匹配的状态码，逗号分隔的状态码、范围或通配，如 2xx,403,500-599；!开头表示排除，如 2xx,!204"
+    )]
+    match_codes: Option<String>,
+
+    /// 报告受保护的资源
+    #[arg(long = "show-forbidden", help = "把返回401/403的路径作为受保护的资源报告（青色 [#] 标记），在汇总中单独计数")]
+    show_forbidden: bool,
+
+    /// 保存过滤规则
+    #[arg(
+        long = "save-filters",
+        value_name = "FILE",
+        help = "扫描结束时把内容特征和确认过滤的响应大小写入文件，下次用 -f/--filter 读回即可沿用这些过滤决定"
+    )]
+    save_filters: Option<String>,
+
+    /// 严格字典校验
+    #[arg(long = "strict-dict", help = "跳过含控制字符、反斜杠或过长（超过1024字节）的字典项，默认只给出警告")]
+    strict_dict: bool,
+
+    /// 排除列表文件
+    #[arg(
+        long = "exclude-file",
+        value_name = "FILE",
+        help = "排除列表文件，每行一个不应请求的路径（如 logout、shutdown），支持 * 通配符；匹配的字典项在扫描前剔除"
+    )]
+    exclude_file: Option<String>,
+
+    /// 追加输出
+    #[arg(long = "append", conflicts_with = "no_output", help = "追加到已有的输出文件而不是覆盖（JSON输出会合并到原有数组中，CSV不重复写表头）")]
+    append: bool,
+
+    /// 忽略限流响应头
+    #[arg(
+        long = "ignore-rate-limit",
+        help = "不根据服务器返回的 RateLimit-Remaining/RateLimit-Reset 响应头和429/503响应的 Retry-After 放慢请求（默认额度不足时自动放慢或暂停）"
+    )]
+    ignore_rate_limit: bool,
+
+    /// 出错路径文件
+    #[arg(
+        long = "errors-file",
+        value_name = "FILE",
+        help = "扫描结束时把出错的路径以JSON数组写入文件（url、path、kind、message），便于区分未能扫描和不存在"
+    )]
+    errors_file: Option<String>,
+
+    /// SQLite数据库
+    #[arg(
+        long = "sqlite",
+        value_name = "DB",
+        help = "把本次扫描和有效发现写入SQLite数据库（scans、findings、technologies 表），可跨多次扫描查询（需使用 sqlite 特性编译）"
+    )]
+    sqlite: Option<String>,
+
+    /// Digest认证
+    #[arg(
+        long = "digest-auth",
+        value_name = "USER:PASS",
+        help = "使用HTTP Digest认证（常见于路由器、摄像头等设备），收到质询后带上认证头重新请求，之后同一主机的请求直接带上认证头"
+    )]
+    digest_auth: Option<String>,
+
+    /// 请求签名密钥
+    #[arg(
+        long = "sign-secret",
+        value_name = "SECRET",
+        help = "用该密钥对每个请求计算HMAC-SHA256签名（十六进制），用于扫描要求请求签名的API网关"
+    )]
+    sign_secret: Option<String>,
+
+    /// 签名请求头
+    #[arg(
+        long = "sign-header",
+        value_name = "NAME",
+        requires = "sign_secret",
+        help = "存放签名的请求头 [默认: X-Signature]"
+    )]
+    sign_header: Option<String>,
+
+    /// 时间戳请求头
+    #[arg(
+        long = "sign-timestamp-header",
+        value_name = "NAME",
+        requires = "sign_secret",
+        help = "签名模板用到 {timestamp} 时存放时间戳（Unix秒数）的请求头 [默认: X-Timestamp]"
+    )]
+    sign_timestamp_header: Option<String>,
+
+    /// 签名模板
+    #[arg(
+        long = "sign-template",
+        value_name = "TEMPLATE",
+        requires = "sign_secret",
+        help = "待签名的内容，占位符: {method} {path} {host} {timestamp} {body}，\\n 表示换行 [默认: {method}\\n{path}\\n{timestamp}]"
+    )]
+    sign_template: Option<String>,
+
+    /// 登录地址
+    #[arg(
+        long = "login-url",
+        value_name = "URL",
+        requires = "login_data",
+        help = "扫描前向该地址POST登录表单，之后的请求带上获得的会话Cookie"
+    )]
+    login_url: Option<String>,
+
+    /// 登录表单
+    #[arg(
+        long = "login-data",
+        value_name = "FIELDS",
+        requires = "login_url",
+        help = "登录表单字段（URL编码），如 'username=admin&password=123456'"
+    )]
+    login_data: Option<String>,
+
+    /// 登录成功标志
+    #[arg(
+        long = "login-success",
+        value_name = "TEXT",
+        requires = "login_url",
+        help = "登录响应内容或跳转地址中出现该文本时视为成功（默认要求状态码不是4xx/5xx且服务器设置了Cookie）"
+    )]
+    login_success: Option<String>,
+
+    /// 登录失败标志
+    #[arg(
+        long = "login-fail",
+        value_name = "TEXT",
+        requires = "login_url",
+        help = "登录响应内容中出现该文本时视为失败，如 '密码错误'"
+    )]
+    login_fail: Option<String>,
+
+    /// CSRF令牌选择器
+    #[arg(
+        long = "csrf-selector",
+        value_name = "SELECTOR",
+        requires = "login_url",
+        conflicts_with = "csrf_regex",
+        help = "登录前先请求登录页，用CSS选择器提取CSRF令牌加入登录表单，如 'input[name=csrf_token]'（需使用 csrf 特性编译）"
+    )]
+    csrf_selector: Option<String>,
+
+    /// CSRF令牌正则
+    #[arg(
+        long = "csrf-regex",
+        value_name = "REGEX",
+        requires_all = ["login_url", "csrf_field"],
+        help = "用正则表达式从登录页提取CSRF令牌（取第一个捕获组），需同时指定 --csrf-field（需使用 csrf 特性编译）"
+    )]
+    csrf_regex: Option<String>,
+
+    /// CSRF令牌字段名
+    #[arg(
+        long = "csrf-field",
+        value_name = "NAME",
+        requires = "login_url",
+        help = "CSRF令牌在登录表单中的字段名，默认取选择器匹配元素的 name 属性"
+    )]
+    csrf_field: Option<String>,
+
+    /// 登录页地址
+    #[arg(
+        long = "login-page",
+        value_name = "URL",
+        requires = "login_url",
+        help = "提取CSRF令牌的登录页地址，默认与 --login-url 相同"
+    )]
+    login_page: Option<String>,
+
+    /// 扫描的端口列表
+    #[arg(
+        long = "ports",
+        value_name = "PORTS",
+        value_delimiter = ',',
+        help = "在每个目标的这些端口上分别扫描（逗号分隔，如 80,443,8080），443/8443使用https，80/8080使用http，可写成 https:8000 指定协议"
+    )]
+    ports: Vec<String>,
+
+    /// 固定UA
+    #[arg(
+        long = "static-ua",
+        conflicts_with = "ua_per_connection",
+        help = "扫描开始时随机选定一个UA，整个扫描期间都使用它（设置在客户端上，不再每个请求重新随机）"
+    )]
+    static_ua: bool,
+
+    /// 自定义UA
+    #[arg(
+        long = "user-agent",
+        value_name = "UA",
+        conflicts_with = "ua_per_connection",
+        help = "整个扫描都使用指定的User-Agent，隐含 --static-ua"
+    )]
+    user_agent: Option<String>,
+
+    /// 任务文件
+    #[arg(
+        long = "jobs",
+        value_name = "FILE",
+        conflicts_with_all = ["url", "stdin_urls", "ports", "output", "no_output", "tui", "events_file", "trace", "emit_curl", "errors_file", "save_filters", "diff"],
+        help = "从TOML/JSON任务文件读取多个 (URL, 字典, 请求头, 输出文件) 任务并依次或同时扫描，其余选项对所有任务生效"
+    )]
+    jobs: Option<String>,
+
+    /// 最小响应大小
+    #[arg(
+        long = "min-content-length",
+        value_name = "BYTES",
+        help = "过滤小于该字节数的2xx响应（空白或几乎为空的页面），在汇总中单独计数"
+    )]
+    min_content_length: Option<usize>,
+
+    /// 输出时间戳
+    #[arg(
+        long = "timestamps",
+        help = "文本输出文件的每行末尾附上发现时间（ISO-8601，UTC），JSON和CSV输出总是包含时间"
+    )]
+    timestamps: bool,
+
+    /// 只做预热
+    #[arg(
+        long = "probe-only",
+        conflicts_with_all = ["stdin_urls", "jobs", "skip_warmup"],
+        help = "只请求一次目标URL，报告状态码、大小、Server头及TLS/代理情况后退出，不扫描"
+    )]
+    probe_only: bool,
+
+    /// 跳过预热
+    #[arg(
+        long = "skip-warmup",
+        help = "扫描前不请求目标URL确认连通性，直接开始扫描"
+    )]
+    skip_warmup: bool,
+
+    /// 显示证书信息
+    #[arg(
+        long = "show-cert",
+        conflicts_with_all = ["stdin_urls", "jobs", "unix_socket"],
+        help = "扫描前读取https目标的TLS证书，显示主题、备用名称（SAN）、签发者和有效期"
+    )]
+    show_cert: bool,
+
+    /// 字典抽样比例
+    #[arg(
+        long = "sample",
+        value_name = "RATIO",
+        value_parser = parse_sample,
+        help = "只随机扫描字典的一部分，写成百分比（10%）或小数（0.1），用于在完整扫描前快速摸底"
+    )]
+    sample: Option<f64>,
+
+    /// 随机种子
+    #[arg(
+        long = "seed",
+        value_name = "N",
+        help = "随机种子，用于抽样以及UA、校准路径、随机请求头等所有随机选择，相同的种子得到相同的选择（单线程时完全可复现）"
+    )]
+    seed: Option<u64>,
+
+    /// 发现回调命令
+    #[arg(
+        long = "on-finding",
+        value_name = "COMMAND",
+        help = "每条有效发现都在后台用shell执行一次该命令，URL、状态码、大小作为 $1 $2 $3 及环境变量 RDIRSCAN_URL 等传入（最多同时运行4个）"
+    )]
+    on_finding: Option<String>,
+
+    /// 发现推送地址
+    #[arg(
+        long = "webhook",
+        value_name = "URL",
+        help = "把有效发现以JSON批量POST到该地址（{\"findings\": [...]}），在后台发送，失败时重试，不阻塞扫描"
+    )]
+    webhook: Option<String>,
+
+    /// 与上次结果比较
+    #[arg(
+        long = "diff",
+        value_name = "FILE",
+        help = "扫描结束后与之前的输出文件（文本/JSON/CSV）比较，列出新出现、已消失和大小变化的发现；可以是本次要覆盖的同一个文件"
+    )]
+    diff: Option<String>,
+
+    /// 重放之前的结果
+    #[arg(
+        long = "replay",
+        value_name = "FILE",
+        conflicts_with_all = ["url", "stdin_urls", "ports", "jobs", "dict", "output", "append", "sqlite", "diff", "rescan", "probe_only", "tui", "recursion_depth", "param_fuzz"],
+        help = "重新请求之前的输出文件（文本/JSON/CSV）中的每个URL，逐个比较状态码和大小，确认旧结果是否仍然有效，不扫描字典也不写入输出文件"
+    )]
+    replay: Option<String>,
+
+    /// 每个地址的并发上限
+    #[arg(
+        long = "max-per-ip",
+        value_name = "N",
+        conflicts_with_all = ["unix_socket", "proxy", "ua_per_connection"],
+        help = "主机解析到多个地址（DNS轮询、负载均衡）时，每个后端地址上同时进行的请求不超过N个，请求轮流分配到有空闲的地址"
+    )]
+    max_per_ip: Option<usize>,
+
+    /// Unix域套接字
+    #[arg(
+        long = "unix-socket",
+        value_name = "PATH",
+        conflicts_with_all = ["stdin_urls", "ports", "jobs"],
+        help = "通过Unix域套接字（如 /var/run/docker.sock）发送HTTP请求，-u 给出的URL只决定Host头和请求路径，必须使用http（需使用 unix-socket 特性编译）"
+    )]
+    unix_socket: Option<String>,
+
+
+    /// 诊断日志格式
+    #[arg(
+        long = "log-format",
+        value_name = "FORMAT",
+        default_value = "pretty",
+        help = "警告、错误和调试日志的格式：pretty（着色文本）、json（每行一个JSON对象），级别由 RUST_LOG 控制"
+    )]
+    log_format: String,
+
+    /// 递归扫描层数
+    #[arg(
+        long = "recursion-depth",
+        value_name = "N",
+        default_value_t = 0,
+        help = "对发现的目录递归扫描的最大层数，0表示不递归"
+    )]
+    recursion_depth: usize,
+
+    /// 递归字典
+    #[arg(
+        long = "recursion-dict",
+        value_name = "FILE",
+        requires = "recursion_depth",
+        help = "递归进入子目录时使用的（通常更小的）字典，未指定时沿用主字典"
+    )]
+    recursion_dict: Option<String>,
+
+    /// 按延迟估算剩余时间
+    #[arg(
+        long = "latency-eta",
+        requires = "tui",
+        help = "界面中按最近请求的平均延迟估算剩余时间（默认按每秒请求数），延迟差异大的目标上更平稳"
+    )]
+    latency_eta: bool,
+}
+
+/// `--threads` 的取值：固定数量或 `auto`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThreadCount {
+    Auto,
+    Fixed(usize),
+}
+
+impl ThreadCount {
+    /// 创建扫描器时使用的并发数，`auto` 先按CPU核心数选择，预热后再按目标延迟调整
+    fn initial(self) -> usize {
+        match self {
+            ThreadCount::Auto => rdirscan::suggest_threads(None),
+            ThreadCount::Fixed(threads) => threads,
+        }
+    }
+}
+
+impl std::fmt::Display for ThreadCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreadCount::Auto => f.write_str("auto"),
+            ThreadCount::Fixed(threads) => write!(f, "{}", threads),
+        }
+    }
+}
+
+fn parse_threads(text: &str) -> std::result::Result<ThreadCount, String> {
+    if text.trim().eq_ignore_ascii_case("auto") {
+        return Ok(ThreadCount::Auto);
+    }
+    text.trim()
+        .parse()
+        .map(ThreadCount::Fixed)
+        .map_err(|_| format!("线程数 '{}' 无效，应为正整数或 auto", text))
+}
+
+/// 命令行中时长的上限，更长的值加到当前时间上可能溢出
+const MAX_DURATION: Duration = Duration::from_secs(24 * 3600);
+
+/// 解析带单位的时长（ms、s、m、h），纯数字按秒计算，不能超过 [`MAX_DURATION`]
+fn parse_duration(text: &str) -> std::result::Result<Duration, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || format!("时长 '{}' 无效，应为数字加可选单位（ms、s、m、h）", text);
+    let value: u64 = number.parse().map_err(|_| invalid())?;
+    let seconds = |factor: u64| value.checked_mul(factor).map(Duration::from_secs).ok_or_else(invalid);
+    let duration = match unit.trim() {
+        "" | "s" => seconds(1)?,
+        "ms" => Duration::from_millis(value),
+        "m" => seconds(60)?,
+        "h" => seconds(3600)?,
+        other => return Err(format!("时长 '{}' 的单位 '{}' 无效，支持 ms、s、m、h", text, other)),
+    };
+    if duration.is_zero() {
+        return Err(format!("时长 '{}' 必须大于0", text));
+    }
+    if duration > MAX_DURATION {
+        return Err(format!("时长 '{}' 无效，不能超过24h", text));
+    }
+    Ok(duration)
+}
+
+/// 解析抽样比例，`10%` 或 `0.1`，必须在 (0, 1] 之间
+fn parse_sample(text: &str) -> std::result::Result<f64, String> {
+    let text = text.trim();
+    let ratio = match text.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|value| value / 100.0),
+        None => text.parse::<f64>(),
+    }
+    .map_err(|_| format!("抽样比例 '{}' 无效，应为百分比（10%）或小数（0.1）", text))?;
+    if !(ratio > 0.0 && ratio <= 1.0) {
+        return Err(format!("抽样比例 '{}' 必须大于0且不超过100%", text));
+    }
+    Ok(ratio)
+}
+
+/// 按比例随机抽取字典项（至少一条），保持原有顺序；指定种子时结果可复现
+fn sample_paths(paths: Vec<String>, ratio: f64, seed: Option<u64>) -> Vec<String> {
+    let count = ((paths.len() as f64 * ratio).ceil() as usize).max(1).min(paths.len());
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut indices = rand::seq::index::sample(&mut rng, paths.len(), count).into_vec();
+    indices.sort_unstable();
+    let mut paths: Vec<Option<String>> = paths.into_iter().map(Some).collect();
+    indices.into_iter().filter_map(|index| paths[index].take()).collect()
+}
+
+/// 以最合适的单位显示时长
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() != 0 {
+        format!("{}毫秒", duration.as_millis())
+    } else {
+        format!("{}秒", duration.as_secs())
+    }
+}
+
+/// 文本输出文件开头的扫描参数说明：工具版本、目标、字典、并发、开始时间和启用的过滤
+fn output_header(args: &Args, target: &str, dict_name: &str) -> Vec<String> {
+    let now = OffsetDateTime::now_utc();
+    let started = now.replace_nanosecond(0).unwrap_or(now).format(&Rfc3339).unwrap_or_default();
+    let mut filters = vec![format!("重复大小阈值 {}", args.filter_threshold)];
+    if let Some(ref file) = args.filter_file {
+        filters.push(format!("过滤文件 {}", file));
+    }
+    if let Some(threshold) = args.smart_match {
+        filters.push(format!("智能匹配 {}", threshold));
+    }
+    if let Some(threshold) = args.outliers {
+        filters.push(format!("异常值 {}", threshold));
+    }
+    if let Some(bytes) = args.min_content_length {
+        filters.push(format!("最小响应 {} 字节", bytes));
+    }
+    if let Some(ref level) = args.min_severity {
+        filters.push(format!("最低严重性 {}", level.to_uppercase()));
+    }
+    if args.show_forbidden {
+        filters.push("报告401/403".to_string());
+    }
+    let threads = if args.auto_concurrency {
+        format!("自动（上限 {}）", args.threads)
+    } else {
+        args.threads.to_string()
+    };
+    // 结果文件常被分享，不记录命令行，目标URL中的密码也隐去
+    let target = match Url::parse(target) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("***"));
+            url.to_string()
+        }
+        _ => target.to_string(),
+    };
+    vec![
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        format!("目标: {}", target),
+        format!("字典: {}", dict_name),
+        format!("并发: {}", threads),
+        format!("开始时间: {}", started),
+        format!("过滤: {}", filters.join("，")),
+    ]
+}
+
+/// 剔除完整URL形式的字典项
+fn drop_absolute_entries(paths: &mut Vec<String>) -> Result<()> {
+    paths.retain(|path| {
+        let absolute = dict::is_absolute_entry(path);
+        if absolute {
+            warn!(entry = %path, "字典项是完整URL，已跳过（使用 --allow-absolute 按原样扫描）");
+        }
+        !absolute
+    });
+    if paths.is_empty() {
+        return Err(anyhow!("字典中没有可用的路径"));
+    }
+    Ok(())
+}
+
+/// 剔除 `--exclude-file` 中列出的字典项，返回剔除的条数
+fn apply_exclusions(paths: &mut Vec<String>, exclusions: &[String]) -> Result<usize> {
+    let excluded = dict::exclude_entries(paths, exclusions);
+    if paths.is_empty() {
+        return Err(anyhow!("字典中的路径都在排除列表中"));
+    }
+    Ok(excluded)
+}
+
+/// 警告可疑的字典项，严格模式下跳过它们；只逐条显示前几条，其余汇总计数
+fn validate_entries(paths: &mut Vec<String>, strict: bool) -> Result<()> {
+    const SHOWN: usize = 10;
+    let mut problems = 0;
+    paths.retain(|path| {
+        let Some(problem) = dict::entry_problem(path) else {
+            return true;
+        };
+        problems += 1;
+        if problems <= SHOWN {
+            let shown: String = path.escape_debug().take(80).collect();
+            warn!(entry = %shown, problem = %problem, "可疑的字典项");
+        }
+        !strict
+    });
+    if problems == 0 {
+        return Ok(());
+    }
+    if strict {
+        warn!("严格模式: 已跳过 {} 条可疑的字典项", problems);
+        if paths.is_empty() {
+            return Err(anyhow!("字典中没有可用的路径"));
+        }
+    } else {
+        warn!("共有 {} 条可疑的字典项，仍会扫描（使用 --strict-dict 跳过）", problems);
+    }
+    Ok(())
+}
+
+fn parse_timeout_override(entry: &str) -> Result<(String, Duration)> {
+    let (pattern, secs) = entry
+        .rsplit_once('=')
+        .ok_or_else(|| anyhow!("超时规则 '{}' 格式错误，应为 PATTERN=SECONDS", entry))?;
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err(anyhow!("超时规则 '{}' 缺少匹配模式", entry));
+    }
+    let secs: u64 = secs
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("超时规则 '{}' 中的秒数无效: {}", entry, e))?;
+    let timeout = Duration::from_secs(secs);
+    if timeout > MAX_DURATION {
+        return Err(anyhow!("超时规则 '{}' 中的秒数无效，不能超过24h", entry));
+    }
+    Ok((pattern.to_string(), timeout))
+}
+
+fn parse_header(entry: &str) -> Result<(String, String)> {
+    let (name, value) = entry
+        .split_once(':')
+        .ok_or_else(|| anyhow!("请求头 '{}' 格式错误，应为 NAME: VALUE", entry))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("请求头 '{}' 缺少名称", entry));
+    }
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+/// 解析端口列表中的一项，格式为 `PORT` 或 `SCHEME:PORT`
+fn parse_port(entry: &str) -> Result<(Option<String>, u16)> {
+    let (scheme, port) = match entry.trim().split_once(':') {
+        Some((scheme, port)) => (Some(scheme.trim().to_lowercase()), port),
+        None => (None, entry),
+    };
+    if let Some(ref scheme) = scheme {
+        if scheme != "http" && scheme != "https" {
+            return Err(anyhow!("端口 '{}' 的协议无效，只能是 http 或 https", entry));
+        }
+    }
+    let port = port
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| anyhow!("端口 '{}' 无效，端口号必须在 1-65535 之间", entry))?;
+    Ok((scheme, port))
+}
+
+/// 把目标展开为每个端口一个URL，目标无效或无法改到指定端口时返回错误
+fn expand_ports(target: String, ports: &[(Option<String>, u16)]) -> Result<Vec<String>> {
+    if ports.is_empty() {
+        return Ok(vec![target]);
+    }
+    ports
+        .iter()
+        .map(|(scheme, port)| {
+            with_port(&target, *port, scheme.as_deref())
+                .with_context(|| format!("无法按 --ports 展开目标 {}", target))
+        })
+        .collect()
+}
+
+fn parse_resolve(entry: &str) -> Result<(String, SocketAddr)> {
+    let (host, ip) = entry
+        .split_once(':')
+        .ok_or_else(|| anyhow!("解析规则 '{}' 格式错误，应为 HOST:IP", entry))?;
+    let host = host.trim();
+    if host.is_empty() {
+        return Err(anyhow!("解析规则 '{}' 缺少主机名", entry));
+    }
+    let ip = ip.trim().trim_start_matches('[').trim_end_matches(']');
+    let ip: IpAddr = ip
+        .parse()
+        .map_err(|e| anyhow!("解析规则 '{}' 中的IP地址无效: {}", entry, e))?;
+    // 端口由请求URL决定，这里的端口会被忽略
+    Ok((host.to_string(), SocketAddr::new(ip, 0)))
+}
+
+/// 在命令行中查找 `--config` 指定的配置文件路径
+fn find_config_path(argv: &[OsString]) -> Option<String> {
+    let mut iter = argv.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return iter.next().map(|path| path.into_owned());
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        }
+    }
+    None
+}
+
+/// 读取代理列表文件，每行一个代理，跳过空行和 `#` 注释
+fn load_proxy_file(path: &str) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("读取代理列表文件失败: {}", e))?;
+    let proxies = parse_proxy_list(&content);
+    if proxies.is_empty() {
+        return Err(anyhow!("代理列表文件 {} 中没有代理", path));
+    }
+    Ok(proxies)
+}
+
+/// 代理列表每行一个代理，忽略空行和 `#` 注释
+fn parse_proxy_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 将配置文件中的键值转换为命令行参数，放在真实参数之前以便被命令行覆盖
+fn config_file_args(path: &str) -> Result<Vec<OsString>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("读取配置文件失败: {}", e))?;
+    let table: toml::Table = content.parse()
+        .map_err(|e| anyhow!("配置文件格式错误: {}", e))?;
+
+    let command = Args::command();
+    let mut args = Vec::new();
+    for (key, value) in table {
+        let name = key.replace('_', "-");
+        let known = command.get_arguments().any(|arg| arg.get_long() == Some(name.as_str()));
+        if !known || name == "config" {
+            return Err(anyhow!("配置文件中存在未知选项 '{}'", key));
+        }
+
+        let values = match value {
+            toml::Value::Array(items) => items,
+            value => vec![value],
+        };
+        for value in values {
+            let flag = OsString::from(format!("--{}", name));
+            match value {
+                toml::Value::Boolean(true) => args.push(flag),
+                toml::Value::Boolean(false) => {}
+                toml::Value::String(text) => args.extend([flag, text.into()]),
+                toml::Value::Integer(n) => args.extend([flag, n.to_string().into()]),
+                toml::Value::Float(n) => args.extend([flag, n.to_string().into()]),
+                _ => return Err(anyhow!("配置文件中选项 '{}' 的值类型不受支持", key)),
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// 解析命令行参数，指定了 `--config` 时先合并配置文件中的默认值
+fn parse_args() -> Result<Args> {
+    let argv: Vec<OsString> = std::env::args_os().collect();
+    let Some(config_path) = find_config_path(&argv) else {
+        return Ok(Args::parse_from(argv));
+    };
+
+    let mut merged = argv[..1].to_vec();
+    merged.extend(config_file_args(&config_path)?);
+    merged.extend_from_slice(&argv[1..]);
+    Ok(Args::parse_from(merged))
+}
+
+/// 加载字典：显式指定时直接读取，否则依次尝试 Dir.txt 和内置字典
+fn load_paths(dict_path: Option<&str>) -> Result<(Vec<String>, String)> {
+    const DEFAULT_DICT: &str = "Dir.txt";
+
+    if let Some(path) = dict_path {
+        return Ok((dict::load_dictionary(path)?, path.to_string()));
+    }
+    if let Ok(content) = fs::read_to_string(DEFAULT_DICT) {
+        let has_entries = content
+            .lines()
+            .any(|line| !line.trim().is_empty() && !line.starts_with('#'));
+        if has_entries {
+            return Ok((dict::load_dictionary(DEFAULT_DICT)?, DEFAULT_DICT.to_string()));
+        }
+        println!("{}", format!("{} 为空，改用内置字典", DEFAULT_DICT).yellow());
+    }
+    let paths = dict::builtin_dictionary();
+    let name = format!("内置字典（{} 条）", paths.len());
+    Ok((paths, name))
+}
+
+/// 读取递归字典，标签和逐项请求头对拼接出的子路径没有意义，直接去掉
+fn load_recursion_paths(path: &str, args: &Args) -> Result<Vec<String>> {
+    let (paths, _) = dict::extract_path_headers(dict::load_dictionary(path)?);
+    let (mut paths, _) = dict::extract_severity_tags(paths);
+    validate_entries(&mut paths, args.strict_dict)?;
+    if let Some(ref file) = args.exclude_file {
+        apply_exclusions(&mut paths, &dict::load_exclusions(file)?)?;
+    }
+    if !args.allow_absolute {
+        drop_absolute_entries(&mut paths)?;
+    }
+    if args.add_slash {
+        paths = dict::add_slash_variants(paths);
+    }
+    Ok(paths)
+}
+
+/// 处理终端输入的 p/r 命令，暂停或继续派发新请求；询问的回答不会被当作命令
+fn watch_pause_keys(pause: PauseControl) {
+    rdirscan::on_input_line(move |line| match line {
+        "p" if pause.pause() => print_line("已暂停派发新请求，在途请求会继续完成；输入 r 回车继续".yellow()),
+        "r" if pause.resume() => print_line("已继续扫描".green()),
+        _ => {}
+    });
+}
+
+/// 分节打印与上次扫描结果的差异
+fn print_diff(diff: &ScanDiff, path: &str) {
+    println!("\n{}", format!("与上次结果（{}）相比:", path).green());
+    if diff.is_empty() {
+        println!("{}", "  没有变化".cyan());
+        return;
+    }
+    if !diff.new.is_empty() {
+        println!("{}", format!("新出现 {} 个:", diff.new.len()).green().bold());
+        for (url, size) in &diff.new {
+            println!("{}", format!("  + {} (大小: {} 字节)", url, size).green());
+        }
+    }
+    if !diff.gone.is_empty() {
+        println!("{}", format!("已消失 {} 个:", diff.gone.len()).red().bold());
+        for (url, size) in &diff.gone {
+            println!("{}", format!("  - {} (大小: {} 字节)", url, size).red());
+        }
+    }
+    if !diff.changed.is_empty() {
+        println!("{}", format!("大小变化 {} 个:", diff.changed.len()).yellow().bold());
+        for (url, old, new) in &diff.changed {
+            println!("{}", format!("  ~ {} ({} -> {} 字节)", url, old, new).yellow());
+        }
+    }
+}
+
+/// 重放之前的输出文件中的发现，逐个打印状态码和大小的变化
+///
+/// URL按来源分组，每个来源作为一个目标、其下的路径作为字典扫描一遍；所有状态码都视为匹配，以便拿到失效URL的新状态码
+async fn run_replay(builder: ScanConfigBuilder, path: &str, args: &Args) -> Result<()> {
+    let previous = diff::load_findings(Path::new(path))?;
+    let mut origins: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut entries = Vec::with_capacity(previous.len());
+    for (url, finding) in previous {
+        let Ok(parsed) = Url::parse(&url) else {
+            warn!(url = %url, "无法解析的URL，已跳过");
+            continue;
+        };
+        let mut relative = parsed.path().trim_start_matches('/').to_string();
+        if let Some(query) = parsed.query() {
+            relative.push('?');
+            relative.push_str(query);
+        }
+        origins.entry(format!("{}/", parsed.origin().ascii_serialization())).or_default().push(relative);
+        entries.push((url, parsed.to_string(), finding));
+    }
+    if entries.is_empty() {
+        return Err(anyhow!("{} 中没有可以重放的URL", path));
+    }
+    println!("{}", format!("重放 {}: 共 {} 个URL，涉及 {} 个主机", path, entries.len(), origins.len()).green());
+
+    let mut current: HashMap<String, std::result::Result<(u16, usize), String>> = HashMap::new();
+    for (origin, paths) in origins {
+        let config = builder.clone()
+            .url(&origin)
+            .match_codes("100-599".parse()?)
+            .interactive(false)
+            .quiet(true)
+            .build()
+            .context("配置校验失败")?;
+        let scanner = Scanner::new(config)?;
+        if args.login_url.is_some() {
+            scanner.login().await.with_context(|| format!("登录未成功，已跳过 {}", origin))?;
+        }
+        let report = scanner.run(paths).await?;
+        for finding in &report.findings {
+            current.insert(finding.url.to_string(), Ok((finding.status.as_u16(), finding.size)));
+        }
+        for error in &report.failed {
+            current.insert(error.url.clone(), Err(error.message.clone()));
+        }
+    }
+
+    let (mut unchanged, mut resized, mut changed, mut failed) = (0, 0, 0, 0);
+    println!();
+    for (url, normalized, finding) in &entries {
+        let before = match (finding.status, finding.size) {
+            (Some(status), Some(size)) => format!("{} ({} 字节)", status, size),
+            (None, Some(size)) => format!("{} 字节", size),
+            (Some(status), None) => status.to_string(),
+            (None, None) => "未记录".to_string(),
+        };
+        let replayed = match current.remove(normalized) {
+            Some(Ok((status, size))) => finding.compare(status, size),
+            Some(Err(message)) => Replayed::Failed(message),
+            None => Replayed::Failed("没有得到响应（超出范围或扫描提前停止）".to_string()),
+        };
+        match replayed {
+            Replayed::Unchanged(status, size) => {
+                unchanged += 1;
+                println!("{}", format!("  = {} {} ({} 字节)", url, status, size).green());
+            }
+            Replayed::SizeChanged(status, size) => {
+                resized += 1;
+                println!("{}", format!("  ~ {} {} -> {} ({} 字节)", url, before, status, size).yellow());
+            }
+            Replayed::StatusChanged(status, size) => {
+                changed += 1;
+                println!("{}", format!("  ! {} {} -> {} ({} 字节)", url, before, status, size).red());
+            }
+            Replayed::Failed(message) => {
+                failed += 1;
+                println!("{}", format!("  x {} {} -> 失败: {}", url, before, message).red());
+            }
+        }
+    }
+    println!("\n{}", format!("重放完成: 未变 {} 个，大小变化 {} 个，状态码变化 {} 个，失败 {} 个",
+        unchanged, resized, changed, failed).cyan());
+    Ok(())
+}
+
+/// 打印预热请求的结果
+fn print_warmup(outcome: &WarmupOutcome) {
+    println!("{}", format!("预热请求: {} 状态码 {}，大小 {} 字节，耗时 {}毫秒",
+        outcome.url, outcome.status, outcome.size, outcome.elapsed.as_millis()).green());
+    let server = outcome.server.as_deref().unwrap_or("未知");
+    let tls = if outcome.tls { "正常" } else { "未使用" };
+    let proxy = outcome.proxy.as_deref().unwrap_or("未使用");
+    println!("{}", format!("Server: {}，TLS: {}，代理: {}", server, tls, proxy).cyan());
+}
+
+/// 打印目标的TLS证书信息，备用名称中的其他主机往往值得一并扫描
+fn print_certificate(cert: &CertificateInfo) {
+    let date = |time: OffsetDateTime| time.date().to_string();
+    println!("{}", format!("TLS证书主题: {}", cert.subject).cyan());
+    println!("{}", format!("TLS证书签发者: {}", cert.issuer).cyan());
+    if !cert.sans.is_empty() {
+        println!("{}", format!("TLS证书备用名称（{} 个）: {}", cert.sans.len(), cert.sans.join(", ")).cyan());
+    }
+    let validity = format!("TLS证书有效期: {} 至 {}", date(cert.not_before), date(cert.not_after));
+    match cert.days_remaining() {
+        days if days < 0 => println!("{}", format!("{}（已过期 {} 天）", validity, -days).yellow()),
+        days if days < EXPIRY_WARNING_DAYS => println!("{}", format!("{}（{} 天后过期）", validity, days).yellow()),
+        days => println!("{}", format!("{}（剩余 {} 天）", validity, days).cyan()),
+    }
+}
+
+/// 按任务文件逐个或同时运行扫描，每个任务都在 `builder` 的基础上设置自己的目标、字典、请求头和输出文件
+async fn run_jobs(builder: ScanConfigBuilder, job_file: JobFile, args: &Args) -> Result<()> {
+    let total = job_file.jobs.len();
+    let mode = if job_file.concurrent { "同时" } else { "依次" };
+    println!("{}", format!("任务文件: {}，共 {} 个任务，{}运行", args.jobs.as_deref().unwrap_or_default(), total, mode).cyan());
+    // 同时运行时多个任务共用终端，无法交互确认
+    let builder = builder.interactive(!job_file.concurrent && !args.auto);
+
+    let run = |index: usize, job: Job| {
+        let builder = builder.clone();
+        async move {
+            let result = run_job(builder, index, &job, args).await;
+            (index, job, result)
+        }
+    };
+    let results = if job_file.concurrent {
+        future::join_all(job_file.jobs.into_iter().enumerate().map(|(index, job)| run(index, job))).await
+    } else {
+        let mut results = Vec::with_capacity(total);
+        for (index, job) in job_file.jobs.into_iter().enumerate() {
+            results.push(run(index, job).await);
+        }
+        results
+    };
+
+    println!("\n{}", "任务汇总:".green());
+    let mut failed = 0;
+    for (index, job, result) in results {
+        match result {
+            Ok((report, outputs)) => println!("{}", format!("  [{}] {}: 共发送请求 {} 个，有效发现 {} 个，已过滤 {} 个，出错 {} 个，结果已保存到 {}",
+                index + 1, job.url, report.requests, report.found(), report.filtered(), report.errors, outputs.join(", ")).cyan()),
+            Err(e) => {
+                failed += 1;
+                println!("{}", format!("  [{}] {}: 失败: {:#}", index + 1, job.url, e).red());
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow!("{} 个任务中有 {} 个失败", total, failed));
+    }
+    Ok(())
+}
+
+/// 运行任务文件中的一个任务，返回扫描结果和输出文件
+async fn run_job(mut builder: ScanConfigBuilder, index: usize, job: &Job, args: &Args) -> Result<(ScanReport, Vec<String>)> {
+    builder = builder.url(&job.url);
+    for (name, value) in &job.headers {
+        builder = builder.header(name, value);
+    }
+    let outputs = if job.output.is_empty() {
+        vec![format!("out-{}.txt", index + 1)]
+    } else {
+        job.output.clone()
+    };
+    for path in &outputs {
+        builder = builder.output_file(path);
+    }
+    let (paths, dict_name) = load_paths(job.dict.as_deref().or(args.dict.as_deref()))?;
+    builder = builder.output_header(output_header(args, &job.url, &dict_name));
+    let (paths, path_headers) = dict::extract_path_headers(paths);
+    let (mut paths, severity_tags) = dict::extract_severity_tags(paths);
+    builder = builder.severity_tags(severity_tags).path_headers(path_headers);
+    validate_entries(&mut paths, args.strict_dict)?;
+    if let Some(ref file) = args.exclude_file {
+        apply_exclusions(&mut paths, &dict::load_exclusions(file)?)?;
+    }
+    let scanner = Scanner::new(builder.build().context("配置校验失败")?)?;
+    if !args.allow_absolute && !args.param_fuzz {
+        drop_absolute_entries(&mut paths)?;
+    }
+    if args.add_slash && !args.param_fuzz {
+        paths = dict::add_slash_variants(paths);
+    }
+    let (mut paths, _) = scanner.dedupe_paths(paths);
+    if let Some(ratio) = args.sample {
+        paths = sample_paths(paths, ratio, args.seed);
+    }
+
+    // 同时运行的任务与其他任务的扫描输出共用打印队列
+    print_line(format!("[任务 {}] 开始扫描 {}（字典: {}）", index + 1, job.url, dict_name).green());
+    if args.login_url.is_some() {
+        let outcome = scanner.login().await.context("登录未成功，已跳过该任务")?;
+        print_line(format!("[任务 {}] 登录成功（状态码 {}）", index + 1, outcome.status).green());
+    }
+    let report = scanner.run(paths).await?;
+    Ok((report, outputs))
+}
+
+/// 运行基准测试并打印吞吐量
+#[cfg(feature = "benchmark")]
+async fn benchmark(builder: ScanConfigBuilder, requests: usize) -> Result<()> {
+    println!("{}", format!("基准测试: 向本地测试服务器发送 {} 个请求...", requests).green());
+    let result = rdirscan::run_benchmark(builder, requests).await?;
+    let report = &result.report;
+    println!("{}", format!("\n共发送请求 {} 个，有效发现 {} 个，已过滤 {} 个，出错 {} 个",
+        report.requests, report.found(), report.filtered(), report.errors).cyan());
+    println!("{}", format!("耗时 {:.2} 秒，吞吐量 {:.0} 请求/秒",
+        result.elapsed.as_secs_f64(), result.requests_per_sec()).green().bold());
+    Ok(())
+}
+
+#[cfg(not(feature = "benchmark"))]
+async fn benchmark(_builder: ScanConfigBuilder, _requests: usize) -> Result<()> {
+    Err(anyhow!("当前版本未包含基准测试功能，请使用 cargo build --features benchmark 重新编译"))
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 不访问网络，运行一组内部检查（URL校验、过滤规则解析、路径拼接、UA随机性等）并逐项报告结果
+    Selftest,
+}
+
+/// 运行自检并打印每一项的结果，有未通过的项时返回错误
+fn selftest() -> Result<()> {
+    println!("RDirScan {} 自检", env!("CARGO_PKG_VERSION"));
+    let checks = rdirscan::run_selftest();
+    for check in &checks {
+        match check.error {
+            None => println!("{} {}", "[通过]".green(), check.name),
+            Some(ref error) => println!("{} {}: {}", "[失败]".red(), check.name, error),
+        }
+    }
+    let failed = checks.iter().filter(|check| !check.passed()).count();
+    if failed > 0 {
+        return Err(anyhow!("{} 项自检未通过（共 {} 项）", failed, checks.len()));
+    }
+    println!("{}", format!("全部 {} 项自检通过", checks.len()).green());
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = parse_args()?;
+    rdirscan::init_logging(args.log_format.parse()?)?;
+    if let Some(Command::Selftest) = args.command {
+        return selftest();
+    }
+    if args.list_builtin {
+        for path in dict::builtin_dictionary() {
+            println!("{}", path);
+        }
+        return Ok(());
+    }
+
+    let ports = args.ports
+        .iter()
+        .map(|entry| parse_port(entry))
+        .collect::<Result<Vec<_>>>()?;
+    if args.tui {
+        tui::check_available()?;
+    }
+    let tui = args.tui && tui::supported();
+    if args.tui && !tui {
+        warn!("标准输出不是终端，已忽略 --tui");
+    }
+    // 非交互模式下只能在界面或终端中输入 r 继续，否则暂停后扫描再也无法继续
+    if !args.pause_on_match.is_empty() && args.auto && !tui && !io::stdin().is_terminal() {
+        return Err(anyhow!("--pause-on-match 与 --auto 同时使用时标准输入必须是终端（或使用 --tui），否则暂停后无法继续"));
+    }
+
+    let mut builder = ScanConfig::builder()
+        .threads(args.threads.initial())
+        .timeout(args.timeout)
+        .connect_timeout(args.connect_timeout)
+        .insecure(args.insecure)
+        .follow_redirects(!args.no_follow_redirects)
+        .max_redirects(args.max_redirects)
+        .decompress(!args.no_decompress)
+        .sensitive_extensions(&args.sensitive_ext)
+        .randomize_headers(args.randomize_headers)
+        .shuffle_headers(args.shuffle_headers)
+        .auto_concurrency(args.auto_concurrency)
+        .head_only(args.only_status_line)
+        .compare_methods(args.compare_methods)
+        .sort_output(args.sort_output)
+        .ordered_output(args.ordered_output)
+        .ua_per_connection(args.ua_per_connection)
+        .static_ua(args.static_ua)
+        .timestamps(args.timestamps)
+        .param_fuzz(args.param_fuzz)
+        .allow_absolute(args.allow_absolute)
+        .filter_threshold(args.filter_threshold)
+        .no_proxy_env(args.no_proxy_env)
+        .auto_upgrade(args.auto_upgrade)
+        .max_errors_per_path(args.max_errors_per_path)
+        .collapse(args.collapse)
+        .unique_titles(args.unique_titles)
+        .quiet(tui)
+        // 标准输入用于读取目标或显示界面时无法交互确认
+        .interactive(!args.stdin_urls && !args.auto && !tui);
+    if let Some(ref url) = args.url {
+        builder = builder.url(url);
+    }
+    if let Some(ref user_agent) = args.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(ref proxy) = args.proxy {
+        builder = builder.proxy(proxy);
+    }
+    if let Some(ref path) = args.proxy_file {
+        builder = builder.proxy_pool(load_proxy_file(path)?);
+    }
+    if let Some(ref url) = args.proxy_check_url {
+        builder = builder.proxy_check_url(url);
+    }
+    if let Some(interval) = args.proxy_check_interval {
+        builder = builder.proxy_check_interval(interval);
+    }
+    if let Some(addr) = args.bind_address {
+        builder = builder.bind_address(addr);
+    }
+    for path in &args.ca_cert {
+        builder = builder.ca_cert(path);
+    }
+    for entry in &args.resolve {
+        let (host, addr) = parse_resolve(entry)?;
+        builder = builder.resolve(host, addr);
+    }
+    for host in &args.no_proxy {
+        builder = builder.no_proxy(host);
+    }
+    for host in &args.scope {
+        builder = builder.scope(host);
+    }
+    for text in &args.match_path {
+        builder = builder.match_path(text);
+    }
+    for text in &args.filter_path {
+        builder = builder.filter_path(text);
+    }
+    if let Some(delay) = args.delay_on_match {
+        builder = builder.delay_on_match(delay);
+    }
+    for text in &args.pause_on_match {
+        builder = builder.pause_on_match(text);
+    }
+    for entry in &args.header {
+        let (name, value) = parse_header(entry)?;
+        builder = builder.header(name, value);
+    }
+    if let Some(ref data) = args.data {
+        builder = builder.data(data);
+    }
+    if let Some(ref sni) = args.sni {
+        builder = builder.sni(sni);
+    }
+    if let Some(limit) = args.max_per_ip {
+        builder = builder.max_per_ip(limit);
+    }
+    if let Some(ref path) = args.unix_socket {
+        builder = builder.unix_socket(path);
+    }
+    for entry in &args.timeout_for {
+        let (pattern, timeout) = parse_timeout_override(entry)?;
+        builder = builder.timeout_override(pattern, timeout);
+    }
+    if let Some(secs) = args.calibration_timeout {
+        builder = builder.calibration_timeout(Duration::from_secs(secs));
+    }
+    if let Some(ref filter_path) = args.filter_file {
+        builder = builder.filter_file(filter_path);
+    }
+    if let Some(max) = args.max_requests {
+        builder = builder.max_requests(max);
+    }
+    if let Some(limit) = args.error_abort {
+        builder = builder.error_abort(limit);
+    }
+    if let Some(threads) = args.per_host_threads {
+        builder = builder.per_host_threads(threads);
+    }
+    if let Some(max) = args.max_findings {
+        builder = builder.max_findings(max);
+    }
+    if let Some(cap) = args.cap_per_code {
+        builder = builder.cap_per_code(cap);
+    }
+    builder = builder.max_url_length(args.max_url_length);
+    builder = builder.recursion_depth(args.recursion_depth);
+    if let Some(ref path) = args.recursion_dict {
+        builder = builder.recursion_paths(load_recursion_paths(path, &args)?);
+    }
+    if let Some(threshold) = args.outliers {
+        builder = builder.outlier_threshold(threshold);
+    }
+    if let Some(threshold) = args.smart_match {
+        builder = builder.smart_match(threshold);
+    }
+    if let Some(size) = args.pool_size {
+        builder = builder.pool_max_idle_per_host(size);
+    }
+    if let Some(secs) = args.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(nodelay) = args.tcp_nodelay {
+        builder = builder.tcp_nodelay(nodelay);
+    }
+    if let Some(interval) = args.tcp_keepalive {
+        builder = builder.tcp_keepalive(interval);
+    }
+    // 基准测试使用自己的目标和字典，不写入输出文件
+    if let Some(requests) = args.benchmark {
+        return benchmark(builder, requests).await;
+    }
+
+    let outputs = if args.no_output {
+        Vec::new()
+    } else if args.output.is_empty() {
+        vec!["out.txt".to_string()]
+    } else {
+        args.output.clone()
+    };
+    builder = builder.output_encoding(args.output_encoding.parse()?);
+    if let Some(ref template) = args.output_template {
+        builder = builder.output_template(template.parse()?);
+    }
+    if let Some(ref path) = args.events_file {
+        builder = builder.events_file(path);
+    }
+    if let Some(ref path) = args.trace {
+        builder = builder.trace_file(path);
+    }
+    if let Some(ref path) = args.emit_curl {
+        builder = builder.curl_file(path);
+    }
+    if let Some(ref path) = args.rescan {
+        builder = builder.conditional_file(path);
+    }
+    if let Some(ref level) = args.min_severity {
+        builder = builder.min_severity(level.parse()?);
+    }
+    if let Some(bytes) = args.max_body_size {
+        builder = builder.max_body_size(bytes);
+    }
+    if let Some(bytes) = args.skip_large {
+        builder = builder.skip_large(bytes);
+    }
+    if let Some(bytes) = args.min_content_length {
+        builder = builder.min_content_length(bytes);
+    }
+    builder = builder.show_forbidden(args.show_forbidden);
+    if let Some(ref spec) = args.match_codes {
+        builder = builder.match_codes(spec.parse()?);
+    }
+    builder = builder.append_output(args.append);
+    builder = builder.follow_rate_limit(!args.ignore_rate_limit);
+    if let Some(ref path) = args.save_filters {
+        builder = builder.save_filters(path);
+    }
+    if let Some(ref path) = args.errors_file {
+        builder = builder.errors_file(path);
+    }
+    if let Some(ref path) = args.sqlite {
+        builder = builder.sqlite(path);
+    }
+    if let Some(ref command) = args.on_finding {
+        builder = builder.on_finding(command);
+    }
+    if let Some(ref url) = args.webhook {
+        builder = builder.webhook(url);
+    }
+    if let Some(seed) = args.seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(ref credentials) = args.digest_auth {
+        let (username, password) = credentials
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Digest认证凭据 '{}' 格式错误，应为 用户名:密码", credentials))?;
+        builder = builder.digest_auth(username, password);
+    }
+    if let Some(ref secret) = args.sign_secret {
+        builder = builder.sign_secret(secret);
+    }
+    if let Some(ref name) = args.sign_header {
+        builder = builder.sign_header(name);
+    }
+    if let Some(ref name) = args.sign_timestamp_header {
+        builder = builder.sign_timestamp_header(name);
+    }
+    if let Some(ref template) = args.sign_template {
+        builder = builder.sign_template(template.parse()?);
+    }
+    if let (Some(url), Some(data)) = (&args.login_url, &args.login_data) {
+        builder = builder.login(url, data);
+    }
+    if let Some(ref text) = args.login_success {
+        builder = builder.login_success(text);
+    }
+    if let Some(ref text) = args.login_fail {
+        builder = builder.login_failure(text);
+    }
+    if let Some(ref selector) = args.csrf_selector {
+        builder = builder.csrf(CsrfExtractor::Selector(selector.clone()));
+    }
+    if let Some(ref pattern) = args.csrf_regex {
+        builder = builder.csrf(CsrfExtractor::Regex(pattern.clone()));
+    }
+    if let Some(ref name) = args.csrf_field {
+        builder = builder.csrf_field(name);
+    }
+    if let Some(ref url) = args.login_page {
+        builder = builder.login_page(url);
+    }
+    if let Some(ref path) = args.jobs {
+        return run_jobs(builder, JobFile::load(path)?, &args).await;
+    }
+    if let Some(ref path) = args.replay {
+        return run_replay(builder, path, &args).await;
+    }
+    for path in &outputs {
+        builder = builder.output_file(path);
+    }
+
+    // 字典项末尾的严重性标签需要在扫描前拆出
+    let (paths, dict_name) = load_paths(args.dict.as_deref())?;
+    let (paths, path_headers) = dict::extract_path_headers(paths);
+    let (mut paths, severity_tags) = dict::extract_severity_tags(paths);
+    builder = builder.severity_tags(severity_tags).path_headers(path_headers);
+    validate_entries(&mut paths, args.strict_dict)?;
+    // 明确不应请求的路径（注销、关机等）在一切扩展之前剔除
+    let excluded = match args.exclude_file {
+        Some(ref file) => Some(apply_exclusions(&mut paths, &dict::load_exclusions(file)?)?),
+        None => None,
+    };
+    let target = args.url.as_deref().unwrap_or("标准输入");
+    builder = builder.output_header(output_header(&args, target, &dict_name));
+
+    // 校验URL等配置
+    let config = builder.clone().build()
+        .context("配置校验失败")?;
+    let mut scanner = Scanner::new(config)?;
+    // 在扫描覆盖输出文件之前读取上次的结果
+    let previous = match args.diff {
+        Some(ref path) => Some(diff::load_previous(Path::new(path))?),
+        None => None,
+    };
+
+    // 完整URL会替换掉目标主机，除非明确允许，否则在扫描前剔除
+    if !args.allow_absolute && !args.param_fuzz {
+        drop_absolute_entries(&mut paths)?;
+    }
+    if !ports.is_empty() {
+        let ports = ports
+            .iter()
+            .map(|(scheme, port)| match scheme {
+                Some(scheme) => format!("{}:{}", scheme, port),
+                None => port.to_string(),
+            })
+            .collect::<Vec<_>>();
+        println!("{}", format!("扫描端口: {}", ports.join(", ")).cyan());
+    }
+    if args.no_output {
+        println!("{}", "不写入输出文件，结果只打印到控制台".cyan());
+    }
+    if args.append {
+        println!("{}", "追加到已有的输出文件".cyan());
+    }
+    if args.timestamps {
+        println!("{}", "文本输出附带发现时间".cyan());
+    }
+    if let Some(ref template) = args.output_template {
+        println!("{}", format!("输出模板: {}", template).cyan());
+    }
+    if let Some(ref command) = args.on_finding {
+        println!("{}", format!("发现回调命令: {}", command).cyan());
+    }
+    if let Some(ref url) = args.webhook {
+        println!("{}", format!("发现推送地址: {}", url).cyan());
+    }
+    if args.ignore_rate_limit {
+        println!("{}", "忽略服务器返回的限流响应头".cyan());
+    }
+    if let Some(ref level) = args.min_severity {
+        println!("{}", format!("最低严重性: {}", level.to_uppercase()).cyan());
+    }
+    if let Some(bytes) = args.max_body_size {
+        println!("{}", format!("响应体上限: {} 字节", bytes).cyan());
+    }
+    if let Some(bytes) = args.skip_large {
+        println!("{}", format!("大文件阈值: {} 字节（超过时不读取响应体）", bytes).cyan());
+    }
+    if let Some(bytes) = args.min_content_length {
+        println!("{}", format!("最小响应大小: {} 字节", bytes).cyan());
+    }
+    if args.show_forbidden {
+        println!("{}", "报告返回401/403的受保护资源".cyan());
+    }
+    if let Some(ref spec) = args.match_codes {
+        println!("{}", format!("匹配状态码: {}", spec).cyan());
+    }
+    if let (Some(ref file), Some(excluded)) = (&args.exclude_file, excluded) {
+        println!("{}", format!("排除列表 {}: 已剔除 {} 条字典项", file, excluded).cyan());
+    }
+    // 参数名加斜杠没有意义
+    if args.add_slash && !args.param_fuzz {
+        paths = dict::add_slash_variants(paths);
+    }
+    let (mut paths, duplicates) = scanner.dedupe_paths(paths);
+    if duplicates > 0 {
+        println!("{}", format!("已合并 {} 个指向相同URL的重复字典项", duplicates).cyan());
+    }
+    if let Some(ratio) = args.sample {
+        let total = paths.len();
+        paths = sample_paths(paths, ratio, args.seed);
+        let seed = args.seed.map_or(String::new(), |seed| format!("，种子 {}", seed));
+        println!("{}", format!("抽样扫描: 从 {} 条字典项中随机抽取 {} 条（{:.1}%{}）", total, paths.len(), ratio * 100.0, seed).cyan());
+    }
+
+    println!("{}", "开始扫描...".green());
+    match scanner.config().base_url() {
+        Some(base_url) => println!("{}", format!("目标 URL: {}", base_url).cyan()),
+        None => println!("{}", "目标 URL: 从标准输入读取".cyan()),
+    }
+    println!("{}", format!("字典文件: {}", dict_name).cyan());
+    match (args.auto_concurrency, args.threads) {
+        (true, _) => println!("{}", format!("并发线程: 自动（上限 {}）", args.threads.initial()).cyan()),
+        (false, ThreadCount::Auto) => println!("{}", format!("并发线程: 自动（初始 {}，预热后按目标延迟调整）", args.threads.initial()).cyan()),
+        (false, ThreadCount::Fixed(threads)) => println!("{}", format!("并发线程: {}", threads).cyan()),
+    }
+    if let Some(threads) = args.per_host_threads {
+        println!("{}", format!("每主机并发: {}", threads).cyan());
+    }
+    if let Some(seed) = args.seed {
+        println!("{}", format!("随机种子: {}", seed).cyan());
+    }
+    println!("{}", format!("连接超时: {}", format_duration(args.connect_timeout)).cyan());
+    println!("{}", format!("请求超时: {}", format_duration(args.timeout)).cyan());
+    for entry in &args.timeout_for {
+        println!("{}", format!("单独超时: {}", entry).cyan());
+    }
+    if let Some(secs) = args.calibration_timeout {
+        println!("{}", format!("基线校准超时: {}秒", secs).cyan());
+    }
+    if let Some(ref proxy) = args.proxy {
+        println!("{}", format!("使用代理: {}", proxy).cyan());
+    } else if let Some(ref path) = args.proxy_file {
+        println!("{}", format!("代理池文件: {}", path).cyan());
+    } else if args.no_proxy_env {
+        println!("{}", "已忽略环境变量中的代理".cyan());
+    } else {
+        for scheme in ["http", "https"] {
+            if let Some(proxy) = scanner.config().effective_proxy(scheme) {
+                println!("{}", format!("使用环境变量中的代理（{}）: {}", scheme, proxy).cyan());
+            }
+        }
+    }
+    if !args.no_proxy.is_empty() {
+        println!("{}", format!("不经过代理: {}", args.no_proxy.join(", ")).cyan());
+    }
+    if args.filter_file.is_some() {
+        println!("{}", "已启用误报过滤".cyan());
+    }
+    if args.filter_threshold != rdirscan::DEFAULT_FILTER_THRESHOLD {
+        println!("{}", format!("重复大小过滤阈值: {} 次", args.filter_threshold).cyan());
+    }
+    if args.only_status_line {
+        println!("{}", "仅状态行模式: 使用HEAD请求，不下载响应体".cyan());
+        if args.filter_file.is_some() {
+            println!("{}", "仅状态行模式下没有响应内容，过滤规则文件中的内容特征不会生效".yellow());
+        }
+    }
+    if args.compare_methods {
+        println!("{}", "请求方法比较: 每条发现用 GET、HEAD、POST、OPTIONS 中的其余方法各补发一次请求".cyan());
+    }
+    if args.insecure {
+        println!("{}", "已禁用SSL证书验证".yellow());
+    }
+    for path in &args.ca_cert {
+        println!("{}", format!("额外信任的CA证书: {}", path).cyan());
+    }
+    if let Some(addr) = args.bind_address {
+        println!("{}", format!("绑定地址: {}", addr).cyan());
+    }
+    for entry in &args.resolve {
+        println!("{}", format!("自定义解析: {}", entry).cyan());
+    }
+    if !args.scope.is_empty() {
+        println!("{}", format!("扫描范围: {}", args.scope.join(", ")).cyan());
+    }
+    if !args.match_path.is_empty() {
+        println!("{}", format!("只请求路径包含以下任一内容的URL: {}", args.match_path.join(", ")).cyan());
+    }
+    if !args.filter_path.is_empty() {
+        println!("{}", format!("跳过路径包含以下任一内容的URL: {}", args.filter_path.join(", ")).cyan());
+    }
+    if let Some(delay) = args.delay_on_match {
+        println!("{}", format!("每个有效发现之后暂缓 {} 再派发新请求", format_duration(delay)).cyan());
+    }
+    if !args.pause_on_match.is_empty() {
+        println!("{}", format!("路径包含以下任一内容的有效发现会暂停扫描: {}", args.pause_on_match.join(", ")).cyan());
+    }
+    if let Some(ref config) = args.config {
+        println!("{}", format!("配置文件: {}", config).cyan());
+    }
+    if args.auto_upgrade {
+        println!("{}", "目标统一跳转到https时自动改用https".cyan());
+    }
+    if args.no_follow_redirects {
+        println!("{}", "已禁用跟随跳转，3xx响应会连同跳转目标一起报告".cyan());
+    }
+    if args.no_decompress {
+        println!("{}", "已禁用响应解压".yellow());
+    }
+    if let Some(threshold) = args.smart_match {
+        println!("{}", format!("智能匹配: 与404基线相差超过 {} 字节或状态码不同即报告", threshold).cyan());
+    }
+    if let Some(threshold) = args.outliers {
+        println!("{}", format!("异常值模式: 偏离主导大小 {} 字节以上", threshold).cyan());
+    }
+    if args.param_fuzz {
+        println!("{}", "参数模式: 字典项作为查询参数名追加到目标URL".cyan());
+    }
+    if let Some(ref sni) = args.sni {
+        println!("{}", format!("TLS SNI: {}", sni).cyan());
+    }
+    if let Some(ref path) = args.unix_socket {
+        println!("{}", format!("Unix套接字: {}", path).cyan());
+    }
+    if let Some(limit) = args.max_per_ip {
+        println!("{}", format!("每个后端地址最多 {} 个并发请求", limit).cyan());
+    }
+    if args.recursion_depth > 0 {
+        let dict = args.recursion_dict.as_deref().unwrap_or("主字典");
+        println!("{}", format!("递归扫描: 最多 {} 层（字典: {}）", args.recursion_depth, dict).cyan());
+    }
+    for header in &args.header {
+        println!("{}", format!("请求头: {}", header).cyan());
+    }
+    if let Some(ref data) = args.data {
+        println!("{}", format!("POST请求体: {}", data).cyan());
+    }
+    if args.add_slash && !args.param_fuzz {
+        println!("{}", format!("已启用斜杠变体，共 {} 条路径", paths.len()).cyan());
+    }
+    if let Some(size) = args.pool_size {
+        println!("{}", format!("连接池大小: 每主机 {} 个空闲连接", size).cyan());
+    }
+    if let Some(secs) = args.pool_idle_timeout {
+        println!("{}", format!("空闲连接超时: {}秒", secs).cyan());
+    }
+    if args.tcp_nodelay == Some(false) {
+        println!("{}", "已关闭 TCP_NODELAY".cyan());
+    }
+    if let Some(interval) = args.tcp_keepalive {
+        println!("{}", format!("TCP keepalive: {:.0}秒", interval.as_secs_f64()).cyan());
+    }
+    if args.sort_output {
+        println!("{}", "输出文件将在扫描结束后排序去重".cyan());
+    }
+    if args.ordered_output {
+        println!("{}", "输出文件将在扫描结束后按字典顺序写入".cyan());
+    }
+    if args.ua_per_connection {
+        println!("{}", "UA模式: 每个连接固定一个UA".cyan());
+    }
+    if let Some(ref user_agent) = args.user_agent {
+        println!("{}", format!("UA模式: 固定使用 {}", user_agent).cyan());
+    } else if args.static_ua {
+        println!("{}", "UA模式: 整个扫描固定一个随机UA".cyan());
+    }
+    if args.randomize_headers {
+        println!("{}", "已启用请求头随机化".cyan());
+    }
+    if args.shuffle_headers {
+        println!("{}", "已启用请求头顺序随机化".cyan());
+    }
+    if let Some(ref path) = args.events_file {
+        println!("{}", format!("事件流文件: {}", path).cyan());
+    }
+    if let Some(ref path) = args.sqlite {
+        println!("{}", format!("SQLite数据库: {}", path).cyan());
+    }
+    if let Some((username, _)) = args.digest_auth.as_deref().and_then(|credentials| credentials.split_once(':')) {
+        println!("{}", format!("Digest认证用户: {}", username).cyan());
+    }
+    if args.sign_secret.is_some() {
+        let template = args.sign_template.as_deref().unwrap_or(DEFAULT_SIGN_TEMPLATE);
+        let header = args.sign_header.as_deref().unwrap_or(DEFAULT_SIGN_HEADER);
+        println!("{}", format!("请求签名: {} = HMAC-SHA256({})", header, template).cyan());
+    }
+    if let Some(ref path) = args.trace {
+        println!("{}", format!("请求跟踪文件: {}", path).cyan());
+    }
+    if let Some(ref path) = args.emit_curl {
+        println!("{}", format!("curl命令文件: {}", path).cyan());
+    }
+    if let Some(ref path) = args.rescan {
+        println!("{}", format!("条件请求: 按 {} 中记录的 ETag/Last-Modified 跳过未修改的资源", path).cyan());
+    }
+    if let Some(max) = args.max_requests {
+        println!("{}", format!("最大请求数: {}", max).cyan());
+    }
+    if let Some(max) = args.max_findings {
+        println!("{}", format!("最大发现数: {}", max).cyan());
+    }
+    if let Some(cap) = args.cap_per_code {
+        println!("{}", format!("每个状态码最多写入 {} 条发现", cap).cyan());
+    }
+    if let Some(limit) = args.error_abort {
+        println!("{}", format!("连续出错上限: {}", limit).cyan());
+    }
+    if args.max_errors_per_path > 1 {
+        println!("{}", format!("网络错误重试: 每个路径最多尝试 {} 次", args.max_errors_per_path).cyan());
+    }
+    if args.output_encoding != "utf8" {
+        println!("{}", format!("输出文件编码: {}", args.output_encoding).cyan());
+    }
+    if args.collapse {
+        println!("{}", "折叠模式: 被过滤的结果只显示计数".cyan());
+    }
+    if args.unique_titles {
+        println!("{}", "按标题去重: 相同标题的页面只报告第一个".cyan());
+    }
+    if args.auto {
+        println!("{}", "非交互模式: 所有询问自动确认".cyan());
+    }
+
+    if args.show_cert {
+        match scanner.certificate().await {
+            Ok(cert) => print_certificate(&cert),
+            Err(e) => warn!("无法读取TLS证书: {}", e),
+        }
+    }
+    // 先确认目标可达，避免配置错误时刷出满屏的逐路径错误
+    if scanner.config().base_url().is_some() && ports.is_empty() && (args.probe_only || !args.skip_warmup) {
+        let outcome = scanner.warmup().await
+            .context("预热请求失败，已停止扫描（确认无误时可使用 --skip-warmup 跳过检查）")?;
+        print_warmup(&outcome);
+        if args.probe_only {
+            return Ok(());
+        }
+        // 扫描器的连接槽位等按并发数创建，调整并发时需要重建；自动并发模式下由控制器持续调整，不再改动上限
+        if args.threads == ThreadCount::Auto && !args.auto_concurrency {
+            let threads = rdirscan::suggest_threads(Some(outcome.elapsed));
+            println!("{}", format!("按目标延迟 {}ms 自动选择并发线程: {}", outcome.elapsed.as_millis(), threads).cyan());
+            if threads != args.threads.initial() {
+                scanner = Scanner::new(builder.threads(threads).build().context("配置校验失败")?)?;
+            }
+        }
+    } else if args.threads == ThreadCount::Auto && !args.auto_concurrency {
+        println!("{}", format!("未进行预热请求，按CPU核心数选择并发线程: {}", args.threads.initial()).cyan());
+    }
+
+    // 先登录，之后的请求都带上会话Cookie
+    if let Some(ref url) = args.login_url {
+        println!("{}", format!("正在登录: {}", url).cyan());
+        let outcome = scanner.login().await.context("登录未成功，已停止扫描")?;
+        if let Some(ref field) = outcome.csrf_field {
+            println!("{}", format!("已从登录页提取CSRF令牌（字段 {}）", field).cyan());
+        }
+        println!("{}", format!("登录成功（状态码 {}），目标可用的Cookie {} 个", outcome.status, outcome.cookies).green());
+    }
+
+    let dashboard = match tui {
+        true => {
+            let target = args.url.clone().unwrap_or_else(|| "标准输入".to_string());
+            Some(Dashboard::start(scanner.stats(), scanner.pause_control(), target, args.latency_eta)?)
+        }
+        false => None,
+    };
+    // 询问的回答与命令共用终端输入，由 console 统一读取；标准输入提供目标时不能读取
+    if !tui && !args.stdin_urls && io::stdin().is_terminal() {
+        println!("{}", "输入 p 回车暂停派发新请求，输入 r 回车继续".cyan());
+        watch_pause_keys(scanner.pause_control());
+    }
+    let result = if args.stdin_urls {
+        let lines = BufReader::new(tokio::io::stdin()).lines();
+        let targets = LinesStream::new(lines)
+            .take_while(|line| future::ready(line.is_ok()))
+            .filter_map(|line| {
+                let line = line.unwrap_or_default().trim().to_string();
+                future::ready((!line.is_empty() && !line.starts_with('#')).then_some(line))
+            })
+            .flat_map(|target| {
+                // 标准输入中的一个目标无效时跳过它，继续扫描其他目标
+                let targets = expand_ports(target, &ports).unwrap_or_else(|e| {
+                    warn!("{:#}，已跳过", e);
+                    Vec::new()
+                });
+                futures::stream::iter(targets)
+            });
+        scanner.run_targets(targets, paths).await
+    } else if !ports.is_empty() {
+        let targets = args.url.clone().map(|url| expand_ports(url, &ports)).transpose()?.unwrap_or_default();
+        scanner.run_targets(futures::stream::iter(targets), paths).await
+    } else {
+        scanner.run(paths).await
+    };
+    if let Some(dashboard) = dashboard {
+        dashboard.stop()?;
+    }
+    let report = result?;
+
+    if outputs.is_empty() {
+        println!("{}", "\n扫描完成！".green());
+    } else {
+        println!("{}", format!("\n扫描完成！结果已保存到 {}", outputs.join(", ")).green());
+    }
+    println!("{}", format!("共发送请求 {} 个，有效发现 {} 个，已过滤 {} 个，出错 {} 个",
+        report.requests, report.found(), report.filtered(), report.errors).cyan());
+    if report.skipped_long_urls > 0 {
+        println!("{}", format!("URL超过 {} 字节而跳过的请求 {} 个", args.max_url_length, report.skipped_long_urls).yellow());
+    }
+    if report.skipped_paths > 0 {
+        println!("{}", format!("按路径筛选跳过的请求 {} 个", report.skipped_paths).cyan());
+    }
+    if report.webhook_undelivered > 0 {
+        println!("{}", format!("未能发送到webhook的发现 {} 个", report.webhook_undelivered).yellow());
+    }
+    if report.too_small() > 0 {
+        println!("{}", format!("其中小于 {} 字节的响应 {} 个", args.min_content_length.unwrap_or_default(), report.too_small()).cyan());
+    }
+    if !report.capped.is_empty() {
+        let totals = report.found_by_status();
+        let summary = report.capped
+            .iter()
+            .map(|(status, capped)| {
+                let total = totals.get(status).copied().unwrap_or(0);
+                format!("状态码 {} 共 {} 个，写入 {} 个", status, total, total - capped)
+            })
+            .collect::<Vec<_>>();
+        println!("{}", format!("已达到每个状态码的输出上限: {}", summary.join("，")).yellow());
+    }
+    if report.listings() > 0 {
+        println!("{}", format!("发现开放的目录列表 {} 个", report.listings()).bright_cyan().bold());
+    }
+    if let Some(ref path) = args.save_filters {
+        println!("{}", format!("过滤规则已保存到 {}", path).green());
+    }
+    let sizes = report.size_counts();
+    if !sizes.is_empty() {
+        let common = sizes
+            .iter()
+            .take(SUMMARY_SIZES)
+            .map(|(size, count)| format!("{} 字节 ×{}", size, count))
+            .collect::<Vec<_>>();
+        println!("{}", format!("响应大小: 共 {} 种，最常见: {}", sizes.len(), common.join("，")).cyan());
+        if sizes.len() <= FEW_SIZES && report.findings.len() >= FEW_SIZES_MIN_RESPONSES {
+            println!("{}", format!("{} 个响应只有 {} 种大小，目标可能对任意路径返回相同页面（泛解析或WAF拦截），建议检查", report.findings.len(), sizes.len()).yellow());
+        }
+    }
+    if report.clusters.len() > 1 {
+        println!("{}", format!("响应聚类: 共 {} 类，其中只出现一次的响应 {} 个", report.clusters.len(), report.unique_responses()).cyan());
+        for cluster in report.clusters.iter().take(SUMMARY_CLUSTERS) {
+            println!("  {}", cluster);
+        }
+        if report.clusters.len() > SUMMARY_CLUSTERS {
+            println!("  {}", format!("……其余 {} 类见 --events-file 的汇总", report.clusters.len() - SUMMARY_CLUSTERS).dimmed());
+        }
+    }
+    if ports.len() > 1 {
+        let counts = report.found_by_port();
+        let summary = ports
+            .iter()
+            .map(|(_, port)| format!("{} 端口 {} 个", port, counts.get(port).copied().unwrap_or(0)))
+            .collect::<Vec<_>>();
+        println!("{}", format!("按端口统计有效发现: {}", summary.join("，")).cyan());
+    }
+    let technologies = report.technologies();
+    if !technologies.is_empty() {
+        let mut technologies: Vec<_> = technologies.into_iter().collect();
+        technologies.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+        let summary = technologies
+            .iter()
+            .map(|(technology, count)| format!("{} ({})", technology, count))
+            .collect::<Vec<_>>();
+        println!("{}", format!("检测到的服务端技术: {}", summary.join("，")).cyan());
+    }
+    if report.protected() > 0 {
+        println!("{}", format!("发现受保护（401/403）的资源 {} 个", report.protected()).cyan());
+    }
+    if report.skipped_bodies() > 0 {
+        println!("{}", format!("超过 {} 字节而没有读取响应体的发现 {} 个", args.skip_large.unwrap_or_default(), report.skipped_bodies()).cyan());
+    }
+    if !report.method_discrepancies.is_empty() {
+        println!("\n{}", format!("以下 {} 个路径的状态码因请求方法而不同，可能存在基于方法的访问控制绕过:", report.method_discrepancies.len()).yellow());
+        for discrepancy in &report.method_discrepancies {
+            println!("{}", format!("  {}", discrepancy).yellow());
+        }
+    }
+    if report.length_mismatches() > 0 {
+        println!("{}", format!("Content-Length 与实际长度不符的响应 {} 个，可能存在截断或中间设备注入内容", report.length_mismatches()).yellow());
+    }
+    if let Some(concurrency) = report.concurrency {
+        println!("{}", format!("自动并发最终稳定在: {}", concurrency).cyan());
+    }
+    if let Some(ref reason) = report.stop_reason {
+        println!("{}", format!("扫描提前停止: {}", reason).yellow());
+    }
+    if !report.failed.is_empty() {
+        println!("\n{}", format!("以下 {} 个路径出错后已放弃:", report.failed.len()).yellow());
+        for error in &report.failed {
+            println!("{}", format!("  {} ({})", error.url, error.message).yellow());
+        }
+    }
+    if args.tree && report.found() > 0 {
+        println!("\n{}", "目录结构:".green());
+        print!("{}", render_tree(&report.findings));
+    }
+    if let (Some(previous), Some(path)) = (previous, &args.diff) {
+        print_diff(&ScanDiff::compute(&previous, &report.findings), path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration(" 2m "), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("24h"), Ok(MAX_DURATION));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5d").is_err());
+        // 超过上限或溢出的值直接拒绝，不会在之后加到当前时间上时溢出
+        for text in ["25h", "86401", "5124095576030431h", "18446744073709551615"] {
+            let err = parse_duration(text).unwrap_err();
+            assert!(err.contains("无效"), "{}", err);
+        }
+        assert!(parse_timeout_override("*.zip=86401").is_err());
+        assert_eq!(parse_timeout_override("*.zip=60").unwrap(), ("*.zip".to_string(), Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn expands_targets_per_port() {
+        let ports = [(None, 8443), (Some("http".to_string()), 9000), (None, 8000)];
+        assert_eq!(
+            expand_ports("http://example.com/app/".to_string(), &ports).unwrap(),
+            ["https://example.com:8443/app/", "http://example.com:9000/app/", "http://example.com:8000/app/"]
+        );
+        assert_eq!(expand_ports("not a url".to_string(), &[]).unwrap(), ["not a url"]);
+        let err = expand_ports("http://".to_string(), &ports).unwrap_err();
+        assert!(format!("{:#}", err).starts_with("无法按 --ports 展开目标 http://"), "{:#}", err);
+        let err = expand_ports("http://example.com/".to_string(), &[(Some("ftp".to_string()), 21)]).unwrap_err();
+        assert!(format!("{:#}", err).contains("ftp"), "{:#}", err);
+    }
+
+    #[test]
+    fn parses_sample_ratios() {
+        assert_eq!(parse_sample("10%"), Ok(0.1));
+        assert_eq!(parse_sample(" 25 % "), Ok(0.25));
+        assert_eq!(parse_sample("0.5"), Ok(0.5));
+        assert_eq!(parse_sample("100%"), Ok(1.0));
+        for invalid in ["0", "0%", "150%", "1.5", "-10%", "abc", "%", "NaN"] {
+            assert!(parse_sample(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn samples_paths_in_order() {
+        let paths: Vec<String> = (0..100).map(|i| format!("p{}", i)).collect();
+        let sampled = sample_paths(paths.clone(), 0.1, Some(7));
+        assert_eq!(sampled.len(), 10);
+        // 保持字典中的顺序
+        let positions: Vec<usize> = sampled.iter().map(|path| paths.iter().position(|p| p == path).unwrap()).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        // 同一种子结果相同
+        assert_eq!(sample_paths(paths.clone(), 0.1, Some(7)), sampled);
+        // 至少保留一条，比例为1时全部保留
+        assert_eq!(sample_paths(paths[..3].to_vec(), 0.01, None).len(), 1);
+        assert_eq!(sample_paths(paths.clone(), 1.0, None), paths);
+        assert!(sample_paths(Vec::new(), 0.5, None).is_empty());
+    }
+
+    #[test]
+    fn parses_proxy_lists() {
+        let proxies = parse_proxy_list("# 机房A\nhttp://10.0.0.1:8080\n\n  socks5://10.0.0.2:1080  \r\n#http://10.0.0.3:8080\n");
+        assert_eq!(proxies, ["http://10.0.0.1:8080", "socks5://10.0.0.2:1080"]);
+        assert!(parse_proxy_list("# 全部停用\n\n").is_empty());
+        let err = load_proxy_file("/nonexistent/proxies.txt").unwrap_err().to_string();
+        assert!(err.contains("读取代理列表文件失败"), "{}", err);
+    }
 }
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+use url::Url;
+
+/// `--compare-methods` 依次使用的请求方法
+pub(crate) const COMPARED_METHODS: [Method; 4] = [Method::GET, Method::HEAD, Method::POST, Method::OPTIONS];
+
+/// 同一路径的状态码因请求方法而不同，可能存在基于方法的访问控制绕过（如GET返回403而POST返回200）
+#[derive(Debug, Clone)]
+pub struct MethodDiscrepancy {
+    pub url: Url,
+    /// 各方法得到的状态码，按请求顺序；请求出错的方法不在其中
+    pub statuses: Vec<(Method, StatusCode)>,
+}
+
+impl MethodDiscrepancy {
+    /// 比较各方法的状态码，表示不支持该方法的响应（405/501）不参与比较，其余状态码不全相同时返回差异
+    pub(crate) fn detect(url: Url, statuses: Vec<(Method, StatusCode)>) -> Option<Self> {
+        let mut compared = statuses
+            .iter()
+            .map(|(_, status)| *status)
+            .filter(|status| !matches!(*status, StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED));
+        let first = compared.next()?;
+        compared.any(|status| status != first).then_some(Self { url, statuses })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url.as_str(),
+            "statuses": self.statuses
+                .iter()
+                .map(|(method, status)| json!({ "method": method.as_str(), "status": status.as_u16() }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for MethodDiscrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let statuses = self.statuses
+            .iter()
+            .map(|(method, status)| format!("{} {}", method, status.as_u16()))
+            .collect::<Vec<_>>();
+        write!(f, "{} ({})", self.url, statuses.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_unsupported_methods() {
+        let url = Url::parse("http://example.com/admin").unwrap();
+        let detect = |codes: [u16; 4]| {
+            let statuses = COMPARED_METHODS
+                .iter()
+                .cloned()
+                .zip(codes.map(|code| StatusCode::from_u16(code).unwrap()))
+                .collect();
+            MethodDiscrepancy::detect(url.clone(), statuses)
+        };
+        assert!(detect([200, 200, 405, 501]).is_none());
+        assert!(detect([404, 404, 404, 404]).is_none());
+        let bypass = detect([403, 403, 200, 405]).unwrap();
+        assert_eq!(bypass.to_string(), "http://example.com/admin (GET 403, HEAD 403, POST 200, OPTIONS 405)");
+    }
+}
@@ -0,0 +1,516 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use colored::*;
+use encoding_rs::GBK;
+use tracing::warn;
+
+use crate::config::ScanConfig;
+use crate::console;
+use crate::finding::Finding;
+use crate::severity::Severity;
+use crate::template::OutputTemplate;
+
+/// 结果输出目标，每条未过滤的发现都会依次写入
+pub(crate) trait OutputSink: Send {
+    fn write_finding(&mut self, finding: &Finding) -> Result<()>;
+
+    /// 扫描结束时调用，用于刷新缓冲
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// 文本和CSV输出文件的编码，JSON输出始终为UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputEncoding {
+    #[default]
+    Utf8,
+    /// 带BOM的UTF-8，部分Windows工具依靠BOM识别编码
+    Utf8Bom,
+    /// 中文Windows旧版编辑器的默认编码，无法表示的字符写为 `&#NNNN;`
+    Gbk,
+}
+
+impl OutputEncoding {
+    fn bom(self) -> &'static [u8] {
+        match self {
+            OutputEncoding::Utf8Bom => b"\xEF\xBB\xBF",
+            _ => b"",
+        }
+    }
+
+    fn encode(self, text: &str) -> Cow<'_, [u8]> {
+        match self {
+            OutputEncoding::Gbk => GBK.encode(text).0,
+            _ => Cow::Borrowed(text.as_bytes()),
+        }
+    }
+}
+
+impl FromStr for OutputEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => Ok(OutputEncoding::Utf8),
+            "utf8bom" => Ok(OutputEncoding::Utf8Bom),
+            "gbk" => Ok(OutputEncoding::Gbk),
+            _ => Err(anyhow!("不支持的输出编码 '{}'，可选 utf8、utf8-bom、gbk", s)),
+        }
+    }
+}
+
+impl fmt::Display for OutputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputEncoding::Utf8 => write!(f, "utf8"),
+            OutputEncoding::Utf8Bom => write!(f, "utf8-bom"),
+            OutputEncoding::Gbk => write!(f, "gbk"),
+        }
+    }
+}
+
+/// 按输出编码写入一行
+fn write_line(writer: &mut impl Write, encoding: OutputEncoding, line: &str) -> Result<()> {
+    writer.write_all(&encoding.encode(&format!("{}\n", line)))
+        .map_err(|e| anyhow!("写入结果到文件失败: {}", e))
+}
+
+/// 按扩展名创建输出：`.json` 为JSON数组，`.csv` 为CSV表格，其余为纯文本
+///
+/// 追加模式下保留文件原有内容；否则覆盖，文件已有内容时给出警告
+pub(crate) fn create_sink(path: &Path, config: &ScanConfig) -> Result<Box<dyn OutputSink>> {
+    let encoding = config.output_encoding;
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    let existing = has_content(path);
+    if existing && !config.append_output {
+        warn!(path = %path.display(), "输出文件已有内容，将被覆盖（使用 --append 追加）");
+    }
+    let append = config.append_output && existing;
+    Ok(match extension.as_deref() {
+        Some("json") => Box::new(JsonSink::create(path, append)?),
+        Some("csv") => Box::new(CsvSink::create(path, encoding, append)?),
+        _ => Box::new(TextSink::create(path, config.sort_output, encoding, append, config.timestamps, &config.output_header, config.output_template.clone())?),
+    })
+}
+
+fn has_content(path: &Path) -> bool {
+    path.metadata().is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+}
+
+fn create_file(path: &Path, append: bool) -> Result<File> {
+    let mut options = OpenOptions::new();
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).create(true).truncate(true);
+    }
+    options.open(path)
+        .map_err(|e| anyhow!("创建输出文件 {} 失败: {}", path.display(), e))
+}
+
+/// 纯文本输出，每行一个URL
+pub(crate) struct TextSink {
+    writer: File,
+    encoding: OutputEncoding,
+    // 排序模式下先缓存所有行，结束时排序去重后一次写入
+    buffered: Option<Vec<(Severity, String)>>,
+    timestamps: bool,
+    template: Option<OutputTemplate>,
+}
+
+impl TextSink {
+    /// `header` 中的每行以 `# ` 开头写在本次结果之前，作为字典重新载入时会被忽略
+    pub(crate) fn create(
+        path: &Path,
+        sorted: bool,
+        encoding: OutputEncoding,
+        append: bool,
+        timestamps: bool,
+        header: &[String],
+        template: Option<OutputTemplate>,
+    ) -> Result<Self> {
+        let mut file = create_file(path, append)?;
+        if !append {
+            file.write_all(encoding.bom())
+                .map_err(|e| anyhow!("写入结果到文件失败: {}", e))?;
+        }
+        for line in header {
+            write_line(&mut file, encoding, &format!("# {}", line))?;
+        }
+        Ok(Self {
+            writer: file,
+            encoding,
+            buffered: sorted.then(Vec::new),
+            timestamps,
+            template,
+        })
+    }
+
+    /// 默认的行格式：URL、大小以及各种标记
+    fn default_line(&self, finding: &Finding) -> String {
+        let mut line = format!("{} (大小: {} 字节)", finding.url.as_str(), finding.size);
+        if let Some(ref location) = finding.redirect {
+            line.push_str(&format!(" -> {}", location));
+        }
+        if let Some(ref final_url) = finding.final_url {
+            line.push_str(&format!(" => {}", final_url));
+        }
+        if finding.outlier {
+            line.push_str(&format!(" [异常: 状态码 {}]", finding.status.as_u16()));
+        }
+        if finding.sensitive {
+            line.push_str(" [敏感文件]");
+        }
+        if finding.listing {
+            line.push_str(" [DIR]");
+        }
+        if finding.protected {
+            line.push_str(&format!(" [受保护: 状态码 {}]", finding.status.as_u16()));
+        }
+        if let Some((declared, actual)) = finding.length_mismatch {
+            line.push_str(&format!(" [长度不符: 声明 {} 实际 {} 字节]", declared, actual));
+        }
+        if finding.truncated {
+            line.push_str(" [已截断]");
+        }
+        if finding.skipped_body {
+            line.push_str(" [大文件: 未读取内容]");
+        }
+        if let Some(ref word) = finding.word {
+            line.push_str(&format!(" [WORD: {}]", word));
+        }
+        if finding.severity != Severity::Info {
+            line.push_str(&format!(" [{}]", finding.severity));
+        }
+        if let Some(ref title) = finding.title {
+            line.push_str(&format!(" [标题: {}]", title));
+        }
+        if !finding.technologies.is_empty() {
+            line.push_str(&format!(" [技术: {}]", finding.technologies.join(", ")));
+        }
+        if self.timestamps {
+            line.push_str(&format!(" [时间: {}]", finding.timestamp_iso()));
+        }
+        line
+    }
+}
+
+impl OutputSink for TextSink {
+    fn write_finding(&mut self, finding: &Finding) -> Result<()> {
+        let line = match self.template {
+            Some(ref template) => template.render(finding),
+            None => self.default_line(finding),
+        };
+        if let Some(ref mut lines) = self.buffered {
+            lines.push((finding.severity, line));
+            return Ok(());
+        }
+        write_line(&mut self.writer, self.encoding, &line)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(mut lines) = self.buffered.take() {
+            // 严重性从高到低，同级内每行以URL开头，按行排序即按URL排序
+            lines.sort_by(|(a_severity, a), (b_severity, b)| b_severity.cmp(a_severity).then_with(|| a.cmp(b)));
+            lines.dedup();
+            for (_, line) in lines {
+                write_line(&mut self.writer, self.encoding, &line)?;
+            }
+        }
+        self.writer.flush()
+            .map_err(|e| anyhow!("写入结果到文件失败: {}", e))
+    }
+}
+
+/// JSON输出，整个文件是一个发现对象的数组
+///
+/// 每次写入都带上结尾的 `]`，下次写入时覆盖它，扫描中途退出时文件也是完整的JSON
+pub(crate) struct JsonSink {
+    writer: File,
+    count: usize,
+}
+
+/// 数组的结尾，下一个元素写入前回退到它之前
+const JSON_ENDING: &str = "\n]\n";
+
+impl JsonSink {
+    /// 追加模式下先读出原有数组，重写文件时保留其中的元素
+    pub(crate) fn create(path: &Path, append: bool) -> Result<Self> {
+        let existing: Vec<serde_json::Value> = if append {
+            let content = fs::read_to_string(path)
+                .map_err(|e| anyhow!("读取输出文件 {} 失败: {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| anyhow!("输出文件 {} 不是JSON数组，无法追加: {}", path.display(), e))?
+        } else {
+            Vec::new()
+        };
+        let mut sink = Self { writer: create_file(path, false)?, count: 0 };
+        sink.write_chunk("[")?;
+        for value in existing {
+            sink.write_value(&value)?;
+        }
+        Ok(sink)
+    }
+
+    fn write_value(&mut self, value: &serde_json::Value) -> Result<()> {
+        let separator = if self.count == 0 { "\n" } else { ",\n" };
+        self.count += 1;
+        self.write_chunk(&format!("{}  {}", separator, value))
+    }
+
+    /// 写入内容和数组结尾，再回到结尾之前，下次写入从旧的结尾处开始并将其完整覆盖
+    fn write_chunk(&mut self, content: &str) -> Result<()> {
+        self.writer.write_all(format!("{}{}", content, JSON_ENDING).as_bytes())
+            .and_then(|_| self.writer.seek(SeekFrom::Current(-(JSON_ENDING.len() as i64))))
+            .map(|_| ())
+            .map_err(|e| anyhow!("写入结果到文件失败: {}", e))
+    }
+}
+
+impl OutputSink for JsonSink {
+    fn write_finding(&mut self, finding: &Finding) -> Result<()> {
+        self.write_value(&finding.to_json())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()
+            .map_err(|e| anyhow!("写入结果到文件失败: {}", e))
+    }
+}
+
+/// CSV输出，首行为表头
+pub(crate) struct CsvSink {
+    writer: BufWriter<File>,
+    encoding: OutputEncoding,
+}
+
+impl CsvSink {
+    /// 追加模式下不再写入BOM和表头
+    pub(crate) fn create(path: &Path, encoding: OutputEncoding, append: bool) -> Result<Self> {
+        let mut writer = BufWriter::new(create_file(path, append)?);
+        if append {
+            return Ok(Self { writer, encoding });
+        }
+        writer.write_all(encoding.bom())
+            .map_err(|e| anyhow!("写入结果到文件失败: {}", e))?;
+        write_line(&mut writer, encoding, "url,status,size,content_type,redirect,final_url,outlier,sensitive,listing,protected,declared_length,etag,last_modified,word,severity,truncated,skipped_body,timestamp,technologies,title")?;
+        Ok(Self { writer, encoding })
+    }
+}
+
+impl OutputSink for CsvSink {
+    fn write_finding(&mut self, finding: &Finding) -> Result<()> {
+        let fields = [
+            csv_field(finding.url.as_str()),
+            finding.status.as_u16().to_string(),
+            finding.size.to_string(),
+            csv_field(finding.content_type.as_deref().unwrap_or_default()),
+            csv_field(finding.redirect.as_ref().map_or("", |url| url.as_str())),
+            csv_field(finding.final_url.as_ref().map_or("", |url| url.as_str())),
+            finding.outlier.to_string(),
+            finding.sensitive.to_string(),
+            finding.listing.to_string(),
+            finding.protected.to_string(),
+            finding.length_mismatch.map_or(String::new(), |(declared, _)| declared.to_string()),
+            csv_field(finding.etag.as_deref().unwrap_or_default()),
+            csv_field(finding.last_modified.as_deref().unwrap_or_default()),
+            csv_field(finding.word.as_deref().unwrap_or_default()),
+            finding.severity.to_string(),
+            finding.truncated.to_string(),
+            finding.skipped_body.to_string(),
+            finding.timestamp_iso(),
+            csv_field(&finding.technologies.join("; ")),
+            csv_field(finding.title.as_deref().unwrap_or_default()),
+        ];
+        write_line(&mut self.writer, self.encoding, &fields.join(","))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()
+            .map_err(|e| anyhow!("写入结果到文件失败: {}", e))
+    }
+}
+
+/// 包含逗号、引号或换行的字段用双引号包裹，内部引号加倍
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 在终端打印一条发现，被过滤的结果显示为紫色，敏感文件和异常值高亮显示，
+/// 未过滤的发现前加上按严重性着色的标签；指定了输出模板时按模板生成整行，颜色和过滤原因不变
+pub(crate) fn print_finding(finding: &Finding, template: Option<&OutputTemplate>) {
+    if let Some(template) = template {
+        console::print_line(colorize(finding, template_line(finding, template)));
+        return;
+    }
+    let prefix = if finding.sensitive {
+        "[!] 敏感"
+    } else if finding.listing {
+        "[DIR] 目录列表"
+    } else if finding.outlier {
+        "[*] 异常"
+    } else if finding.protected {
+        "[#] 受保护"
+    } else {
+        "[+] 发现"
+    };
+    let mut message = format!("{}: {} (状态码: {}, 大小: {} 字节)",
+        prefix, finding.url.as_str(), finding.status, finding.size);
+    if let Some(ref location) = finding.redirect {
+        message.push_str(&format!(" -> {}", location));
+    }
+    if let Some(ref final_url) = finding.final_url {
+        message.push_str(&format!(" => {}", final_url));
+    }
+    if let Some((declared, actual)) = finding.length_mismatch {
+        message.push_str(&format!(" [长度不符: 声明 {} 实际 {} 字节]", declared, actual));
+    }
+    if finding.truncated {
+        message.push_str(" [已截断]");
+    }
+    if finding.skipped_body {
+        message.push_str(" [大文件: 未读取内容]");
+    }
+    if let Some(ref word) = finding.word {
+        message.push_str(&format!(" [WORD: {}]", word));
+    }
+    if let Some(ref title) = finding.title {
+        message.push_str(&format!(" [标题: {}]", title));
+    }
+    if let Some(ref reason) = finding.reason {
+        message.push_str(&format!(" [过滤: {}]", reason));
+    }
+
+    let message = colorize(finding, message);
+    match finding.severity.label().filter(|_| !finding.filtered) {
+        Some(label) => console::print_line(format!("{} {}", label, message)),
+        None => console::print_line(message),
+    }
+}
+
+/// 按模板生成的终端输出行，被过滤的结果仍附上过滤原因，以便在不着色的终端中与真实发现区分
+fn template_line(finding: &Finding, template: &OutputTemplate) -> String {
+    let mut line = template.render(finding);
+    if let Some(ref reason) = finding.reason {
+        line.push_str(&format!(" [过滤: {}]", reason));
+    }
+    line
+}
+
+/// 按发现的类型给终端输出着色
+fn colorize(finding: &Finding, message: String) -> ColoredString {
+    if finding.sensitive && !finding.filtered {
+        message.white().on_red().bold()
+    } else if finding.listing && !finding.filtered {
+        message.bright_cyan().bold()
+    } else if finding.outlier && !finding.filtered {
+        message.bright_yellow().bold()
+    } else if finding.protected && !finding.filtered {
+        message.cyan()
+    } else if finding.filtered {
+        message.purple()
+    } else {
+        message.red()
+    }
+}
+
+/// 折叠模式的终端输出：被过滤的结果不逐条打印，而是按响应大小计数并在同一行原地更新
+#[derive(Default)]
+pub(crate) struct CollapsedPrinter {
+    state: Mutex<CollapsedState>,
+}
+
+#[derive(Default)]
+struct CollapsedState {
+    filtered_sizes: HashMap<usize, usize>,
+    // 当前行是否为尚未换行的计数行
+    status_line: bool,
+}
+
+impl CollapsedPrinter {
+    pub(crate) fn print(&self, finding: &Finding, template: Option<&OutputTemplate>) {
+        let mut state = self.state.lock().unwrap();
+        if finding.filtered {
+            let count = state.filtered_sizes.entry(finding.size).or_insert(0);
+            *count += 1;
+            let line = format!("(已过滤 {} 个大小为 {} 字节的响应)", count, finding.size);
+            console::print_text(format!("\r\x1b[2K{}", line.purple()));
+            state.status_line = true;
+        } else {
+            // 先清掉计数行，真实发现完整打印在单独一行
+            if state.status_line {
+                console::print_text("\r\x1b[2K".to_string());
+                state.status_line = false;
+            }
+            print_finding(finding, template);
+        }
+    }
+
+    /// 扫描结束时结束计数行，并汇总各响应大小被过滤的数量
+    pub(crate) fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.status_line {
+            console::print_text("\r\x1b[2K".to_string());
+            state.status_line = false;
+        }
+        let mut sizes: Vec<(usize, usize)> = state.filtered_sizes.iter().map(|(size, count)| (*size, *count)).collect();
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (size, count) in sizes {
+            console::print_line(format!("(已过滤 {} 个大小为 {} 字节的响应)", count, size).purple());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finding::FilterReason;
+
+    #[test]
+    fn keeps_json_output_valid_after_each_finding() {
+        let path = std::env::temp_dir().join(format!("rdirscan-json-sink-{}.json", std::process::id()));
+        let read = || serde_json::from_str::<Vec<serde_json::Value>>(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut sink = JsonSink::create(&path, false).unwrap();
+        assert!(read().is_empty());
+        // 没有调用 finish 时文件也是完整的数组
+        sink.write_finding(&Finding::sample("http://example.com/admin", 200, 10)).unwrap();
+        assert_eq!(read().len(), 1);
+        sink.write_finding(&Finding::sample("http://example.com/.git/config", 200, 20)).unwrap();
+        sink.finish().unwrap();
+        let findings = read();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[1]["url"], "http://example.com/.git/config");
+
+        // 追加时保留原有元素
+        let mut sink = JsonSink::create(&path, true).unwrap();
+        sink.write_finding(&Finding::sample("http://example.com/backup.zip", 200, 30)).unwrap();
+        assert_eq!(read().len(), 3);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn marks_filtered_findings_in_template_lines() {
+        let template: OutputTemplate = "{status}\\t{url}".parse().unwrap();
+        let mut finding = Finding::sample("http://example.com/admin", 200, 10);
+        assert_eq!(template_line(&finding, &template), "200\thttp://example.com/admin");
+        finding.filtered = true;
+        finding.reason = Some(FilterReason::RepeatedSize);
+        assert_eq!(
+            template_line(&finding, &template),
+            format!("200\thttp://example.com/admin [过滤: {}]", FilterReason::RepeatedSize)
+        );
+    }
+}
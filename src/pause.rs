@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// 暂停/继续派发新请求的开关，可以在其他线程中切换
+///
+/// 暂停只影响尚未派发的请求，在途请求会照常完成；继续后扫描从原来的位置接着进行。
+/// 停止后不再派发任何请求，在途请求完成后扫描正常结束
+#[derive(Clone, Default)]
+pub struct PauseControl {
+    inner: Arc<PauseState>,
+}
+
+#[derive(Default)]
+struct PauseState {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+    resumed: Notify,
+    // `--delay-on-match` 暂缓派发的截止时间，与手动暂停互不影响
+    held_until: Mutex<Option<Instant>>,
+}
+
+impl PauseControl {
+    /// 暂停派发，原本就已暂停时返回 `false`
+    pub fn pause(&self) -> bool {
+        !self.inner.paused.swap(true, Ordering::SeqCst)
+    }
+
+    /// 继续派发，原本未暂停时返回 `false`
+    pub fn resume(&self) -> bool {
+        let changed = self.inner.paused.swap(false, Ordering::SeqCst);
+        self.inner.resumed.notify_waiters();
+        changed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
+    /// 停止派发新请求（如用户在界面中退出），暂停中的扫描也会随之结束
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.resumed.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 在 `duration` 内暂缓派发，到时自动继续；多次调用时以最晚的截止时间为准
+    pub(crate) fn hold(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut held = self.inner.held_until.lock().unwrap();
+        if held.is_none_or(|current| current < until) {
+            *held = Some(until);
+        }
+    }
+
+    /// 暂停或暂缓期间等待，都没有或已停止时立即返回
+    pub(crate) async fn wait(&self) {
+        loop {
+            // 先登记再检查状态，避免错过检查之后、等待之前发生的继续
+            let resumed = self.inner.resumed.notified();
+            tokio::pin!(resumed);
+            resumed.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            if self.is_paused() {
+                resumed.await;
+                continue;
+            }
+            let held = *self.inner.held_until.lock().unwrap();
+            match held {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_pause_state() {
+        let pause = PauseControl::default();
+        assert!(!pause.is_paused());
+        assert!(pause.pause());
+        // 重复暂停或继续时返回 false，调用者据此只提示一次
+        assert!(!pause.pause());
+        assert!(pause.is_paused());
+        assert!(pause.resume());
+        assert!(!pause.resume());
+        assert!(!pause.is_paused());
+    }
+
+    #[tokio::test]
+    async fn waits_until_resumed() {
+        let pause = PauseControl::default();
+        tokio::time::timeout(Duration::from_millis(50), pause.wait()).await.unwrap();
+        pause.pause();
+        let waiter = tokio::spawn({
+            let pause = pause.clone();
+            async move { pause.wait().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        pause.resume();
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn holds_until_the_latest_deadline() {
+        let pause = PauseControl::default();
+        pause.hold(Duration::from_secs(2));
+        pause.hold(Duration::from_secs(1));
+        let started = Instant::now();
+        pause.wait().await;
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        // 暂缓结束后不再等待
+        let started = Instant::now();
+        pause.wait().await;
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn cancel_releases_paused_waiters() {
+        let pause = PauseControl::default();
+        pause.pause();
+        let waiter = tokio::spawn({
+            let pause = pause.clone();
+            async move { pause.wait().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        pause.cancel();
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(pause.is_cancelled());
+    }
+}
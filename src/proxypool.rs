@@ -0,0 +1,223 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::future;
+use reqwest::Client;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use url::Url;
+
+/// 连续这么多个请求因连接失败或超时出错，就把代理标记为不可用，等健康检查恢复
+const MAX_FAILURES: usize = 3;
+
+/// `--proxy-file`：多个代理轮流分担请求，并跟踪每个代理的健康状态
+///
+/// 请求只从当前可用的代理中轮流选择；连续出错的代理被移出轮换，
+/// 定期的健康检查通过每个代理请求检查地址，恢复能正常响应的代理、移除无法连接的代理。
+/// 所有代理都不可用时请求暂停，等到有代理恢复再继续
+pub(crate) struct ProxyPool {
+    proxies: Vec<PooledProxy>,
+    next: AtomicUsize,
+    // 有代理恢复时唤醒等待中的请求
+    recovered: Notify,
+    // 已提示过所有代理都不可用，恢复后清除
+    stalled: AtomicBool,
+}
+
+/// 代理池中的一个代理及其专用客户端
+pub(crate) struct PooledProxy {
+    pub(crate) url: String,
+    pub(crate) client: Client,
+    healthy: AtomicBool,
+    // 连续出错的请求数，成功一次即清零
+    failures: AtomicUsize,
+}
+
+impl ProxyPool {
+    pub(crate) fn new(urls: &[String], build: impl Fn(&str) -> Result<Client>) -> Result<Self> {
+        let proxies = urls
+            .iter()
+            .map(|url| {
+                Ok(PooledProxy {
+                    url: url.clone(),
+                    client: build(url)?,
+                    healthy: AtomicBool::new(true),
+                    failures: AtomicUsize::new(0),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            proxies,
+            next: AtomicUsize::new(0),
+            recovered: Notify::new(),
+            stalled: AtomicBool::new(false),
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.proxies.len()
+    }
+
+    pub(crate) fn healthy(&self) -> usize {
+        self.proxies.iter().filter(|proxy| proxy.healthy.load(Ordering::SeqCst)).count()
+    }
+
+    /// 轮流选择一个可用的代理，全部不可用时返回 `None`
+    pub(crate) fn select(&self) -> Option<&PooledProxy> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..self.proxies.len())
+            .map(|i| &self.proxies[(start + i) % self.proxies.len()])
+            .find(|proxy| proxy.healthy.load(Ordering::SeqCst))
+    }
+
+    /// 轮流选择一个可用的代理，全部不可用时等待健康检查恢复其中之一
+    pub(crate) async fn acquire(&self) -> &PooledProxy {
+        loop {
+            // 先登记再检查，检查之后才恢复的代理同样会唤醒这次等待
+            let recovered = self.recovered.notified();
+            if let Some(proxy) = self.select() {
+                return proxy;
+            }
+            if !self.stalled.swap(true, Ordering::SeqCst) {
+                warn!(total = self.len(), "代理池中的代理都不可用，暂停请求，等待代理恢复");
+            }
+            recovered.await;
+        }
+    }
+
+    /// 经该代理的请求收到了响应
+    pub(crate) fn record_success(&self, proxy: &PooledProxy) {
+        proxy.failures.store(0, Ordering::SeqCst);
+    }
+
+    /// 经该代理的请求连接失败或超时，连续出错达到上限时移出轮换
+    pub(crate) fn record_failure(&self, proxy: &PooledProxy) {
+        if proxy.failures.fetch_add(1, Ordering::SeqCst) + 1 >= MAX_FAILURES && proxy.healthy.swap(false, Ordering::SeqCst) {
+            warn!(proxy = %proxy.url, healthy = self.healthy(), total = self.len(), "代理连续出错，已暂停使用");
+        }
+    }
+
+    /// 同时通过每个代理请求一次检查地址，收到任何HTTP响应即视为可用；返回可用的代理数
+    pub(crate) async fn check(&self, url: &Url, timeout: Duration) -> usize {
+        let probes = self.proxies.iter().map(|proxy| async move {
+            let result = proxy.client.get(url.as_str()).timeout(timeout).send().await;
+            (proxy, result)
+        });
+        for (proxy, result) in future::join_all(probes).await {
+            match result {
+                Ok(_) => self.reinstate(proxy),
+                Err(e) => {
+                    if proxy.healthy.swap(false, Ordering::SeqCst) {
+                        warn!(proxy = %proxy.url, error = %e, "代理健康检查失败，已暂停使用");
+                    }
+                }
+            }
+        }
+        self.healthy()
+    }
+
+    /// 重新启用所有暂停的代理，用于没有检查地址时定期再试一次
+    fn reinstate_all(&self) {
+        for proxy in &self.proxies {
+            self.reinstate(proxy);
+        }
+    }
+
+    fn reinstate(&self, proxy: &PooledProxy) {
+        proxy.failures.store(0, Ordering::SeqCst);
+        if !proxy.healthy.swap(true, Ordering::SeqCst) {
+            info!(proxy = %proxy.url, "代理已恢复");
+            self.stalled.store(false, Ordering::SeqCst);
+            self.recovered.notify_waiters();
+        }
+    }
+
+    /// 在后台每隔 `interval` 检查一次所有代理，返回的句柄被丢弃时停止检查
+    ///
+    /// 没有检查地址时无法主动检查，改为每次都重新启用暂停的代理，仍然出错的会再次被暂停
+    pub(crate) fn spawn_checks(self: &Arc<Self>, url: Option<Url>, interval: Duration, timeout: Duration) -> HealthChecker {
+        let pool = Arc::clone(self);
+        HealthChecker(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match url {
+                    Some(ref url) => {
+                        pool.check(url, timeout).await;
+                    }
+                    None => pool.reinstate_all(),
+                }
+            }
+        }))
+    }
+}
+
+/// 后台健康检查任务，扫描结束（包括出错返回）时随之停止
+pub(crate) struct HealthChecker(JoinHandle<()>);
+
+impl Drop for HealthChecker {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(count: usize) -> ProxyPool {
+        let urls: Vec<String> = (1..=count).map(|i| format!("http://10.0.0.{}:8080", i)).collect();
+        ProxyPool::new(&urls, |_| Ok(Client::new())).unwrap()
+    }
+
+    #[test]
+    fn skips_proxies_after_repeated_failures() {
+        let pool = pool(3);
+        let picked: Vec<_> = (0..3).map(|_| pool.select().unwrap().url.clone()).collect();
+        assert_eq!(picked, ["http://10.0.0.1:8080", "http://10.0.0.2:8080", "http://10.0.0.3:8080"]);
+
+        let first = &pool.proxies[0];
+        pool.record_failure(first);
+        pool.record_failure(first);
+        // 中间成功一次即重新计数
+        pool.record_success(first);
+        pool.record_failure(first);
+        pool.record_failure(first);
+        assert_eq!(pool.healthy(), 3);
+        pool.record_failure(first);
+        assert_eq!(pool.healthy(), 2);
+        assert!((0..6).all(|_| pool.select().unwrap().url != first.url));
+
+        for proxy in &pool.proxies[1..] {
+            for _ in 0..MAX_FAILURES {
+                pool.record_failure(proxy);
+            }
+        }
+        assert_eq!(pool.healthy(), 0);
+        assert!(pool.select().is_none());
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_proxy_to_recover() {
+        let pool = Arc::new(pool(2));
+        for proxy in &pool.proxies {
+            for _ in 0..MAX_FAILURES {
+                pool.record_failure(proxy);
+            }
+        }
+        let waiting = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.acquire().await.url.clone() }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        pool.reinstate(&pool.proxies[1]);
+        let url = tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        assert_eq!(url, "http://10.0.0.2:8080");
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// 剩余额度不超过该值时开始把剩余请求均匀分布到重置时间之前
+const LOW_REMAINING: u64 = 10;
+/// 大于该值的重置时间视为Unix时间戳而不是秒数（部分服务器的 `X-RateLimit-Reset`）
+const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+/// 服务器要求等待的最长时间，超过时视为无效的限流头并忽略，避免一个错误的值让扫描停顿很久
+const MAX_RESET: Duration = Duration::from_secs(3600);
+
+/// 按服务器通告的 `RateLimit-Remaining`/`RateLimit-Reset`（或 `X-` 前缀的同名头）调整请求节奏
+///
+/// 额度充足时不做任何等待；额度较低时把剩余请求均匀分布到重置时间之前，额度用尽时暂停到重置时间。
+/// 429和503响应带有 `Retry-After` 时视为额度用尽，暂停到指定的时间
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+    state: Mutex<Option<Budget>>,
+}
+
+struct Budget {
+    remaining: u64,
+    reset_at: Instant,
+    // 额度较低时下一个请求最早的发送时间
+    next_slot: Instant,
+    // 已提示过本次暂停，避免并发请求重复打印
+    announced: bool,
+}
+
+impl RateLimiter {
+    /// 发送请求前调用，需要时等待
+    pub(crate) async fn wait(&self, host: &str) {
+        let delay = {
+            let mut state = self.state.lock().unwrap();
+            let Some(budget) = state.as_mut() else {
+                return;
+            };
+            let now = Instant::now();
+            if now >= budget.reset_at {
+                *state = None;
+                return;
+            }
+            if budget.remaining == 0 {
+                if !budget.announced {
+                    budget.announced = true;
+                    let seconds = (budget.reset_at - now).as_secs_f64();
+                    warn!(host = %host, seconds = format!("{:.1}", seconds), "请求额度已用尽，暂停等待额度重置");
+                }
+                budget.reset_at - now
+            } else if budget.remaining <= LOW_REMAINING {
+                let interval = (budget.reset_at - now) / budget.remaining as u32;
+                let slot = budget.next_slot.max(now);
+                budget.next_slot = slot + interval;
+                budget.remaining -= 1;
+                slot - now
+            } else {
+                budget.remaining -= 1;
+                Duration::ZERO
+            }
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// 收到响应后用其中的限流头更新额度，没有这些头时保持不变
+    pub(crate) fn update(&self, status: StatusCode, headers: &HeaderMap) {
+        let retry_after = matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE)
+            .then(|| headers.get(RETRY_AFTER))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, SystemTime::now()));
+        if let Some(delay) = retry_after {
+            self.exhaust(delay);
+            return;
+        }
+        let value = |names: [&str; 2]| {
+            names.iter()
+                .filter_map(|name| headers.get(*name))
+                .filter_map(|value| value.to_str().ok())
+                .find_map(|value| value.trim().parse::<u64>().ok())
+        };
+        let Some(remaining) = value(["ratelimit-remaining", "x-ratelimit-remaining"]) else {
+            return;
+        };
+        let Some(reset) = value(["ratelimit-reset", "x-ratelimit-reset"]) else {
+            return;
+        };
+        let reset = if reset > EPOCH_THRESHOLD {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            reset.saturating_sub(now)
+        } else {
+            reset
+        };
+        self.set(remaining, Duration::from_secs(reset));
+    }
+
+    /// 额度已用尽，`delay` 之后重置
+    fn exhaust(&self, delay: Duration) {
+        self.set(0, delay);
+    }
+
+    fn set(&self, remaining: u64, reset: Duration) {
+        let now = Instant::now();
+        let reset_at = match now.checked_add(reset) {
+            Some(reset_at) if reset <= MAX_RESET => reset_at,
+            _ => {
+                warn!(seconds = reset.as_secs(), "服务器要求的等待时间超过1小时，忽略该限流头");
+                return;
+            }
+        };
+        let mut state = self.state.lock().unwrap();
+        match state.as_mut() {
+            // 并发请求的响应可能乱序到达，同一窗口内只接受更小的剩余额度
+            Some(budget) if budget.reset_at > now => {
+                if remaining < budget.remaining {
+                    budget.remaining = remaining;
+                    budget.reset_at = reset_at;
+                }
+            }
+            _ => {
+                *state = Some(Budget {
+                    remaining,
+                    reset_at,
+                    next_slot: now,
+                    announced: false,
+                });
+            }
+        }
+    }
+}
+
+/// 解析 `Retry-After`：秒数或HTTP日期，日期已过时为0
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(now).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    /// 等待一次，返回等待的时长
+    async fn waited(limiter: &RateLimiter) -> Duration {
+        let started = Instant::now();
+        limiter.wait("example.com").await;
+        started.elapsed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_wait_without_headers_or_with_plenty_left() {
+        let limiter = RateLimiter::default();
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+        limiter.update(StatusCode::OK, &headers(&[("ratelimit-remaining", "100"), ("ratelimit-reset", "60")]));
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spreads_low_remaining_budget_until_reset() {
+        let limiter = RateLimiter::default();
+        limiter.update(StatusCode::OK, &headers(&[("x-ratelimit-remaining", "4"), ("x-ratelimit-reset", "4")]));
+        // 第一个请求立即发送，之后每个请求间隔约1秒
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+        let second = waited(&limiter).await;
+        assert!(second >= Duration::from_millis(900) && second <= Duration::from_millis(1100), "{:?}", second);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pauses_until_reset_when_exhausted() {
+        let limiter = RateLimiter::default();
+        limiter.update(StatusCode::OK, &headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", "3")]));
+        assert_eq!(waited(&limiter).await, Duration::from_secs(3));
+        // 重置之后不再等待
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keeps_the_smallest_remaining_within_a_window() {
+        let limiter = RateLimiter::default();
+        limiter.update(StatusCode::OK, &headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", "2")]));
+        // 乱序到达的较早响应不会恢复额度
+        limiter.update(StatusCode::OK, &headers(&[("ratelimit-remaining", "50"), ("ratelimit-reset", "2")]));
+        assert_eq!(waited(&limiter).await, Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn honours_retry_after_on_throttled_responses() {
+        let limiter = RateLimiter::default();
+        limiter.update(StatusCode::OK, &headers(&[("retry-after", "5")]));
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+        limiter.update(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "5")]));
+        assert_eq!(waited(&limiter).await, Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignores_waits_longer_than_an_hour() {
+        let limiter = RateLimiter::default();
+        limiter.update(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "18446744073709551615")]));
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+        limiter.update(StatusCode::OK, &headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", "3601")]));
+        assert_eq!(waited(&limiter).await, Duration::ZERO);
+        // 恰好1小时仍然等待
+        limiter.update(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "3600")]));
+        assert_eq!(waited(&limiter).await, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn parses_retry_after() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(parse_retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        // 已经过去的时间不再等待
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-1", now), None);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+/// 递归扫描的待请求路径：(字典序号, 路径, 深度)
+pub(crate) type QueuedPath = (usize, String, usize);
+
+/// 一个目标的递归队列，发现的子目录与递归字典组合成新路径，排在字典路径之后派发
+///
+/// 字典路径派发完后，队列在还有请求未完成时等待它们可能发现的子目录；
+/// 队列为空且没有在途请求时该目标的扫描结束
+pub(crate) struct RecursionQueue {
+    pending: Mutex<VecDeque<QueuedPath>>,
+    // 已经展开过的目录，重定向到带斜杠地址等情况下同一目录只递归一次
+    expanded: Mutex<HashSet<String>>,
+    in_flight: AtomicUsize,
+    // 递归路径的序号接在字典之后，按字典顺序输出时排在最后
+    next_index: AtomicUsize,
+    notify: Notify,
+}
+
+impl RecursionQueue {
+    pub(crate) fn new(first_index: usize) -> Self {
+        Self {
+            pending: Mutex::new(VecDeque::new()),
+            expanded: Mutex::new(HashSet::new()),
+            in_flight: AtomicUsize::new(0),
+            next_index: AtomicUsize::new(first_index),
+            notify: Notify::new(),
+        }
+    }
+
+    /// 把目录 `dir` 与递归字典组合后加入队列，目录已展开过时返回 `false`
+    pub(crate) fn expand(&self, dir: &str, words: &[String], depth: usize) -> bool {
+        let dir = dir.trim_end_matches('/');
+        if !self.expanded.lock().unwrap().insert(dir.to_string()) {
+            return false;
+        }
+        let mut pending = self.pending.lock().unwrap();
+        for word in words {
+            let index = self.next_index.fetch_add(1, Ordering::SeqCst);
+            pending.push_back((index, child_path(dir, word), depth));
+        }
+        true
+    }
+
+    /// 取出下一个递归路径；队列为空时等待在途请求完成，全部完成后返回 `None`
+    pub(crate) async fn next(&self) -> Option<QueuedPath> {
+        loop {
+            if let Some(path) = self.pending.lock().unwrap().pop_front() {
+                return Some(path);
+            }
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return None;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// 在派发时登记一个请求，返回的守卫在请求结束（包括提前返回）时注销
+    pub(crate) fn start(self: &Arc<Self>) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlight(Arc::clone(self))
+    }
+}
+
+pub(crate) struct InFlight(Arc<RecursionQueue>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.0.notify.notify_one();
+    }
+}
+
+/// 子目录下的路径，字典项开头的斜杠会被去掉以保持相对于目标的拼接
+fn child_path(dir: &str, word: &str) -> String {
+    format!("{}/{}", dir, word.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn queues_children_after_dictionary_with_depth() {
+        let queue = RecursionQueue::new(10);
+        assert!(queue.expand("/admin/", &words(&["a", "/b"]), 1));
+        assert!(queue.expand("/admin/a", &words(&["c"]), 2));
+        assert_eq!(queue.next().await, Some((10, "/admin/a".to_string(), 1)));
+        assert_eq!(queue.next().await, Some((11, "/admin/b".to_string(), 1)));
+        assert_eq!(queue.next().await, Some((12, "/admin/a/c".to_string(), 2)));
+        assert_eq!(queue.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn expands_each_directory_once() {
+        let queue = RecursionQueue::new(0);
+        assert!(queue.expand("/admin", &words(&["a"]), 1));
+        // 带不带结尾斜杠都是同一目录
+        assert!(!queue.expand("/admin/", &words(&["a"]), 1));
+        assert!(!queue.expand("/admin", &words(&["b"]), 2));
+        assert_eq!(queue.next().await, Some((0, "/admin/a".to_string(), 1)));
+        assert_eq!(queue.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn waits_for_requests_in_flight() {
+        let queue = Arc::new(RecursionQueue::new(0));
+        let request = queue.start();
+        // 还有在途请求时不会结束
+        assert!(timeout(Duration::from_millis(50), queue.next()).await.is_err());
+
+        let waiter = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.next().await }
+        });
+        queue.expand("/admin", &words(&["a"]), 1);
+        drop(request);
+        let next = timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert_eq!(next, Some((0, "/admin/a".to_string(), 1)));
+    }
+
+    #[tokio::test]
+    async fn finishes_when_workers_drain() {
+        let queue = Arc::new(RecursionQueue::new(0));
+        let requests = [queue.start(), queue.start()];
+        let waiter = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.next().await }
+        });
+        let [first, second] = requests;
+        drop(first);
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        drop(second);
+        let next = timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert_eq!(next, None);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::cluster::ResponseCluster;
+use crate::finding::{FilterReason, Finding};
+use crate::methods::MethodDiscrepancy;
+
+/// 一次扫描的结果汇总
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    /// 所有被报告的发现（包括被过滤的）
+    pub findings: Vec<Finding>,
+    /// 实际发出的请求数
+    pub requests: usize,
+    /// 请求出错的路径数
+    pub errors: usize,
+    /// 出错后放弃的路径及最后一次的错误
+    pub failed: Vec<PathError>,
+    /// 提前停止的原因，正常跑完字典时为 `None`
+    pub stop_reason: Option<StopReason>,
+    /// 自动并发模式下最终稳定的并发数
+    pub concurrency: Option<usize>,
+    /// 所有响应按相似度分成的类别，响应数多的在前
+    pub clusters: Vec<ResponseCluster>,
+    /// `--compare-methods` 发现的状态码因请求方法而不同的路径
+    pub method_discrepancies: Vec<MethodDiscrepancy>,
+    /// 拼接后的URL超过 `--max-url-length` 而没有发送的请求数
+    pub skipped_long_urls: usize,
+    /// 被 `--match-path`/`--filter-path` 排除而没有发送的请求数
+    pub skipped_paths: usize,
+    /// 因 `--cap-per-code` 没有写入输出文件的有效发现数，按状态码统计
+    pub capped: BTreeMap<u16, usize>,
+    /// 重试后仍未能发送到 `--webhook` 的发现数
+    pub webhook_undelivered: usize,
+}
+
+impl ScanReport {
+    /// 未被过滤的发现数，不含401/403的受保护资源
+    pub fn found(&self) -> usize {
+        self.findings.iter().filter(|f| !f.filtered && !f.protected).count()
+    }
+
+    /// 未被过滤的受保护资源（401/403）数
+    pub fn protected(&self) -> usize {
+        self.findings.iter().filter(|f| f.protected && !f.filtered).count()
+    }
+
+    /// 未被过滤的目录列表页面数
+    pub fn listings(&self) -> usize {
+        self.findings.iter().filter(|f| f.listing && !f.filtered).count()
+    }
+
+    /// `Content-Length` 与实际长度不符的响应数（包括被过滤的）
+    pub fn length_mismatches(&self) -> usize {
+        self.findings.iter().filter(|f| f.length_mismatch.is_some()).count()
+    }
+
+    /// 超过 `--skip-large` 而没有读取响应体的发现数（包括被过滤的）
+    pub fn skipped_bodies(&self) -> usize {
+        self.findings.iter().filter(|f| f.skipped_body).count()
+    }
+
+    /// 按端口统计未被过滤的发现数（不含受保护资源）
+    pub fn found_by_port(&self) -> BTreeMap<u16, usize> {
+        let mut counts = BTreeMap::new();
+        for finding in self.findings.iter().filter(|f| !f.filtered && !f.protected) {
+            if let Some(port) = finding.port() {
+                *counts.entry(port).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// 各项服务端技术出现在多少条发现（包括被过滤的）的响应头中
+    pub fn technologies(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for technology in self.findings.iter().flat_map(|f| &f.technologies) {
+            *counts.entry(technology.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// 按状态码统计未被过滤的发现数（包括没有写入输出文件的）
+    pub fn found_by_status(&self) -> BTreeMap<u16, usize> {
+        let mut counts = BTreeMap::new();
+        for finding in self.findings.iter().filter(|f| !f.filtered) {
+            *counts.entry(finding.status.as_u16()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// 各响应大小出现的次数，按次数从多到少排列（次数相同时按大小）
+    ///
+    /// 统计所有发现（包括被过滤的），即参与重复大小判断的那些响应；
+    /// 种类很少说明目标可能对任意路径返回同一页面（泛解析、WAF拦截等）
+    pub fn size_counts(&self) -> Vec<(usize, usize)> {
+        let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+        for finding in &self.findings {
+            *counts.entry(finding.size).or_insert(0) += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|(a_size, a), (b_size, b)| b.cmp(a).then_with(|| a_size.cmp(b_size)));
+        counts
+    }
+
+    /// 只出现过一次的响应数，通常最值得逐个查看
+    pub fn unique_responses(&self) -> usize {
+        self.clusters.iter().filter(|c| c.count == 1).count()
+    }
+
+    /// 因小于 `--min-content-length` 被过滤的响应数
+    pub fn too_small(&self) -> usize {
+        self.findings.iter().filter(|f| matches!(f.reason, Some(FilterReason::TooSmall(_)))).count()
+    }
+
+    /// 被过滤的发现数
+    pub fn filtered(&self) -> usize {
+        self.findings.iter().filter(|f| f.filtered).count()
+    }
+
+    /// 汇总计数的JSON形式，不包含发现明细
+    pub fn summary_json(&self) -> Value {
+        json!({
+            "requests": self.requests,
+            "found": self.found(),
+            "filtered": self.filtered(),
+            "too_small": self.too_small(),
+            "listings": self.listings(),
+            "protected": self.protected(),
+            "length_mismatches": self.length_mismatches(),
+            "skipped_bodies": self.skipped_bodies(),
+            "errors": self.errors,
+            "skipped_long_urls": self.skipped_long_urls,
+            "skipped_paths": self.skipped_paths,
+            "technologies": self.technologies(),
+            "unique_sizes": self.size_counts().len(),
+            "capped": self.capped,
+            "webhook_undelivered": self.webhook_undelivered,
+            "clusters": self.clusters.iter().map(ResponseCluster::to_json).collect::<Vec<_>>(),
+            "method_discrepancies": self.method_discrepancies.iter().map(MethodDiscrepancy::to_json).collect::<Vec<_>>(),
+            "failed": self.failed.iter().map(PathError::to_json).collect::<Vec<_>>(),
+            "stop_reason": self.stop_reason.as_ref().map(ToString::to_string),
+            "concurrency": self.concurrency,
+        })
+    }
+}
+
+/// 一个出错后放弃的路径，用于区分“扫描过但不存在”和“未能扫描”
+#[derive(Debug, Clone)]
+pub struct PathError {
+    /// 拼接后的URL，拼接失败时为字典项本身
+    pub url: String,
+    /// 字典项
+    pub path: String,
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl PathError {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url,
+            "path": self.path,
+            "kind": self.kind.code(),
+            "message": self.message,
+        })
+    }
+}
+
+/// 路径出错的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 无法建立连接
+    Connect,
+    /// 请求或读取响应超时
+    Timeout,
+    /// 发送请求时的其他网络错误
+    Request,
+    /// 读取响应内容失败
+    Read,
+    /// 路径本身无法请求，如拼接URL失败或超出扫描范围，不会重试
+    Rejected,
+}
+
+impl ErrorKind {
+    /// 稳定的类别标识，供结构化输出使用
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::Connect => "connect",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Request => "request",
+            ErrorKind::Read => "read",
+            ErrorKind::Rejected => "rejected",
+        }
+    }
+}
+
+/// 扫描提前停止的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// 达到 `--max-requests` 上限
+    MaxRequests(usize),
+    /// 达到 `--max-findings` 上限
+    MaxFindings(usize),
+    /// 连续出错达到 `--error-abort` 上限
+    TooManyErrors(usize),
+    /// 用户在界面中退出
+    Cancelled,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::MaxRequests(n) => write!(f, "已达到最大请求数 {}", n),
+            StopReason::MaxFindings(n) => write!(f, "已达到最大发现数 {}", n),
+            StopReason::TooManyErrors(n) => write!(f, "连续 {} 个请求出错，目标可能已宕机或屏蔽了扫描", n),
+            StopReason::Cancelled => write!(f, "已被用户中断"),
+        }
+    }
+}
@@ -0,0 +1,2534 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{anyhow, Result};
+use colored::*;
+use futures::{future, Stream, StreamExt};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, ETAG, HOST,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION, SERVER, USER_AGENT,
+};
+use reqwest::cookie::Jar;
+use reqwest::redirect::Policy;
+use reqwest::{Certificate, Client, Method, NoProxy, Proxy, RequestBuilder, Response, StatusCode};
+use serde_json::json;
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{debug, debug_span, warn, Instrument};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use url::{Host, Url};
+
+use crate::baseline::{random_path, Baseline, CALIBRATION_PROBES, HOMEPAGE_PROBES};
+use crate::cert::{self, CertificateInfo};
+use crate::cluster::ResponseClusters;
+use crate::concurrency::ConcurrencyController;
+use crate::conditional::{load_validators, Validators};
+use crate::config::{host_in_scope, validate_url, ScanConfig, WORD_PLACEHOLDER};
+use crate::console;
+use crate::curl::{curl_command, CurlLog};
+use crate::dict::is_absolute_entry;
+use crate::digest::DigestAuth;
+use crate::dns::CachingResolver;
+use crate::events::EventLog;
+use crate::finding::{FilterReason, Finding};
+use crate::hook::FindingHook;
+use crate::html::{decode_body, extract_title};
+use crate::ippool::{Backend, IpPool};
+use crate::login::{self, LoginOutcome};
+use crate::methods::{MethodDiscrepancy, COMPARED_METHODS};
+use crate::output::{create_sink, print_finding, CollapsedPrinter, OutputSink};
+use crate::pause::PauseControl;
+use crate::proxypool::{PooledProxy, ProxyPool};
+use crate::ratelimit::RateLimiter;
+use crate::recursion::RecursionQueue;
+use crate::report::{ErrorKind, PathError, ScanReport, StopReason};
+use crate::severity::Severity;
+use crate::signing::{RequestSigner, DEFAULT_SIGN_HEADER, DEFAULT_TIMESTAMP_HEADER};
+use crate::sqlite;
+use crate::stats::LiveStats;
+use crate::state::ScanState;
+use crate::trace::{TraceLog, TraceRecord};
+use crate::ua::{get_random_headers, get_random_user_agent};
+use crate::unix::{self, SendError, UnixClient};
+use crate::warmup::{self, WarmupOutcome};
+use crate::webhook::Webhook;
+
+/// 事件流中 `progress` 事件的间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 派发的一个请求：(目标, 字典序号, 路径, 递归深度)
+type Job = (Arc<Target>, usize, String, usize);
+
+/// 参数模式下每个参数使用的值
+const PARAM_FUZZ_VALUE: &str = "test";
+
+/// 目录扫描器，持有HTTP客户端和扫描过程中的过滤状态
+pub struct Scanner {
+    config: ScanConfig,
+    client: Client,
+    // 从过滤规则文件加载的初始状态，每个目标各自复制一份
+    base_state: ScanState,
+    // 跟随跳转时检测到循环的原始URL，由跳转策略写入
+    redirect_loops: Arc<std::sync::Mutex<HashSet<String>>>,
+    trace: Option<TraceLog>,
+    curl: Option<CurlLog>,
+    // 按连接固定UA时的客户端槽位
+    ua_slots: Option<UaSlots>,
+    // 整个扫描使用的固定UA，已设置在共享客户端上
+    static_ua: Option<String>,
+    // 所有客户端共享的DNS缓存
+    resolver: Arc<CachingResolver>,
+    // URL -> 上次扫描记录的缓存校验信息
+    validators: HashMap<String, Validators>,
+    // 表单登录时所有客户端共享的Cookie存储
+    cookies: Option<Arc<Jar>>,
+    // Digest认证，缓存各主机的质询
+    digest: Option<DigestAuth>,
+    // 请求签名，每个请求按当前时间重新计算
+    signer: Option<RequestSigner>,
+    // --max-per-ip 时按后端地址划分的客户端和并发名额
+    ip_pool: Option<IpPool>,
+    // --proxy-file 时轮流使用的代理及其健康状态，与健康检查任务共享
+    proxies: Option<Arc<ProxyPool>>,
+    // UA、随机路径、请求头等随机选择共用的随机数生成器
+    rng: std::sync::Mutex<StdRng>,
+    stats: Arc<LiveStats>,
+    // 所有响应的相似度分类，扫描结束时放入报告
+    clusters: ResponseClusters,
+    // --compare-methods 发现的方法差异，扫描结束时放入报告
+    method_discrepancies: std::sync::Mutex<Vec<MethodDiscrepancy>>,
+    // 通过Unix域套接字扫描时发送请求的客户端
+    unix: Option<UnixClient>,
+    // 本轮扫描中因URL过长而跳过的请求数
+    skipped_long_urls: AtomicUsize,
+    skipped_paths: AtomicUsize,
+    pause: PauseControl,
+}
+
+/// 每个槽位持有独立连接池的客户端和固定的UA，同一时刻只被一个请求使用，
+/// 因此同一条连接上始终发送相同的UA
+struct UaSlots {
+    slots: Vec<(Client, &'static str)>,
+    free: std::sync::Mutex<Vec<usize>>,
+}
+
+impl UaSlots {
+    fn acquire(&self) -> Option<SlotGuard<'_>> {
+        let index = self.free.lock().unwrap().pop()?;
+        Some(SlotGuard { slots: self, index })
+    }
+}
+
+/// 占用中的槽位，释放时归还
+struct SlotGuard<'a> {
+    slots: &'a UaSlots,
+    index: usize,
+}
+
+impl SlotGuard<'_> {
+    fn client(&self) -> (&Client, &'static str) {
+        let (ref client, user_agent) = self.slots.slots[self.index];
+        (client, user_agent)
+    }
+}
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        self.slots.free.lock().unwrap().push(self.index);
+    }
+}
+
+/// 一个请求选用的客户端和UA，UA槽位和后端地址名额一直占用到请求（包括读取响应体）结束
+struct Lease<'a> {
+    shared: &'a Client,
+    slot: Option<SlotGuard<'a>>,
+    backend: Option<Backend>,
+    proxy: Option<&'a PooledProxy>,
+    user_agent: &'a str,
+}
+
+impl Lease<'_> {
+    /// 代理池的客户端优先，其次是选定后端地址的客户端、UA槽位的客户端，都没有时使用共享客户端
+    fn client(&self) -> &Client {
+        if let Some(proxy) = self.proxy {
+            return &proxy.client;
+        }
+        if let Some(ref backend) = self.backend {
+            return &backend.client;
+        }
+        self.slot.as_ref().map_or(self.shared, |slot| slot.client().0)
+    }
+}
+
+/// 一个扫描目标及其独立的过滤状态
+struct Target {
+    base: Url,
+    scan_state: Arc<Mutex<ScanState>>,
+    // 按主机限流时该目标所在主机的信号量
+    host_limit: Option<Arc<Semaphore>>,
+    // 连续网络错误数，收到任何响应时清零
+    consecutive_errors: AtomicUsize,
+    // 连续出错过多而中止，剩余路径不再请求
+    aborted: AtomicBool,
+    // 服务器通告的限流额度
+    rate_limit: RateLimiter,
+    // 目标的先后顺序，按字典顺序输出时使用
+    order: usize,
+    // 递归扫描发现的子目录路径
+    recursion: Arc<RecursionQueue>,
+}
+
+impl Scanner {
+    /// 根据配置创建HTTP客户端并加载过滤规则
+    pub fn new(config: ScanConfig) -> Result<Self> {
+        let mut base_state = match config.filter_file {
+            Some(ref filter_path) => ScanState::from_file(filter_path)?,
+            None => ScanState::new(),
+        };
+        base_state.set_interactive(config.interactive);
+        base_state.set_size_threshold(config.filter_threshold);
+        let redirect_loops = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let mut resolver = CachingResolver::default();
+        // 自定义SNI时请求发往SNI名称，连接仍需指向目标主机
+        if let (Some(sni), Some(base)) = (&config.sni, config.base_url.as_deref().and_then(|url| Url::parse(url).ok())) {
+            if let Some(host) = base.host_str() {
+                resolver = resolver.alias(sni, host.trim_start_matches('[').trim_end_matches(']'));
+            }
+        }
+        let resolver = Arc::new(resolver);
+        // 表单登录时所有客户端共用一个Cookie存储，登录获得的会话对每个请求生效
+        let cookies = config.login_url.is_some().then(|| Arc::new(Jar::default()));
+        let digest = config.digest_auth.as_ref().map(|(username, password)| DigestAuth::new(username, password));
+        let signer = config.sign_secret.as_ref().map(|secret| {
+            RequestSigner::new(
+                secret,
+                config.sign_header.as_deref().unwrap_or(DEFAULT_SIGN_HEADER),
+                config.sign_timestamp_header.as_deref().unwrap_or(DEFAULT_TIMESTAMP_HEADER),
+                config.sign_template.clone().unwrap_or_default(),
+            )
+        });
+        // 指定种子时扫描中的所有随机选择都来自同一个确定的序列，便于复现
+        let mut rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        // 固定UA只在客户端上设置一次，请求中不再单独发送
+        let static_ua = config.user_agent.clone()
+            .or_else(|| config.static_ua.then(|| get_random_user_agent(&mut rng).to_string()));
+        let client_ua = static_ua.as_deref().unwrap_or_else(|| get_random_user_agent(&mut rng));
+        let ca_certs = load_ca_certs(&config.ca_certs)?;
+        // 使用代理池时每个代理一个客户端；共享客户端固定走第一个代理，不会直连目标
+        let proxies = match config.proxy_pool.first() {
+            Some(first) => {
+                let pool = ProxyPool::new(&config.proxy_pool, |proxy| {
+                    let config = ScanConfig { proxy: Some(proxy.to_string()), ..config.clone() };
+                    build_client(&config, &ca_certs, &redirect_loops, &resolver, cookies.as_ref(), client_ua)
+                })?;
+                Some((Arc::new(pool), first.clone()))
+            }
+            None => None,
+        };
+        let client = match proxies {
+            Some((_, ref first)) => {
+                let config = ScanConfig { proxy: Some(first.clone()), ..config.clone() };
+                build_client(&config, &ca_certs, &redirect_loops, &resolver, cookies.as_ref(), client_ua)?
+            }
+            None => build_client(&config, &ca_certs, &redirect_loops, &resolver, cookies.as_ref(), client_ua)?,
+        };
+        let proxies = proxies.map(|(pool, _)| pool);
+        let ip_pool = config.max_per_ip.map(|limit| {
+            let (config, ca_certs, redirect_loops, cookies) = (config.clone(), ca_certs.clone(), Arc::clone(&redirect_loops), cookies.clone());
+            let user_agent = client_ua.to_string();
+            IpPool::new(limit, Arc::clone(&resolver), move |resolver| {
+                build_client(&config, &ca_certs, &redirect_loops, &resolver, cookies.as_ref(), &user_agent)
+            })
+        });
+        let curl = match config.curl_file {
+            Some(ref path) => Some(CurlLog::create(path)?),
+            None => None,
+        };
+        let ua_slots = if config.ua_per_connection {
+            let mut slots = Vec::with_capacity(config.threads);
+            for _ in 0..config.threads {
+                let user_agent = get_random_user_agent(&mut rng);
+                slots.push((build_client(&config, &ca_certs, &redirect_loops, &resolver, cookies.as_ref(), user_agent)?, user_agent));
+            }
+            Some(UaSlots {
+                slots,
+                free: std::sync::Mutex::new((0..config.threads).collect()),
+            })
+        } else {
+            None
+        };
+        let validators = match config.conditional_file {
+            Some(ref path) => load_validators(path)?,
+            None => HashMap::new(),
+        };
+        let trace = match config.trace_file {
+            Some(ref path) => Some(TraceLog::create(path)?),
+            None => None,
+        };
+        let unix = match config.unix_socket {
+            Some(_) => Some(UnixClient::new(&config, client_ua)?),
+            None => None,
+        };
+
+        Ok(Self {
+            config,
+            client,
+            base_state,
+            redirect_loops,
+            trace,
+            curl,
+            ua_slots,
+            static_ua,
+            resolver,
+            validators,
+            cookies,
+            digest,
+            signer,
+            ip_pool,
+            proxies,
+            rng: std::sync::Mutex::new(rng),
+            stats: Arc::default(),
+            pause: PauseControl::default(),
+            clusters: ResponseClusters::default(),
+            method_discrepancies: std::sync::Mutex::default(),
+            unix,
+            skipped_long_urls: AtomicUsize::new(0),
+            skipped_paths: AtomicUsize::new(0),
+        })
+    }
+
+    /// 提交配置中的登录表单，成功后之后的请求都会带上获得的会话Cookie；未配置登录时返回错误
+    pub async fn login(&self) -> Result<LoginOutcome> {
+        let jar = self.cookies.as_ref().ok_or_else(|| anyhow!("未设置登录地址"))?;
+        login::login(self.shared_client(), &self.config, jar).await
+    }
+
+    /// 扫描前请求一次目标URL，确认目标可达、TLS和代理工作正常；失败时的错误说明了出错的环节
+    pub async fn warmup(&self) -> Result<WarmupOutcome> {
+        let base = self.config.base_url.as_deref()
+            .ok_or_else(|| anyhow!("未设置目标URL"))?;
+        let url = Url::parse(base)?;
+        let (request_url, host_header) = self.connect_target(&url);
+        let mut request = self.shared_client().get(request_url.as_str());
+        if let Some(host) = host_header {
+            request = request.header(HOST, host);
+        }
+        if self.static_ua.is_none() {
+            request = request.header(USER_AGENT, get_random_user_agent(&mut *self.rng()));
+        }
+        let signature = self.signer.as_ref().map(|signer| signer.headers(&Method::GET, &url, None)).unwrap_or_default();
+        warmup::warmup(request, self.unix.as_ref(), &self.config, &url, &signature).await
+    }
+
+    /// 与目标完成一次TLS握手，读取证书的主题、备用名称、签发者和有效期
+    ///
+    /// 连接目标主机（或 `--resolve` 固定的地址），SNI与扫描请求相同；经过代理或Unix域套接字时无法直接读取
+    pub async fn certificate(&self) -> Result<CertificateInfo> {
+        let base = self.config.base_url.as_deref()
+            .ok_or_else(|| anyhow!("未设置目标URL"))?;
+        let url = Url::parse(base)?;
+        if url.scheme() != "https" {
+            return Err(anyhow!("目标不是https，没有证书"));
+        }
+        if self.unix.is_some() || self.config.proxied(&url) {
+            return Err(anyhow!("请求经过代理或Unix域套接字，无法直接读取证书"));
+        }
+        let ip = match url.host() {
+            Some(Host::Ipv4(ip)) => ip.into(),
+            Some(Host::Ipv6(ip)) => ip.into(),
+            Some(Host::Domain(host)) => match self.config.resolve.iter().find(|(name, _)| name == host) {
+                Some((_, addr)) => addr.ip(),
+                None => self.resolver.addresses(host).await?
+                    .first()
+                    .ok_or_else(|| anyhow!("解析主机失败: {} 没有可用的地址", host))?
+                    .ip(),
+            },
+            None => return Err(anyhow!("目标URL没有主机")),
+        };
+        let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+        let server_name = self.config.sni.as_deref().unwrap_or(host);
+        let addr = SocketAddr::new(ip, url.port_or_known_default().unwrap_or(443));
+        cert::fetch_certificate(addr, server_name, self.config.connect_timeout).await
+    }
+
+    pub fn config(&self) -> &ScanConfig {
+        &self.config
+    }
+
+    /// 扫描进行中的实时统计，可在另一个任务或线程中定期读取
+    pub fn stats(&self) -> Arc<LiveStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// 暂停/继续派发新请求的开关，可以交给键盘监听等其他线程
+    pub fn pause_control(&self) -> PauseControl {
+        self.pause.clone()
+    }
+
+    /// 去掉与目标URL拼接后得到相同URL的字典项（如根目录下的 `admin` 与 `/admin`），
+    /// 保留首次出现的顺序，返回去重后的列表和合并掉的数量
+    ///
+    /// 没有配置单个目标或请求头/请求体中使用了模板时，只去掉完全相同的字典项
+    pub fn dedupe_paths(&self, paths: Vec<String>) -> (Vec<String>, usize) {
+        let base = self.config.base_url.as_deref()
+            .and_then(|url| Url::parse(url).ok())
+            .filter(|_| !self.config.templated_request());
+        let mut seen = HashSet::new();
+        let total = paths.len();
+        let unique: Vec<String> = paths
+            .into_iter()
+            .filter(|path| {
+                let key = base.as_ref()
+                    .and_then(|base| self.build_url(base, path).ok())
+                    .map_or_else(|| path.clone(), String::from);
+                seen.insert(key)
+            })
+            .collect();
+        let collapsed = total - unique.len();
+        (unique, collapsed)
+    }
+
+    /// 按字典并发扫描配置中的目标URL，返回包含所有发现（包括被过滤的）的汇总
+    pub async fn run(&self, paths: Vec<String>) -> Result<ScanReport> {
+        let base_url = self.config.base_url.clone()
+            .ok_or_else(|| anyhow!("未设置目标URL"))?;
+        let report = self.scan(futures::stream::iter([base_url]), paths, false).await;
+        console::flush();
+        report
+    }
+
+    /// 依次扫描流中到达的每个目标，所有目标共享同一并发上限
+    ///
+    /// 目标URL在到达时校验，无效的目标会被跳过；流结束且在途请求完成后返回
+    pub async fn run_targets<S>(&self, targets: S, paths: Vec<String>) -> Result<ScanReport>
+    where
+        S: Stream<Item = String>,
+    {
+        let report = self.scan(targets, paths, true).await;
+        console::flush();
+        report
+    }
+
+    async fn scan<S>(&self, targets: S, paths: Vec<String>, announce: bool) -> Result<ScanReport>
+    where
+        S: Stream<Item = String>,
+    {
+        // 创建输出文件，未指定任何输出文件时只打印到控制台
+        let mut sinks: Vec<Box<dyn OutputSink>> = Vec::new();
+        for path in &self.config.output_files {
+            sinks.push(create_sink(path, &self.config)?);
+        }
+        if let Some(ref path) = self.config.sqlite {
+            // 打开数据库时可能等待其他任务的写锁，不占用异步工作线程
+            let (path, config) = (path.clone(), self.config.clone());
+            let sink = tokio::task::spawn_blocking(move || sqlite::create_sink(&path, &config)).await
+                .map_err(|e| anyhow!("打开SQLite数据库失败: {}", e))??;
+            sinks.push(sink);
+        }
+        let sinks = (!sinks.is_empty()).then(|| Arc::new(Mutex::new(sinks)));
+
+        let events = match self.config.events_file {
+            Some(ref path) => Some(EventLog::create(path)?),
+            None => None,
+        };
+        let hook = self.config.on_finding.as_deref().map(FindingHook::new);
+        let webhook = match self.config.webhook {
+            Some(ref url) => Some(Webhook::start(url)?),
+            None => None,
+        };
+        // 代理池：扫描开始前检查一次所有代理，之后在后台定期检查
+        let _proxy_checker = match (&self.proxies, self.config.proxy_check_url.as_deref().or(self.config.base_url.as_deref())) {
+            (Some(pool), Some(check_url)) => {
+                let check_url = Url::parse(check_url)?;
+                let healthy = pool.check(&check_url, self.config.timeout).await;
+                if healthy == 0 {
+                    return Err(anyhow!("代理池中的 {} 个代理都无法访问 {}", pool.len(), check_url));
+                }
+                if !self.config.quiet {
+                    console::print_line(format!("代理池: {}/{} 个代理可用", healthy, pool.len()).cyan());
+                }
+                Some(pool.spawn_checks(Some(check_url), self.config.proxy_check_interval, self.config.timeout))
+            }
+            (Some(pool), None) => {
+                warn!("未设置代理检查地址（--proxy-check-url），暂停的代理只会定期重新启用，不会预先检查");
+                Some(pool.spawn_checks(None, self.config.proxy_check_interval, self.config.timeout))
+            }
+            (None, _) => None,
+        };
+        if let Some(ref events) = events {
+            events.emit("start", json!({ "config": self.config.to_json() }));
+        }
+
+        let requests = AtomicUsize::new(0);
+        let found = AtomicUsize::new(0);
+        let errors = AtomicUsize::new(0);
+        let stop_reason: std::sync::Mutex<Option<StopReason>> = std::sync::Mutex::new(None);
+        let failed: std::sync::Mutex<Vec<PathError>> = std::sync::Mutex::new(Vec::new());
+        // 每个状态码已写入输出文件的发现数，以及因 --cap-per-code 未写入的发现数
+        let written: std::sync::Mutex<HashMap<u16, usize>> = std::sync::Mutex::new(HashMap::new());
+        let capped: std::sync::Mutex<BTreeMap<u16, usize>> = std::sync::Mutex::new(BTreeMap::new());
+        // 超过 --cap-per-code 上限的发现不写入输出文件，只计数
+        let within_cap = |finding: &Finding| match self.config.cap_per_code {
+            Some(cap) => {
+                let status = finding.status.as_u16();
+                let mut written = written.lock().unwrap();
+                let count = written.entry(status).or_insert(0);
+                if *count < cap {
+                    *count += 1;
+                    true
+                } else {
+                    *capped.lock().unwrap().entry(status).or_insert(0) += 1;
+                    false
+                }
+            }
+            None => true,
+        };
+        // 按字典顺序输出时先缓存发现及其(目标序号, 字典序号)，扫描结束后排序写入
+        let ordered = (self.config.ordered_output && sinks.is_some())
+            .then(|| std::sync::Mutex::new(Vec::<((usize, usize), Finding)>::new()));
+        let collapsed = self.config.collapse.then(CollapsedPrinter::default);
+        // 自动并发模式下 `threads` 作为上限，实际并发由控制器决定
+        let controller = self.config.auto_concurrency
+            .then(|| ConcurrencyController::new(self.config.threads));
+        self.clusters.reset();
+        self.method_discrepancies.lock().unwrap().clear();
+        self.skipped_long_urls.store(0, Ordering::SeqCst);
+        self.skipped_paths.store(0, Ordering::SeqCst);
+        self.stats.reset(controller.as_ref().map_or(self.config.threads, ConcurrencyController::limit));
+
+        let paths: Arc<[String]> = paths.into();
+        let recursion_paths: Arc<[String]> = if self.config.recursion_paths.is_empty() {
+            Arc::clone(&paths)
+        } else {
+            self.config.recursion_paths.clone().into()
+        };
+        let paths_len = paths.len();
+        let host_limits: std::sync::Mutex<HashMap<String, Arc<Semaphore>>> = std::sync::Mutex::new(HashMap::new());
+        let host_limits = &host_limits;
+        let target_order = AtomicUsize::new(0);
+        let target_order = &target_order;
+        // 按主机限流时不再由 buffer_unordered 统一限制，改用全局信号量限制总并发
+        let global_limit = self.config.per_host_threads.map(|_| Semaphore::new(self.config.threads));
+        let targets = targets
+            .filter_map(|target| async move {
+                let base = match validate_url(&target).and_then(|url| Ok(Url::parse(&url)?)) {
+                    Ok(base) => base,
+                    Err(e) => {
+                        warn!(url = %target, error = %e, "跳过目标");
+                        return None;
+                    }
+                };
+                if !self.config.in_scope(&base) {
+                    warn!(url = %base, "跳过目标: 主机不在扫描范围内");
+                    return None;
+                }
+                if announce {
+                    console::print_line(format!("开始扫描目标: {}", base).cyan());
+                }
+                if let Err(e) = self.pre_resolve(&base).await {
+                    warn!(url = %base, error = %e, "跳过目标");
+                    return None;
+                }
+                // 非交互模式下无法询问，只有指定了 --auto-upgrade 才发送探测请求
+                let upgrade = if self.config.auto_upgrade || self.config.interactive {
+                    self.detect_https_upgrade(&base).await
+                } else {
+                    None
+                };
+                let base = match upgrade {
+                    Some(upgraded) if self.confirm_upgrade(&base, &upgraded) => upgraded,
+                    _ => base,
+                };
+                let mut scan_state = self.base_state.clone();
+                if self.config.smart_match.is_some() {
+                    match self.calibrate(&base).await {
+                        Ok((baseline, homepage)) => {
+                            console::print_line(format!("{} 的404基线: {}", base, baseline).cyan());
+                            if let Some(ref homepage) = homepage {
+                                console::print_line(format!("{} 的首页指纹: {}", base, homepage).cyan());
+                            }
+                            scan_state.set_baselines(baseline, homepage);
+                        }
+                        Err(e) => {
+                            warn!(url = %base, error = %e, "跳过目标");
+                            return None;
+                        }
+                    }
+                }
+                // 同一主机的多个目标共用一个限流信号量
+                let host_limit = self.config.per_host_threads.map(|per_host| {
+                    let host = format!("{}:{}", base.host_str().unwrap_or_default(), base.port_or_known_default().unwrap_or(0));
+                    let mut host_limits = host_limits.lock().unwrap();
+                    Arc::clone(host_limits.entry(host).or_insert_with(|| Arc::new(Semaphore::new(per_host))))
+                });
+                Some(Arc::new(Target {
+                    base,
+                    scan_state: Arc::new(Mutex::new(scan_state)),
+                    host_limit,
+                    consecutive_errors: AtomicUsize::new(0),
+                    aborted: AtomicBool::new(false),
+                    rate_limit: RateLimiter::default(),
+                    order: target_order.fetch_add(1, Ordering::SeqCst),
+                    recursion: Arc::new(RecursionQueue::new(paths_len)),
+                }))
+                .inspect(|_| self.stats.add_total(paths_len))
+            });
+        // 字典路径之后接着派发该目标递归发现的子目录路径；不递归时无需等待在途请求
+        let recursive = self.config.recursion_depth > 0;
+        let expand = move |target: Arc<Target>| {
+            let paths = Arc::clone(&paths);
+            let dictionary = {
+                let target = Arc::clone(&target);
+                futures::stream::iter(0..paths.len())
+                    .map(move |i| (Arc::clone(&target), i, paths[i].clone(), 0))
+            };
+            let subdirectories = futures::stream::unfold(target, move |target| async move {
+                if !recursive {
+                    return None;
+                }
+                let (index, path, depth) = target.recursion.next().await?;
+                Some(((Arc::clone(&target), index, path, depth), target))
+            });
+            dictionary.chain(subdirectories)
+        };
+
+        // 已中止的目标不再派发请求
+        let not_aborted = |(target, _, _, _): &Job| {
+            let aborted = target.aborted.load(Ordering::SeqCst);
+            if aborted && !announce {
+                if let Some(limit) = self.config.error_abort {
+                    *stop_reason.lock().unwrap() = Some(StopReason::TooManyErrors(limit));
+                }
+            }
+            future::ready(!aborted)
+        };
+
+        // 暂停期间不再派发新请求
+        let unpaused = |_: &Job| {
+            let pause = self.pause.clone();
+            async move {
+                pause.wait().await;
+                true
+            }
+        };
+
+        // 派发前检查上限，已派发的请求会继续完成
+        let keep_going = |_: &Job| {
+            let keep_going = 'check: {
+                if self.pause.is_cancelled() {
+                    *stop_reason.lock().unwrap() = Some(StopReason::Cancelled);
+                    break 'check false;
+                }
+                if let Some(max) = self.config.max_requests {
+                    if requests.load(Ordering::SeqCst) >= max {
+                        *stop_reason.lock().unwrap() = Some(StopReason::MaxRequests(max));
+                        break 'check false;
+                    }
+                }
+                if let Some(max) = self.config.max_findings {
+                    if found.load(Ordering::SeqCst) >= max {
+                        *stop_reason.lock().unwrap() = Some(StopReason::MaxFindings(max));
+                        break 'check false;
+                    }
+                }
+                requests.fetch_add(1, Ordering::SeqCst);
+                self.stats.record_request();
+                true
+            };
+            future::ready(keep_going)
+        };
+
+        let run_job = |(target, index, path, depth): Job| {
+            // 派发时立即登记，递归队列据此判断目标是否还有可能发现新的子目录
+            let in_flight = target.recursion.start();
+            let recursion_paths = Arc::clone(&recursion_paths);
+            let sinks = sinks.clone();
+            let found = &found;
+            let errors = &errors;
+            let failed = &failed;
+            let within_cap = &within_cap;
+            let ordered = ordered.as_ref();
+            let collapsed = collapsed.as_ref();
+            let events = events.as_ref();
+            let hook = hook.as_ref();
+            let webhook = webhook.as_ref();
+            let controller = controller.as_ref();
+            let global_limit = global_limit.as_ref();
+            async move {
+                let _in_flight = in_flight;
+                // 按主机限流时先占用主机许可，再占用全局许可
+                let _host_permit = match target.host_limit {
+                    Some(ref limit) => Some(limit.acquire().await.expect("主机信号量不会被关闭")),
+                    None => None,
+                };
+                let _global_permit = match global_limit {
+                    Some(limit) => Some(limit.acquire().await.expect("全局信号量不会被关闭")),
+                    None => None,
+                };
+                let permit = match controller {
+                    Some(controller) => Some(controller.acquire().await),
+                    None => None,
+                };
+                let started = Instant::now();
+                let result = self.check_path(&target, &path).await;
+                self.stats.record_latency(started.elapsed());
+                if let (Some(controller), Some(permit)) = (controller, permit) {
+                    controller.release(permit, started.elapsed(), result.is_err());
+                    self.stats.set_concurrency(controller.limit());
+                }
+                let finding = match result {
+                    Ok(Some(finding)) => finding,
+                    Ok(None) => return None,
+                    Err(e) => {
+                        errors.fetch_add(1, Ordering::SeqCst);
+                        self.stats.record_error();
+                        if !self.config.quiet {
+                            warn!(path = %path, error = %e, "检查路径时出错");
+                        }
+                        // 网络错误之外的失败都是路径本身无法请求（拼接失败、超出范围等）
+                        let kind = e.downcast_ref::<RequestError>().map_or(ErrorKind::Rejected, |error| error.kind);
+                        failed.lock().unwrap().push(PathError {
+                            url: self.build_url(&target.base, &path).map_or_else(|_| path.clone(), String::from),
+                            path: path.clone(),
+                            kind,
+                            message: e.to_string(),
+                        });
+                        return None;
+                    }
+                };
+
+                self.stats.record_finding(&finding);
+                match collapsed {
+                    _ if self.config.quiet => {}
+                    Some(collapsed) => collapsed.print(&finding, self.config.output_template.as_ref()),
+                    None => print_finding(&finding, self.config.output_template.as_ref()),
+                }
+                if let Some(events) = events {
+                    events.emit("finding", json!({ "finding": finding.to_json() }));
+                }
+                // 仅在未过滤的情况下写入输出文件
+                if !finding.filtered {
+                    found.fetch_add(1, Ordering::SeqCst);
+                    if depth < self.config.recursion_depth && finding.is_directory() && !path.contains('?')
+                        && target.recursion.expand(&path, &recursion_paths, depth + 1)
+                    {
+                        self.stats.add_total(recursion_paths.len());
+                        if !self.config.quiet {
+                            console::print_line(format!("递归扫描目录: {}（第 {} 层）", finding.url, depth + 1).cyan());
+                        }
+                    }
+                    if let Some(hook) = hook {
+                        hook.spawn(&finding);
+                    }
+                    if let Some(webhook) = webhook {
+                        webhook.send(&finding);
+                    }
+                    if let Some(ordered) = ordered {
+                        ordered.lock().unwrap().push(((target.order, index), finding.clone()));
+                    } else if let (Some(ref sinks), true) = (&sinks, within_cap(&finding)) {
+                        for sink in sinks.lock().await.iter_mut() {
+                            if let Err(e) = sink.write_finding(&finding) {
+                                warn!("{}", e);
+                            }
+                        }
+                    }
+                    self.react_to_match(&finding);
+                }
+                Some(finding)
+            }
+        };
+
+        let results: Pin<Box<dyn Stream<Item = Option<Finding>> + '_>> = match self.config.per_host_threads {
+            None => Box::pin(
+                targets
+                    .flat_map(expand)
+                    .filter(not_aborted)
+                    .filter(unpaused)
+                    .take_while(keep_going)
+                    .map(run_job)
+                    .buffer_unordered(self.config.threads),
+            ),
+            // 每个目标各自限流，多个目标交替推进，慢目标不会占满所有并发
+            Some(per_host) => {
+                let (expand, not_aborted, unpaused, keep_going, run_job) = (&expand, &not_aborted, &unpaused, &keep_going, &run_job);
+                let cancelled = self.pause.clone();
+                Box::pin(
+                    targets
+                        // 停止后不再读取新的目标
+                        .take_while(move |_| future::ready(!cancelled.is_cancelled()))
+                        .map(move |target| {
+                            Box::pin(
+                                expand(target)
+                                    .filter(not_aborted)
+                                    .filter(unpaused)
+                                    .take_while(keep_going)
+                                    .map(run_job)
+                                    .buffer_unordered(per_host),
+                            )
+                        })
+                        .flatten_unordered(self.config.threads),
+                )
+            }
+        };
+
+        let findings = {
+            let collect = results
+                .filter_map(|finding| async move { finding })
+                .collect::<Vec<Finding>>();
+            tokio::pin!(collect);
+
+            // 扫描进行中周期性地输出进度事件
+            let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+            ticker.tick().await;
+            let mut hangup = Hangup::new();
+            loop {
+                tokio::select! {
+                    findings = &mut collect => break findings,
+                    _ = hangup.recv() => self.reload_filters(),
+                    _ = ticker.tick() => {
+                        if let Some(ref events) = events {
+                            events.emit("progress", json!({
+                                "requests": requests.load(Ordering::SeqCst),
+                                "found": found.load(Ordering::SeqCst),
+                                "errors": errors.load(Ordering::SeqCst),
+                                "concurrency": controller.as_ref().map(ConcurrencyController::limit),
+                            }));
+                        }
+                    }
+                }
+            }
+        };
+
+        if let Some(ref collapsed) = collapsed {
+            collapsed.finish();
+        }
+        if let Some(ref hook) = hook {
+            hook.wait().await;
+        }
+        let webhook_undelivered = match webhook {
+            Some(webhook) => webhook.finish().await,
+            None => 0,
+        };
+        if let Some(ref sinks) = sinks {
+            let mut sinks = sinks.lock().await;
+            if let Some(ordered) = ordered {
+                let mut ordered = ordered.into_inner().unwrap();
+                ordered.sort_by_key(|(order, _)| *order);
+                for (_, finding) in ordered.iter().filter(|(_, finding)| within_cap(finding)) {
+                    for sink in sinks.iter_mut() {
+                        sink.write_finding(finding)?;
+                    }
+                }
+            }
+            for sink in sinks.iter_mut() {
+                sink.finish()?;
+            }
+        }
+        if let Some(ref path) = self.config.save_filters {
+            self.base_state.save_filters(path)?;
+        }
+        let failed = failed.into_inner().unwrap();
+        if let Some(ref path) = self.config.errors_file {
+            let errors: Vec<_> = failed.iter().map(PathError::to_json).collect();
+            let content = serde_json::to_string_pretty(&errors)?;
+            std::fs::write(path, content + "\n")
+                .map_err(|e| anyhow!("写入错误文件 {} 失败: {}", path.display(), e))?;
+        }
+        if let Some(ref trace) = self.trace {
+            trace.flush().await?;
+        }
+        if let Some(ref curl) = self.curl {
+            curl.flush().await?;
+        }
+
+        let report = ScanReport {
+            findings,
+            // 因URL过长跳过的请求已计入派发数，但并未发出
+            requests: requests.into_inner()
+                .saturating_sub(self.skipped_long_urls.load(Ordering::SeqCst))
+                .saturating_sub(self.skipped_paths.load(Ordering::SeqCst)),
+            skipped_long_urls: self.skipped_long_urls.load(Ordering::SeqCst),
+            skipped_paths: self.skipped_paths.load(Ordering::SeqCst),
+            errors: errors.into_inner(),
+            failed,
+            stop_reason: stop_reason.into_inner().unwrap(),
+            concurrency: controller.map(|controller| controller.limit()),
+            clusters: self.clusters.take(),
+            method_discrepancies: std::mem::take(&mut *self.method_discrepancies.lock().unwrap()),
+            capped: capped.into_inner().unwrap(),
+            webhook_undelivered,
+        };
+        if let Some(ref events) = events {
+            events.emit("end", json!({ "summary": report.summary_json() }));
+        }
+        Ok(report)
+    }
+
+    /// 扫描使用的请求方法
+    fn method(&self) -> Method {
+        if self.config.head_only {
+            Method::HEAD
+        } else if self.config.data.is_some() {
+            Method::POST
+        } else {
+            Method::GET
+        }
+    }
+
+    /// 写入一条请求跟踪记录，未启用跟踪时不做任何事
+    async fn record_trace(
+        &self,
+        url: &Url,
+        user_agent: &str,
+        status: Option<u16>,
+        size: Option<usize>,
+        started: Instant,
+        decision: &str,
+    ) {
+        if let Some(ref trace) = self.trace {
+            trace.record(TraceRecord {
+                method: self.method().as_str(),
+                url: url.as_str(),
+                user_agent,
+                status,
+                size,
+                elapsed: started.elapsed(),
+                decision,
+            }).await;
+        }
+    }
+
+    /// 由字典项生成请求URL：默认将路径（可带查询串）拼接到目标上，参数模式下作为参数名追加到目标的查询串
+    fn build_url(&self, base: &Url, entry: &str) -> Result<Url> {
+        build_request_url(&self.config, base, entry)
+    }
+
+    /// 请求实际发往的URL，指定了SNI时同时返回原主机的Host头
+    fn connect_target(&self, url: &Url) -> (Url, Option<String>) {
+        match self.config.sni_url(url) {
+            Some(rewritten) => {
+                let host = url.host_str().unwrap_or_default();
+                let host = match url.port() {
+                    Some(port) => format!("{}:{}", host, port),
+                    None => host.to_string(),
+                };
+                (rewritten, Some(host))
+            }
+            None => (url.clone(), None),
+        }
+    }
+
+    /// 登录、预热、校准等请求使用的客户端；使用代理池时轮流选择一个可用的代理
+    fn shared_client(&self) -> &Client {
+        self.proxies.as_ref().and_then(|pool| pool.select()).map_or(&self.client, |proxy| &proxy.client)
+    }
+
+    /// 扫描共用的随机数生成器，只在同步代码中短暂持有
+    fn rng(&self) -> std::sync::MutexGuard<'_, StdRng> {
+        self.rng.lock().unwrap()
+    }
+
+    /// 将字典项代入自定义请求头和请求体，有请求体但未指定 Content-Type 时按表单提交
+    fn render_template(&self, word: &str) -> (Vec<(&str, String)>, Option<String>) {
+        let mut headers: Vec<(&str, String)> = self.config.headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.replace(WORD_PLACEHOLDER, word)))
+            .collect();
+        let body = self.config.data.as_ref().map(|data| data.replace(WORD_PLACEHOLDER, word));
+        if body.is_some() && !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(CONTENT_TYPE.as_str())) {
+            headers.push((CONTENT_TYPE.as_str(), "application/x-www-form-urlencoded".to_string()));
+        }
+        (headers, body)
+    }
+
+    /// 有效发现之后按 `--delay-on-match` 暂缓派发，路径匹配 `--pause-on-match` 时暂停扫描
+    ///
+    /// 交互模式下在终端等待回车后继续；否则由 `--tui` 或 `--auto` 时终端输入的 `r` 继续
+    fn react_to_match(&self, finding: &Finding) {
+        if let Some(delay) = self.config.delay_on_match {
+            self.pause.hold(delay);
+        }
+        let Some(pattern) = self.config.pause_pattern(&finding.url) else {
+            return;
+        };
+        // 多个在途请求先后命中时只提示一次
+        if !self.pause.pause() {
+            return;
+        }
+        let notice = format!("[暂停] {} 匹配 '{}'，已暂停派发新请求", finding.url, pattern);
+        if self.config.interactive {
+            console::prompt(&format!("{}，回车继续扫描: ", notice).yellow().to_string());
+            self.pause.resume();
+            console::print_line("已继续扫描".green());
+        } else if !self.config.quiet {
+            console::print_line(format!("{}，输入 r 回车继续", notice).yellow());
+        }
+    }
+
+    /// 记录一次网络错误，连续出错达到 `--error-abort` 时询问是否中止该目标，非交互模式下直接中止
+    async fn record_network_error(&self, target: &Target) {
+        let Some(limit) = self.config.error_abort else {
+            return;
+        };
+        if target.consecutive_errors.fetch_add(1, Ordering::SeqCst) + 1 != limit {
+            return;
+        }
+        // 借用过滤状态的锁，避免与其他询问同时占用终端
+        let state = target.scan_state.lock().await;
+        console::print_line(format!("\n{}", format!("目标 {} 连续 {} 个请求出错，可能已宕机或屏蔽了扫描。", target.base, limit).yellow()));
+        if state.confirm("是否中止扫描该目标？(y/n): ") {
+            target.aborted.store(true, Ordering::SeqCst);
+            console::print_line("已中止扫描该目标。".yellow());
+        } else {
+            target.consecutive_errors.store(0, Ordering::SeqCst);
+            console::print_line("继续扫描。".green());
+        }
+    }
+
+    /// 收到 SIGHUP 时重新加载过滤规则文件，加载失败时保留原有规则
+    fn reload_filters(&self) {
+        let Some(ref path) = self.config.filter_file else {
+            console::print_line("\n未指定过滤规则文件，忽略 SIGHUP".yellow());
+            return;
+        };
+        match self.base_state.reload_signatures(path) {
+            Ok(count) => console::print_line(format!("\n已重新加载过滤规则，共 {} 条", count).green()),
+            Err(e) => warn!(error = %e, "重新加载过滤规则失败，继续使用原有规则"),
+        }
+    }
+
+    /// 扫描前解析目标主机并缓存，解析失败时不必再发送任何请求
+    ///
+    /// IP地址、`--resolve` 固定的主机以及经过代理（由代理负责解析）的主机跳过
+    async fn pre_resolve(&self, base: &Url) -> Result<()> {
+        let Some(Host::Domain(host)) = base.host() else {
+            return Ok(());
+        };
+        if self.unix.is_some() {
+            return Ok(());
+        }
+        if self.config.proxied(base) || self.config.resolve.iter().any(|(name, _)| name == host) {
+            return Ok(());
+        }
+        self.resolver.prime(host).await
+    }
+
+    /// 请求一个随机路径，判断目标是否把http请求统一跳转到同一主机的https，是则返回改用https后的目标URL
+    async fn detect_https_upgrade(&self, base: &Url) -> Option<Url> {
+        if base.scheme() != "http" || self.unix.is_some() {
+            return None;
+        }
+        let url = self.build_url(base, &random_path(&mut *self.rng())).ok()?;
+        let mut request = self.shared_client().get(url.as_str());
+        if self.static_ua.is_none() {
+            request = request.header(USER_AGENT, get_random_user_agent(&mut *self.rng()));
+        }
+        let request = request.send();
+        let resp = tokio::time::timeout(self.config.timeout, request).await.ok()?.ok()?;
+        // 跟随跳转时看最终地址，否则看 Location
+        let landed = if resp.status().is_redirection() {
+            let location = resp.headers().get(LOCATION)?.to_str().ok()?;
+            url.join(location).ok()?
+        } else {
+            resp.url().clone()
+        };
+        if landed.scheme() != "https" || landed.host_str() != base.host_str() {
+            return None;
+        }
+        let mut upgraded = base.clone();
+        upgraded.set_scheme("https").ok()?;
+        upgraded.set_port(landed.port()).ok()?;
+        Some(upgraded)
+    }
+
+    /// 询问是否改用https扫描，`--auto-upgrade` 时直接改用；非交互模式下不会走到这里
+    fn confirm_upgrade(&self, base: &Url, upgraded: &Url) -> bool {
+        console::print_line(format!("\n{} 将所有请求跳转到 https，继续使用 http 只会得到大量跳转。", base).yellow());
+        let upgrade = self.config.auto_upgrade
+            || self.base_state.confirm(&format!("是否改用 {} 扫描？(y/n): ", upgraded));
+        if upgrade {
+            console::print_line(format!("已改用 {} 扫描", upgraded).green());
+        }
+        upgrade
+    }
+
+    /// 请求若干随机路径，确定目标对不存在路径的响应基线
+    ///
+    /// 同时请求目标URL本身得到首页指纹，用于识别对未知路径返回首页内容的站点；首页请求失败时不影响校准
+    async fn calibrate(&self, base: &Url) -> Result<(Baseline, Option<Baseline>)> {
+        let mut probes = Vec::with_capacity(CALIBRATION_PROBES);
+        for _ in 0..CALIBRATION_PROBES {
+            let word = random_path(&mut *self.rng());
+            let url = self.build_url(base, &word)?;
+            probes.push(self.calibration_probe(&url, &word).await?);
+        }
+        let baseline = Baseline::from_probes(&probes).ok_or_else(|| anyhow!("基线校准失败"))?;
+
+        let mut homepage = Vec::with_capacity(HOMEPAGE_PROBES);
+        for _ in 0..HOMEPAGE_PROBES {
+            match self.calibration_probe(base, "").await {
+                Ok(probe) => homepage.push(probe),
+                Err(_) => break,
+            }
+        }
+        Ok((baseline, Baseline::from_probes(&homepage)))
+    }
+
+    /// 发送一个校准请求，返回状态码和响应大小
+    async fn calibration_probe(&self, url: &Url, word: &str) -> Result<(StatusCode, usize)> {
+        let timeout = self.config.calibration_timeout.unwrap_or(self.config.timeout);
+        let (custom, body) = self.render_template(word);
+        let (request_url, host_header) = self.connect_target(url);
+        let mut request = self.shared_client().request(self.method(), request_url.as_str()).timeout(timeout);
+        if let Some(host) = host_header {
+            request = request.header(HOST, host);
+        }
+        if self.static_ua.is_none() && !custom.iter().any(|(name, _)| name.eq_ignore_ascii_case(USER_AGENT.as_str())) {
+            request = request.header(USER_AGENT, get_random_user_agent(&mut *self.rng()));
+        }
+        for (name, value) in &custom {
+            request = request.header(*name, value.as_str());
+        }
+        if let Some(ref signer) = self.signer {
+            for (name, value) in signer.headers(&self.method(), url, body.as_deref()) {
+                request = request.header(name, value);
+            }
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let probe = async {
+            let resp = self.send_authenticated(request, &self.method(), &request_url).await
+                .map_err(|e| anyhow!("基线校准请求失败: {}", e))?;
+            let status = resp.status();
+            let size = if self.config.head_only {
+                header_content_length(&resp)
+            } else {
+                resp.bytes().await
+                    .map_err(|_| anyhow!("读取基线响应内容失败"))?
+                    .len()
+            };
+            Ok::<_, anyhow::Error>((status, size))
+        };
+        tokio::time::timeout(timeout, probe).await
+            .map_err(|_| anyhow!("基线校准请求超时"))?
+    }
+
+    /// `--compare-methods`：对将要报告的发现再用其他几种请求方法分别请求，状态码因方法而异时记录下来并立即提示
+    ///
+    /// 扫描所用方法的状态码直接取自发现本身，不再重复请求
+    async fn compare_methods(&self, target: &Target, path: &str, finding: &Finding) {
+        let mut statuses = Vec::with_capacity(COMPARED_METHODS.len());
+        for method in COMPARED_METHODS {
+            if method == self.method() {
+                statuses.push((method, finding.status));
+                continue;
+            }
+            match self.method_probe(target, &finding.url, path, &method).await {
+                Ok(status) => statuses.push((method, status)),
+                Err(e) => debug!(url = %finding.url, method = %method, error = %e, "方法比较请求失败"),
+            }
+        }
+        let Some(discrepancy) = MethodDiscrepancy::detect(finding.url.clone(), statuses) else {
+            return;
+        };
+        if !self.config.quiet {
+            console::print_line(format!("[~] 方法差异: {}", discrepancy).yellow());
+        }
+        self.method_discrepancies.lock().unwrap().push(discrepancy);
+    }
+
+    /// 用指定方法请求一次URL，只关心状态码；请求体只随POST发送
+    ///
+    /// 与正常请求一样遵守服务器通告的限流额度，并通过同样的UA槽位、后端地址名额和代理池发送
+    async fn method_probe(&self, target: &Target, url: &Url, word: &str, method: &Method) -> Result<StatusCode> {
+        let (custom, body) = self.render_template(word);
+        let body = (method == Method::POST).then(|| body.unwrap_or_default());
+        let lease = self.lease(url).await?;
+        let (request_url, host_header) = self.connect_target(url);
+        let mut request = lease.client().request(method.clone(), request_url.as_str()).timeout(self.config.timeout_for(word));
+        if let Some(host) = host_header {
+            request = request.header(HOST, host);
+        }
+        if self.static_ua.is_none() && !custom.iter().any(|(name, _)| name.eq_ignore_ascii_case(USER_AGENT.as_str())) {
+            request = request.header(USER_AGENT, lease.user_agent);
+        }
+        for (name, value) in &custom {
+            request = request.header(*name, value.as_str());
+        }
+        if let Some(extra) = self.config.headers_for_path(word) {
+            for (name, value) in extra {
+                request = request.header(name.as_str(), value.as_str());
+            }
+        }
+        if let Some(ref signer) = self.signer {
+            for (name, value) in signer.headers(method, url, body.as_deref()) {
+                request = request.header(name, value);
+            }
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        if self.config.follow_rate_limit {
+            target.rate_limit.wait(target.base.host_str().unwrap_or_default()).await;
+        }
+        let sent = match tokio::time::timeout(self.config.timeout_for(word), self.send_authenticated(request, method, &request_url)).await {
+            Ok(sent) => sent.map_err(RequestError::from),
+            Err(_) => Err(RequestError::new(ErrorKind::Timeout, "请求超时")),
+        };
+        self.record_proxy(&lease, &sent);
+        let resp = sent?;
+        if self.config.follow_rate_limit {
+            target.rate_limit.update(resp.status(), resp.headers());
+        }
+        Ok(resp.status())
+    }
+
+    /// 发送请求；配置了Digest认证时带上该主机已缓存质询的认证头，收到新的质询时带上认证头重发一次
+    async fn send_authenticated(&self, mut request: RequestBuilder, method: &Method, url: &Url) -> Result<Response, SendError> {
+        let Some(ref digest) = self.digest else {
+            return unix::send(request, self.unix.as_ref()).await;
+        };
+        let retry = request.try_clone();
+        let mut sent_nonce = None;
+        if let Some((nonce, authorization)) = digest.authorization(method, url) {
+            request = request.header(AUTHORIZATION, authorization);
+            sent_nonce = Some(nonce);
+        }
+        let resp = unix::send(request, self.unix.as_ref()).await?;
+        if resp.status() != StatusCode::UNAUTHORIZED || !digest.update(url, resp.headers(), sent_nonce.as_deref()) {
+            return Ok(resp);
+        }
+        match (retry, digest.authorization(method, url)) {
+            (Some(retry), Some((_, authorization))) => {
+                debug!(url = %url, "收到Digest质询，带上认证头重新请求");
+                unix::send(retry.header(AUTHORIZATION, authorization), self.unix.as_ref()).await
+            }
+            _ => Ok(resp),
+        }
+    }
+
+    /// 为发往 `url` 的请求选择客户端和UA，UA槽位、后端地址名额和代理都按扫描配置分配
+    async fn lease(&self, url: &Url) -> Result<Lease<'_>, RequestError> {
+        // 并发不超过线程数，槽位总是够用；万一取不到则退回共享客户端
+        let slot = self.ua_slots.as_ref().and_then(UaSlots::acquire);
+        let user_agent = match slot {
+            Some(ref slot) => slot.client().1,
+            None => self.static_ua.as_deref().unwrap_or_else(|| get_random_user_agent(&mut *self.rng())),
+        };
+        // 限制每个后端地址的并发时改用只连接选定地址的客户端，名额一直占用到读完响应体
+        let backend = match self.ip_pool {
+            Some(ref pool) => Some(pool.acquire(url.host_str().unwrap_or_default()).await
+                .map_err(|e| RequestError::new(ErrorKind::Connect, e.to_string()))?),
+            None => None,
+        };
+        // 使用代理池时只从当前可用的代理中选择，都不可用时等待恢复，不把路径当作出错
+        let proxy = match self.proxies {
+            Some(ref pool) => Some(pool.acquire().await),
+            None => None,
+        };
+        Ok(Lease { shared: &self.client, slot, backend, proxy, user_agent })
+    }
+
+    /// 按请求结果更新所用代理的健康状态
+    fn record_proxy<T>(&self, lease: &Lease<'_>, sent: &Result<T, RequestError>) {
+        if let (Some(pool), Some(proxy)) = (&self.proxies, lease.proxy) {
+            match sent {
+                Ok(_) => pool.record_success(proxy),
+                Err(error) if matches!(error.kind, ErrorKind::Connect | ErrorKind::Timeout) => pool.record_failure(proxy),
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// 检查一个路径，网络错误时重试，同一路径累计出错 `--max-errors-per-path` 次后放弃
+    async fn check_path(&self, target: &Target, path: &str) -> Result<Option<Finding>> {
+        let span = debug_span!("request", path = %path);
+        async {
+            let mut failures = 0;
+            loop {
+                match self.check_path_once(target, path).await {
+                    Err(e) if e.is::<RequestError>() => {
+                        failures += 1;
+                        if failures >= self.config.max_errors_per_path || target.aborted.load(Ordering::SeqCst) {
+                            return Err(e);
+                        }
+                        debug!(error = %e, failures, "请求出错，重试");
+                    }
+                    result => {
+                        // 只比较将要报告的发现，被过滤或忽略的路径不值得额外的请求
+                        if let Ok(Some(ref finding)) = result {
+                            if self.config.compare_methods && !finding.filtered {
+                                self.compare_methods(target, path, finding).await;
+                            }
+                        }
+                        return result;
+                    }
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn check_path_once(&self, target: &Target, path: &str) -> Result<Option<Finding>> {
+        let url = self.build_url(&target.base, path)?;
+        if !self.config.in_scope(&url) {
+            return Err(anyhow!("{} 的主机不在扫描范围内，已拒绝请求", url));
+        }
+        // 过长的URL几乎总会被服务器以414拒绝，不必发送
+        if self.config.max_url_length > 0 && url.as_str().len() > self.config.max_url_length {
+            self.skipped_long_urls.fetch_add(1, Ordering::SeqCst);
+            return Ok(None);
+        }
+        if !self.config.path_selected(&url) {
+            self.skipped_paths.fetch_add(1, Ordering::SeqCst);
+            return Ok(None);
+        }
+
+        let lease = self.lease(&url).await?;
+        let (client, user_agent) = (lease.client(), lease.user_agent);
+        let mut headers = Vec::new();
+        if self.static_ua.is_none() {
+            headers.push((USER_AGENT.as_str(), user_agent));
+        }
+        if self.config.randomize_headers {
+            let random = get_random_headers(user_agent, &mut *self.rng());
+            headers.push((ACCEPT.as_str(), random.accept));
+            headers.push((ACCEPT_LANGUAGE.as_str(), random.accept_language));
+            // 关闭解压或经Unix域套接字发送时保持默认，避免收到无法处理的压缩响应
+            if self.config.decompress && self.unix.is_none() {
+                headers.push((ACCEPT_ENCODING.as_str(), random.accept_encoding));
+            }
+        }
+        let (request_url, host_header) = self.connect_target(&url);
+        if let Some(ref host) = host_header {
+            headers.push((HOST.as_str(), host));
+        }
+        // 上次扫描记录了缓存校验信息时发送条件请求
+        let validators = self.validators.get(url.as_str());
+        if let Some(validators) = validators {
+            if let Some(ref etag) = validators.etag {
+                headers.push((IF_NONE_MATCH.as_str(), etag));
+            }
+            if let Some(ref last_modified) = validators.last_modified {
+                headers.push((IF_MODIFIED_SINCE.as_str(), last_modified));
+            }
+        }
+        // 自定义请求头覆盖同名的自动请求头
+        let (custom, body) = self.render_template(path);
+        headers.retain(|(name, _)| !custom.iter().any(|(custom_name, _)| custom_name.eq_ignore_ascii_case(name)));
+        headers.extend(custom.iter().map(|(name, value)| (*name, value.as_str())));
+        // 字典项自带的请求头只用于这一个请求，优先级最高
+        if let Some(extra) = self.config.headers_for_path(path) {
+            headers.retain(|(name, _)| !extra.iter().any(|(extra_name, _)| extra_name.eq_ignore_ascii_case(name)));
+            headers.extend(extra.iter().map(|(name, value)| (name.as_str(), value.as_str())));
+        }
+        // 签名按最终的URL和请求体计算，覆盖同名的请求头
+        let signature = self.signer.as_ref().map(|signer| signer.headers(&self.method(), &url, body.as_deref()));
+        if let Some(ref signature) = signature {
+            headers.retain(|(name, _)| !signature.iter().any(|(signed, _)| signed.eq_ignore_ascii_case(name)));
+            headers.extend(signature.iter().map(|(name, value)| (*name, value.as_str())));
+        }
+        // 请求头按加入顺序发送，打乱后每个请求的顺序都不同
+        if self.config.shuffle_headers {
+            headers.shuffle(&mut *self.rng());
+        }
+        let mut request = client.request(self.method(), request_url.as_str()).timeout(self.config.timeout_for(path));
+        for (name, value) in &headers {
+            request = request.header(*name, *value);
+        }
+        if let Some(ref body) = body {
+            request = request.body(body.clone());
+        }
+        if self.config.follow_rate_limit {
+            target.rate_limit.wait(target.base.host_str().unwrap_or_default()).await;
+        }
+        let started = Instant::now();
+        // 整个请求（包括读取响应体）共用一个截止时间
+        let deadline = tokio::time::Instant::now() + self.config.timeout_for(path);
+        let sent = match tokio::time::timeout_at(deadline, self.send_authenticated(request, &self.method(), &request_url)).await {
+            Ok(sent) => sent.map_err(RequestError::from),
+            Err(_) => Err(RequestError::new(ErrorKind::Timeout, "请求超时")),
+        };
+        self.record_proxy(&lease, &sent);
+        let mut resp = match sent {
+            Ok(resp) => resp,
+            Err(error) => {
+                self.record_trace(&url, user_agent, None, None, started, &format!("错误: {}", error)).await;
+                self.record_network_error(target).await;
+                return Err(error.into());
+            }
+        };
+        let received = SystemTime::now();
+        debug!(
+            url = %url,
+            status = resp.status().as_u16(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            ip = lease.backend.as_ref().map(|backend| backend.ip.to_string()),
+            "收到响应"
+        );
+        target.consecutive_errors.store(0, Ordering::SeqCst);
+        if self.config.follow_rate_limit {
+            target.rate_limit.update(resp.status(), resp.headers());
+        }
+
+        let status = resp.status();
+        self.stats.record_status(status.as_u16());
+        // 跟随跳转后记录最终地址
+        let final_url = (resp.url() != &request_url).then(|| resp.url().clone());
+        let header = |name| resp.headers().get(name).and_then(|value: &HeaderValue| value.to_str().ok()).map(str::to_string);
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let technologies = detect_technologies(resp.headers());
+        // 条件请求返回304，资源自上次扫描以来没有变化
+        if status == StatusCode::NOT_MODIFIED && validators.is_some() {
+            self.clusters.record(&url, status.as_u16(), 0, None);
+            self.record_trace(&url, user_agent, Some(status.as_u16()), Some(0), started, "过滤: 未修改").await;
+            let validators = validators.cloned().unwrap_or_default();
+            let severity = self.config.severity_for(path, &url);
+            return Ok(Some(Finding {
+                url,
+                status,
+                size: 0,
+                content_type: None,
+                redirect: None,
+                final_url,
+                outlier: false,
+                sensitive: false,
+                listing: false,
+                protected: false,
+                length_mismatch: None,
+                etag: etag.or(validators.etag),
+                last_modified: last_modified.or(validators.last_modified),
+                word: None,
+                severity,
+                truncated: false,
+                skipped_body: false,
+                timestamp: received,
+                title: None,
+                technologies: technologies.clone(),
+                filtered: true,
+                reason: Some(FilterReason::NotModified),
+            }));
+        }
+        // 跟随跳转时仍拿到3xx，说明跳转因循环或次数超限被中止
+        let location = if status.is_redirection() {
+            resp.headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| url.join(value).ok())
+        } else {
+            None
+        };
+        
+        let StatusClass { reportable, protected, location } = classify_status(&self.config, status, location);
+        // 智能匹配模式下任何状态码都可能是发现，异常值模式下需要统计所有响应的大小
+        let smart = self.config.smart_match.is_some();
+        if !reportable && location.is_none() && !smart && self.config.outlier_threshold.is_none() {
+            self.clusters.record(&url, status.as_u16(), header_content_length(&resp), None);
+            self.record_trace(&url, user_agent, Some(status.as_u16()), resp.content_length().map(|len| len as usize), started, "忽略").await;
+            return Ok(None);
+        }
+
+        let content_type = resp.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        // 解压时客户端会移除 Content-Length，此时无法比较长度
+        let declared_length = resp.headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<usize>().ok());
+        let mut length_mismatch = None;
+        // 读取响应体时命中的内容特征（命中后不再读取），以及是否因 --max-body-size 没有读完
+        let mut early_signature = None;
+        let mut truncated = false;
+        // 超过 --skip-large 的响应只记录存在，不读取（或不再继续读取）响应体
+        let mut skipped_body = false;
+        let too_large = |size: usize| self.config.skip_large.is_some_and(|max| size > max);
+        let (content, content_length) = if self.config.head_only {
+            // HEAD请求没有响应体，大小取自 Content-Length 头
+            (None, declared_length.unwrap_or(0))
+        } else if let Some(declared) = declared_length.filter(|declared| too_large(*declared)) {
+            skipped_body = true;
+            (None, declared)
+        } else {
+            // 逐块读取，命中内容特征或达到大小上限时不再读取剩余内容
+            let overlap = self.base_state.longest_signature();
+            let mut body = Vec::new();
+            // 实际读到的字节数，截断后 body 只保留上限以内的部分
+            let mut received = 0;
+            loop {
+                let chunk = match tokio::time::timeout_at(deadline, resp.chunk()).await {
+                    Ok(Ok(Some(chunk))) => chunk,
+                    Ok(Ok(None)) => break,
+                    // 连接在读完 Content-Length 声明的长度之前关闭，可能是截断或中间设备干扰，按已读到的内容继续处理；
+                    // 声明长度超出时hyper只读取声明的长度，因此只能发现内容比声明短的情况
+                    Ok(Err(e)) => match declared_length {
+                        Some(declared) if !e.is_timeout() && received < declared => {
+                            length_mismatch = Some((declared, received));
+                            break;
+                        }
+                        _ => return Err(RequestError::new(ErrorKind::Read, "读取响应内容失败").into()),
+                    },
+                    Err(_) => return Err(RequestError::new(ErrorKind::Timeout, "请求超时").into()),
+                };
+                let scanned = body.len().saturating_sub(overlap);
+                received += chunk.len();
+                body.extend_from_slice(&chunk);
+                // 没有 Content-Length（如分块传输或自动解压）时按已读到的字节数判断
+                if too_large(body.len()) {
+                    skipped_body = true;
+                    break;
+                }
+                // 恰好读到上限时还不知道后面是否有内容，超过上限才算截断
+                if let Some(max) = self.config.max_body_size.filter(|max| body.len() > *max) {
+                    body.truncate(max);
+                    truncated = true;
+                }
+                if overlap > 0 {
+                    early_signature = self.base_state.signature_in(&body[scanned..]);
+                }
+                if truncated || early_signature.is_some() {
+                    break;
+                }
+            }
+            if skipped_body {
+                (None, received)
+            } else if truncated || early_signature.is_some() {
+                // 没有读完时大小取自 Content-Length，没有该头时只能按已读到的字节数计算
+                (Some(decode_body(&body, content_type.as_deref())), declared_length.unwrap_or(received))
+            } else {
+                // 大小按原始字节计算，与 Content-Length 和解码方式无关
+                (Some(decode_body(&body, content_type.as_deref())), body.len())
+            }
+        };
+        let title = content.as_deref().and_then(extract_title);
+        // 命中内容特征后停止读取且没有 Content-Length 时，大小只是停止前读到的字节数，取决于分块的边界
+        let partial_size = early_signature.is_some() && declared_length.is_none();
+        if !partial_size {
+            self.clusters.record(&url, status.as_u16(), content_length, title.as_deref());
+        }
+        
+        // 跟随跳转时仍拿到3xx：循环被记录在 redirect_loops 中，否则是超过了次数上限
+        let redirect_loop = location.is_some()
+            && self.config.follows_redirects()
+            && self.redirect_loops.lock().unwrap().remove(url.as_str());
+        let response = ResponseView {
+            url: &url,
+            path,
+            status,
+            reportable,
+            location: location.as_ref(),
+            content: content.as_deref(),
+            size: content_length,
+            title: title.as_deref(),
+            early_signature,
+            partial_size,
+            redirect_loop,
+        };
+        let classification = {
+            let mut state = target.scan_state.lock().await;
+            classify_response(&self.config, &mut state, response).await
+        };
+        let verdict = match classification {
+            Classification::Ignored(decision) => {
+                self.record_trace(&url, user_agent, Some(status.as_u16()), Some(content_length), started, decision).await;
+                return Ok(None);
+            }
+            Classification::Reported(verdict) => verdict,
+        };
+        let Verdict { outlier, sensitive, listing, severity, reason } = verdict;
+
+        if self.trace.is_some() {
+            let decision = match reason {
+                Some(ref reason) => format!("过滤: {}", reason),
+                None => "报告".to_string(),
+            };
+            self.record_trace(&url, user_agent, Some(status.as_u16()), Some(content_length), started, &decision).await;
+        }
+        if let (Some(ref curl), None) = (&self.curl, &reason) {
+            // 固定UA设置在客户端上，复现命令中需要补上
+            let client_ua = self.static_ua.as_deref()
+                .filter(|_| !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(USER_AGENT.as_str())))
+                .map(|ua| (USER_AGENT.as_str(), ua));
+            let headers: Vec<_> = client_ua.into_iter().chain(headers.iter().copied()).collect();
+            curl.record(&curl_command(&self.config, &self.method(), &url, &headers, body.as_deref())).await;
+        }
+
+        Ok(Some(Finding {
+            url,
+            status,
+            size: content_length,
+            content_type,
+            redirect: location,
+            final_url,
+            outlier,
+            sensitive,
+            listing,
+            protected,
+            length_mismatch,
+            etag,
+            last_modified,
+            word: self.config.templated_request().then(|| path.to_string()),
+            severity,
+            truncated,
+            skipped_body,
+            timestamp: received,
+            title,
+            technologies,
+            filtered: reason.is_some(),
+            reason,
+        }))
+    }
+}
+
+/// 字典项对应的请求URL，只取决于配置、目标URL和字典项本身
+///
+/// 目标URL含 `%WORD%` 时只替换占位符；占位符在请求头或请求体中时URL保持不变；
+/// 参数模糊测试时字典项作为查询参数名追加；其余情况按 [`join_entry`] 拼接到路径上
+pub(crate) fn build_request_url(config: &ScanConfig, base: &Url, entry: &str) -> Result<Url> {
+    // 请求模板模式：字典项只替换占位符，不再拼接到路径上
+    if base.as_str().contains(WORD_PLACEHOLDER) {
+        let url = fill_url_template(base.as_str(), entry);
+        return Url::parse(&url).map_err(|e| anyhow!("替换占位符后的URL无效: {} ({})", url, e));
+    }
+    if config.templated_request() {
+        return Ok(base.clone());
+    }
+    if config.param_fuzz {
+        let mut url = base.clone();
+        url.query_pairs_mut().append_pair(entry, PARAM_FUZZ_VALUE);
+        return Ok(url);
+    }
+    join_entry(base, entry, config.allow_absolute)
+}
+
+/// URL片段中需要编码的字符（WHATWG URL标准的 fragment percent-encode set）
+const FRAGMENT_ENCODE: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+/// 查询串中需要编码的字符，`&`、`=` 保持原样，字典项可以代入多个参数（query percent-encode set）
+const QUERY_ENCODE: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'<').add(b'>').add(b'\'');
+/// 路径中需要编码的字符，`?`、`#` 编码后不会把字典项的后半截变成查询串或片段（path percent-encode set）
+const PATH_ENCODE: &AsciiSet = &QUERY_ENCODE.add(b'?').add(b'`').add(b'{').add(b'}');
+
+/// 把字典项代入URL模板中的每个 `%WORD%`，按占位符所在的部分（路径、查询串、片段）编码
+///
+/// `/` 和已有的 `%xx` 保持原样，字典项中的 `admin/login`、`a%2Fb` 等按原意发送
+fn fill_url_template(template: &str, entry: &str) -> String {
+    let mut url = String::new();
+    let mut rest = template;
+    while let Some(index) = rest.find(WORD_PLACEHOLDER) {
+        url.push_str(&rest[..index]);
+        // 模板中占位符之前的 ? 和 # 决定它所在的部分，代入的字典项中的这两个字符已被编码
+        let set = if url.contains('#') {
+            FRAGMENT_ENCODE
+        } else if url.contains('?') {
+            QUERY_ENCODE
+        } else {
+            PATH_ENCODE
+        };
+        url.extend(utf8_percent_encode(entry, set));
+        rest = &rest[index + WORD_PLACEHOLDER.len()..];
+    }
+    url.push_str(rest);
+    url
+}
+
+/// 只看状态码得出的初步判断，读取响应体之前决定是否还需要继续处理
+#[derive(Debug, PartialEq)]
+pub(crate) struct StatusClass {
+    /// 状态码本身值得报告（2xx、开启 --show-forbidden 时的401/403，或匹配 --match-codes）
+    pub(crate) reportable: bool,
+    /// 401/403 且开启了 --show-forbidden
+    pub(crate) protected: bool,
+    /// 仍需检查的跳转目标，不匹配 --match-codes 的跳转不保留
+    pub(crate) location: Option<Url>,
+}
+
+/// 按状态码和跳转目标对响应做初步分类，不涉及响应内容和扫描状态
+pub(crate) fn classify_status(config: &ScanConfig, status: StatusCode, location: Option<Url>) -> StatusClass {
+    // 401/403 说明资源存在但需要授权，开启 --show-forbidden 时与2xx一样报告
+    let protected = config.show_forbidden
+        && matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN);
+    // 指定了状态码规则时只报告匹配的响应，不匹配的跳转同样忽略
+    let (reportable, location) = match config.match_codes {
+        Some(ref codes) if codes.matches(status.as_u16()) => (true, location),
+        Some(_) => (false, None),
+        None => (status.is_success() || protected, location),
+    };
+    StatusClass { reportable, protected, location }
+}
+
+/// 读取响应内容后用于判断的信息
+pub(crate) struct ResponseView<'a> {
+    pub(crate) url: &'a Url,
+    /// 请求对应的字典项
+    pub(crate) path: &'a str,
+    pub(crate) status: StatusCode,
+    /// 见 [`StatusClass::reportable`]
+    pub(crate) reportable: bool,
+    /// 见 [`StatusClass::location`]
+    pub(crate) location: Option<&'a Url>,
+    /// 解码后的响应内容，HEAD请求或没有读取响应体时为 `None`
+    pub(crate) content: Option<&'a str>,
+    /// 响应大小（原始字节数）
+    pub(crate) size: usize,
+    pub(crate) title: Option<&'a str>,
+    /// 读取响应体时已经命中的内容特征
+    pub(crate) early_signature: Option<String>,
+    /// `size` 不是完整的响应大小，不参与基线比较和大小统计
+    pub(crate) partial_size: bool,
+    /// 跟随跳转时检测到了循环（否则仍拿到3xx说明超过了次数上限）
+    pub(crate) redirect_loop: bool,
+}
+
+/// 对一个响应的最终判断
+#[derive(Debug, PartialEq)]
+pub(crate) enum Classification {
+    /// 不报告，附带写入请求跟踪的说明
+    Ignored(&'static str),
+    Reported(Verdict),
+}
+
+/// 报告的响应的分类结果，`reason` 不为空时作为被过滤的结果报告
+#[derive(Debug, PartialEq)]
+pub(crate) struct Verdict {
+    pub(crate) outlier: bool,
+    pub(crate) sensitive: bool,
+    pub(crate) listing: bool,
+    pub(crate) severity: Severity,
+    pub(crate) reason: Option<FilterReason>,
+}
+
+/// 按响应状态、内容和目标的扫描状态判断是否报告及过滤原因，不发出任何请求
+///
+/// 会更新扫描状态中的统计（异常值、重复大小、登录页标题、重复标题等），每个响应只应调用一次
+pub(crate) async fn classify_response(config: &ScanConfig, state: &mut ScanState, response: ResponseView<'_>) -> Classification {
+    let ResponseView { url, path, status, reportable, location, content, size, title, early_signature, partial_size, redirect_loop } = response;
+    let smart = config.smart_match.is_some();
+    // 与404基线一致的响应视为不存在
+    if !partial_size && config.smart_match.is_some_and(|threshold| state.is_soft_404(status, size, threshold)) {
+        return Classification::Ignored("忽略: 与基线一致");
+    }
+    let outlier = match config.outlier_threshold {
+        Some(threshold) if !partial_size => state.check_outlier(size, threshold),
+        _ => false,
+    };
+    if !reportable && location.is_none() && !outlier && !smart {
+        return Classification::Ignored("忽略");
+    }
+
+    // 检查是否需要过滤，非2xx（及401/403）响应只会因为异常值被报告，不参与过滤
+    let filterable = reportable || location.is_some();
+    let reason = if !filterable {
+        None
+    } else if let Some(signature) = early_signature {
+        Some(FilterReason::Signature(signature))
+    } else if let Some(reason) = state.matched_rule(content, size) {
+        Some(reason)
+    } else if let Some(location) = location {
+        if !config.follows_redirects() {
+            state.check_redirect(url, location).await
+        } else if redirect_loop {
+            Some(FilterReason::RedirectLoop)
+        } else {
+            Some(FilterReason::TooManyRedirects)
+        }
+    } else if config.smart_match.is_some_and(|threshold| {
+        status.is_success() && state.echoes_homepage(status, size, threshold)
+    }) {
+        // 对未知路径返回首页内容（常见于单页应用的前端路由），并非真实存在的资源
+        Some(FilterReason::Homepage)
+    } else if let Some(min) = config.min_content_length.filter(|min| status.is_success() && size < *min) {
+        // 在重复大小统计之前判断，空白页面不计入重复大小
+        Some(FilterReason::TooSmall(min))
+    } else if state.check_login_title(title).await {
+        Some(FilterReason::LoginTitle)
+    } else if state.check_repeated_size(size).await {
+        Some(FilterReason::RepeatedSize)
+    } else {
+        None
+    };
+
+    // 备份文件等敏感扩展名返回了非空内容，几乎总是真实的泄露
+    let sensitive = status == StatusCode::OK && size > 0 && config.is_sensitive_path(url.path());
+    let mut severity = config.severity_for(path, url);
+    if sensitive {
+        severity = severity.max(Severity::High);
+    }
+    let reason = reason.or_else(|| {
+        config.min_severity
+            .filter(|min| severity < *min)
+            .map(|_| FilterReason::BelowSeverity(severity))
+    });
+    // 放在最后，只有真正报告的发现才会占用标题
+    let reason = match reason {
+        None if filterable && config.unique_titles && state.check_duplicate_title(title) => {
+            Some(FilterReason::DuplicateTitle(title.unwrap_or_default().to_string()))
+        }
+        reason => reason,
+    };
+    let listing = status.is_success() && content.is_some_and(is_directory_listing);
+    Classification::Reported(Verdict { outlier, sensitive, listing, severity, reason })
+}
+
+/// 将字典项拼接到目标URL上
+///
+/// 目标URL的路径总是视为目录前缀：`http://example.com/app` 与 `http://example.com/app/` 拼接 `admin`
+/// 都得到 `/app/admin`，而不是按URL相对引用的规则替换掉最后一段。以 `?` 开头的字典项只替换查询字符串，
+/// 仍作用于目标URL本身；以 `/` 开头的字典项从站点根目录开始。
+///
+/// 完整URL的字典项默认拒绝，避免悄悄扫描其他主机；允许时按原样使用，但仍只接受 http/https
+pub(crate) fn join_entry(base: &Url, entry: &str, allow_absolute: bool) -> Result<Url> {
+    if is_absolute_entry(entry) && !allow_absolute {
+        return Err(anyhow!("字典项 '{}' 是完整URL，会请求其他主机，已跳过（使用 --allow-absolute 按原样扫描）", entry));
+    }
+    let mut base = Cow::Borrowed(base);
+    if !entry.starts_with('?') && !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.to_mut().set_path(&path);
+    }
+    let url = base.join(entry)
+        .map_err(|e| anyhow!("路径 '{}' 拼接失败: {}", entry, e))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(anyhow!("字典项 '{}' 不是 http/https 地址", entry));
+    }
+    Ok(url)
+}
+
+/// 扫描期间监听 SIGHUP，非Unix平台或注册失败时永远不会触发
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok(),
+        }
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(ref mut signal) = self.signal {
+            signal.recv().await;
+            return;
+        }
+        future::pending::<()>().await
+    }
+}
+
+/// 请求发送或读取响应时的网络错误，可以重试
+#[derive(Debug)]
+struct RequestError {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl RequestError {
+    fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+impl From<SendError> for RequestError {
+    fn from(error: SendError) -> Self {
+        if error.is_connect() {
+            Self::new(ErrorKind::Connect, "连接失败")
+        } else if error.is_timeout() {
+            Self::new(ErrorKind::Timeout, "请求超时")
+        } else {
+            Self::new(ErrorKind::Request, format!("请求失败: {}", error))
+        }
+    }
+}
+
+/// 从 `Content-Length` 头读取响应大小，缺失或无效时为0
+fn header_content_length(resp: &Response) -> usize {
+    resp.headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+const X_POWERED_BY: &str = "x-powered-by";
+const X_ASPNET_VERSION: &str = "x-aspnet-version";
+
+/// 常见目录列表页面的特征：Apache/nginx autoindex、IIS、Tomcat、Python http.server
+const LISTING_MARKERS: &[&str] = &[
+    "<title>Index of /",
+    "<h1>Index of /",
+    "[To Parent Directory]",
+    "<title>Directory Listing For",
+    "Directory listing for /",
+];
+
+/// 判断响应内容是否为目录列表页面
+fn is_directory_listing(content: &str) -> bool {
+    LISTING_MARKERS.iter().any(|marker| content.contains(marker))
+}
+
+/// 从 `Server`、`X-Powered-By`、`X-AspNet-Version` 头中识别服务端技术，按出现顺序去重
+fn detect_technologies(headers: &HeaderMap) -> Vec<String> {
+    let values = |name: &str| headers.get_all(name).into_iter().filter_map(|value: &HeaderValue| value.to_str().ok());
+    let mut technologies: Vec<String> = Vec::new();
+    let detected = values(SERVER.as_str())
+        .map(str::to_string)
+        .chain(values(X_POWERED_BY).flat_map(|value| value.split(',')).map(str::to_string))
+        .chain(values(X_ASPNET_VERSION).map(|version| format!("ASP.NET {}", version.trim())));
+    for technology in detected {
+        let technology = technology.trim();
+        if !technology.is_empty() && !technologies.iter().any(|known| known == technology) {
+            technologies.push(technology.to_string());
+        }
+    }
+    technologies
+}
+
+/// 读取额外信任的CA证书，每个PEM文件可以包含多个证书
+fn load_ca_certs(paths: &[PathBuf]) -> Result<Vec<Certificate>> {
+    let mut certs = Vec::new();
+    for path in paths {
+        let pem = std::fs::read(path)
+            .map_err(|e| anyhow!("读取CA证书 {} 失败: {}", path.display(), e))?;
+        let bundle = Certificate::from_pem_bundle(&pem)
+            .map_err(|e| anyhow!("CA证书 {} 不是有效的PEM格式: {}", path.display(), e))?;
+        if bundle.is_empty() {
+            return Err(anyhow!("CA证书 {} 中没有找到PEM格式的证书", path.display()));
+        }
+        certs.extend(bundle);
+    }
+    Ok(certs)
+}
+
+fn build_client(
+    config: &ScanConfig,
+    ca_certs: &[Certificate],
+    redirect_loops: &Arc<std::sync::Mutex<HashSet<String>>>,
+    resolver: &Arc<CachingResolver>,
+    cookies: Option<&Arc<Jar>>,
+    user_agent: &str,
+) -> Result<Client> {
+    // 扫描请求按字典项设置各自的超时（可能比这里长），预热、登录等其他请求使用这里的总超时
+    let mut client_builder = Client::builder()
+        .timeout(config.timeout)
+        .connect_timeout(config.connect_timeout)
+        .user_agent(user_agent)
+        .danger_accept_invalid_certs(config.insecure)
+        // 解压响应，保证内容特征过滤作用于解压后的文本
+        .gzip(config.decompress)
+        .deflate(config.decompress)
+        .brotli(config.decompress);
+    for cert in ca_certs {
+        client_builder = client_builder.add_root_certificate(cert.clone());
+    }
+
+    // 不跟随跳转时3xx响应交由 check_path 识别跳转到登录页等噪音
+    if !config.follow_redirects {
+        client_builder = client_builder.redirect(Policy::none());
+    } else {
+        // 跟随跳转时遇到循环或超过次数上限即停止，交由 check_path 标记
+        let max_redirects = config.max_redirects;
+        let redirect_loops = Arc::clone(redirect_loops);
+        let scope = config.scope.clone();
+        client_builder = client_builder.redirect(Policy::custom(move |attempt| {
+            // 不跟随到范围外的主机，停在当前的3xx响应上
+            if !host_in_scope(&scope, attempt.url()) {
+                attempt.stop()
+            } else if attempt.previous().contains(attempt.url()) {
+                if let Some(origin) = attempt.previous().first() {
+                    redirect_loops.lock().unwrap().insert(origin.to_string());
+                }
+                attempt.stop()
+            } else if attempt.previous().len() > max_redirects {
+                attempt.stop()
+            } else {
+                attempt.follow()
+            }
+        }));
+    }
+
+    // 连接池参数，影响keep-alive连接的复用
+    if let Some(max) = config.pool_max_idle_per_host {
+        client_builder = client_builder.pool_max_idle_per_host(max);
+    }
+    if let Some(timeout) = config.pool_idle_timeout {
+        client_builder = client_builder.pool_idle_timeout(timeout);
+    }
+    // TCP层参数，未指定时保持客户端默认（开启 TCP_NODELAY，不启用keepalive）
+    if let Some(nodelay) = config.tcp_nodelay {
+        client_builder = client_builder.tcp_nodelay(nodelay);
+    }
+    if let Some(interval) = config.tcp_keepalive {
+        client_builder = client_builder.tcp_keepalive(interval);
+    }
+
+    if let Some(jar) = cookies {
+        client_builder = client_builder.cookie_provider(Arc::clone(jar));
+    }
+
+    // 绑定本地出口地址
+    if let Some(addr) = config.bind_address {
+        client_builder = client_builder.local_address(addr);
+    }
+
+    // 缓存DNS解析结果，--resolve 指定的主机优先使用固定地址
+    client_builder = client_builder.dns_resolver(Arc::clone(resolver));
+
+    // 固定域名解析，Host头和SNI仍使用原主机名
+    let target_host = config.base_url.as_deref()
+        .and_then(|url| Url::parse(url).ok())
+        .and_then(|url| url.host_str().map(str::to_string));
+    for (host, addr) in &config.resolve {
+        client_builder = client_builder.resolve(host, *addr);
+        // 自定义SNI的名称跟随目标主机的固定解析
+        if let Some(ref sni) = config.sni {
+            if target_host.as_deref() == Some(host.as_str()) {
+                client_builder = client_builder.resolve(sni, *addr);
+            }
+        }
+    }
+
+    // 代理实现：--proxy 优先于环境变量；设置了代理后reqwest不再自动检测系统代理，
+    // 只有 --no-proxy-env 时才显式关闭，两者都没有时沿用reqwest的默认行为
+    if config.no_proxy_env {
+        client_builder = client_builder.no_proxy();
+    }
+    // 通过Unix套接字扫描时该客户端只用于构造请求，不必设置代理
+    if config.unix_socket.is_some() {
+        return client_builder.build()
+            .map_err(|e| anyhow!("HTTP客户端创建失败: {}", e));
+    }
+    let exclusions = NoProxy::from_string(&config.proxy_exclusions().join(","));
+    if let Some(ref proxy_url) = config.proxy {
+        let proxy = Proxy::all(proxy_url)
+            .map_err(|e| anyhow!("代理设置错误: {}", e))?
+            .no_proxy(exclusions);
+        client_builder = client_builder.proxy(proxy);
+    } else {
+        for scheme in ["http", "https"] {
+            let Some(proxy_url) = config.effective_proxy(scheme) else {
+                continue;
+            };
+            let proxy = match scheme {
+                "http" => Proxy::http(&proxy_url),
+                _ => Proxy::https(&proxy_url),
+            }
+            .map_err(|e| anyhow!("环境变量中的代理设置错误: {}", e))?
+            .no_proxy(exclusions.clone());
+            client_builder = client_builder.proxy(proxy);
+        }
+    }
+
+    client_builder.build()
+        .map_err(|e| anyhow!("HTTP客户端创建失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn joins_relative_entries_onto_base_directory() {
+        let url = join_entry(&base("http://example.com/app/"), "admin/login", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/admin/login");
+        // 以 / 开头的字典项从站点根目录开始
+        let url = join_entry(&base("http://example.com/app/"), "/admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/admin");
+        let url = join_entry(&base("http://example.com"), "admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/admin");
+    }
+
+    #[test]
+    fn treats_base_path_as_directory() {
+        // 目标路径没有结尾斜杠时也不会替换掉最后一段
+        let url = join_entry(&base("http://example.com/app"), "admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/admin");
+        let url = join_entry(&base("http://example.com/a/b"), "c/", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/a/b/c/");
+        let url = join_entry(&base("http://example.com/app"), "", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/");
+        let url = join_entry(&base("http://example.com/app"), "../admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/admin");
+        // 目标URL的查询字符串不会带到拼接结果中
+        let url = join_entry(&base("http://example.com/app?lang=en"), "admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/admin");
+        let url = join_entry(&base("http://example.com/my%20app"), "admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/my%20app/admin");
+        let url = join_entry(&base("http://example.com/app"), "/admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/admin");
+    }
+
+    #[test]
+    fn keeps_query_strings_in_entries() {
+        let url = join_entry(&base("http://example.com/app/"), "debug.php?debug=1", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/debug.php?debug=1");
+        let url = join_entry(&base("http://example.com/app/index.php"), "?debug=1", false).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/index.php?debug=1");
+    }
+
+    #[test]
+    fn rejects_absolute_entries_by_default() {
+        for entry in ["http://other.com/admin", "https://other.com/", "//other.com/admin"] {
+            assert!(is_absolute_entry(entry), "{}", entry);
+            let err = join_entry(&base("http://example.com/"), entry, false).unwrap_err().to_string();
+            assert!(err.contains("--allow-absolute"), "{}", err);
+        }
+        assert!(!is_absolute_entry("admin"));
+        assert!(!is_absolute_entry("/admin/http://x"));
+    }
+
+    #[test]
+    fn scans_absolute_entries_when_allowed() {
+        let url = join_entry(&base("http://example.com/"), "http://other.com/admin", true).unwrap();
+        assert_eq!(url.as_str(), "http://other.com/admin");
+        // 协议相对地址沿用目标的协议
+        let url = join_entry(&base("https://example.com/"), "//other.com/admin", true).unwrap();
+        assert_eq!(url.as_str(), "https://other.com/admin");
+        assert!(join_entry(&base("http://example.com/"), "ftp://other.com/", true).is_err());
+    }
+
+    #[test]
+    fn joins_entries_onto_ipv6_targets() {
+        let url = join_entry(&base("http://[::1]:8080/app"), "admin", false).unwrap();
+        assert_eq!(url.as_str(), "http://[::1]:8080/app/admin");
+        let url = join_entry(&base("https://[2001:db8::1]/"), "/login?next=/", false).unwrap();
+        assert_eq!(url.as_str(), "https://[2001:db8::1]/login?next=/");
+        let url = join_entry(&base("http://[::1]/"), "", false).unwrap();
+        assert_eq!(url.as_str(), "http://[::1]/");
+    }
+
+    fn config() -> crate::config::ScanConfigBuilder {
+        ScanConfig::builder().url("http://example.com/").interactive(false)
+    }
+
+    #[test]
+    fn builds_request_urls_for_each_mode() {
+        let plain = config().build().unwrap();
+        let url = build_request_url(&plain, &base("http://example.com/app/"), "admin/").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/app/admin/");
+        assert!(build_request_url(&plain, &base("http://example.com/"), "http://other.com/").is_err());
+        let absolute = config().allow_absolute(true).build().unwrap();
+        let url = build_request_url(&absolute, &base("http://example.com/"), "http://other.com/").unwrap();
+        assert_eq!(url.as_str(), "http://other.com/");
+
+        // URL中的占位符直接替换，不再拼接路径
+        let url = build_request_url(&plain, &base("http://example.com/%WORD%.php?id=1"), "admin").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/admin.php?id=1");
+        let url = build_request_url(&plain, &base("http://example.com/?q=%WORD%"), "").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/?q=");
+
+        // 字典项按占位符所在的部分编码，? 和 # 不会改变URL的结构
+        let url = build_request_url(&plain, &base("http://example.com/%WORD%.php?id=1"), "a b?c#d").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/a%20b%3Fc%23d.php?id=1");
+        assert_eq!(url.query(), Some("id=1"));
+        let url = build_request_url(&plain, &base("http://example.com/?q=%WORD%"), "a b&x=1#top").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/?q=a%20b&x=1%23top");
+        assert_eq!(url.fragment(), None);
+        // 斜杠和已编码的字符保持原样
+        let url = build_request_url(&plain, &base("http://example.com/%WORD%"), "admin/a%2Fb").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/admin/a%2Fb");
+
+        // 占位符在请求头中时URL不变
+        let templated = config().header("X-Path", "%WORD%").build().unwrap();
+        let url = build_request_url(&templated, &base("http://example.com/api"), "admin").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/api");
+
+        let fuzz = config().param_fuzz(true).build().unwrap();
+        let url = build_request_url(&fuzz, &base("http://example.com/search?lang=en"), "debug").unwrap();
+        assert_eq!(url.as_str(), "http://example.com/search?lang=en&debug=test");
+    }
+
+    #[test]
+    fn classifies_statuses_before_reading_body() {
+        let status = |code| StatusCode::from_u16(code).unwrap();
+        let location = Some(base("http://example.com/login"));
+        let plain = config().build().unwrap();
+        let class = classify_status(&plain, status(200), None);
+        assert_eq!(class, StatusClass { reportable: true, protected: false, location: None });
+        let class = classify_status(&plain, status(403), None);
+        assert!(!class.reportable && !class.protected);
+        // 没有状态码规则时跳转交给后续的跳转检查
+        let class = classify_status(&plain, status(302), location.clone());
+        assert_eq!(class, StatusClass { reportable: false, protected: false, location: location.clone() });
+
+        let forbidden = config().show_forbidden(true).build().unwrap();
+        for code in [401, 403] {
+            assert_eq!(classify_status(&forbidden, status(code), None), StatusClass { reportable: true, protected: true, location: None });
+        }
+        assert!(!classify_status(&forbidden, status(404), None).reportable);
+
+        let matched = config().match_codes("200,403".parse().unwrap()).build().unwrap();
+        assert!(classify_status(&matched, status(403), None).reportable);
+        assert!(!classify_status(&matched, status(204), None).reportable);
+        // 不匹配规则的跳转同样忽略
+        assert_eq!(classify_status(&matched, status(302), location), StatusClass { reportable: false, protected: false, location: None });
+    }
+
+    #[test]
+    fn detects_technologies_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(SERVER, HeaderValue::from_static("nginx/1.18.0"));
+        headers.append("X-Powered-By", HeaderValue::from_static("PHP/7.4.3, Express"));
+        headers.append("X-Powered-By", HeaderValue::from_static("Express"));
+        headers.insert("X-AspNet-Version", HeaderValue::from_static("4.0.30319"));
+        assert_eq!(
+            detect_technologies(&headers),
+            ["nginx/1.18.0", "PHP/7.4.3", "Express", "ASP.NET 4.0.30319"]
+        );
+        assert!(detect_technologies(&HeaderMap::new()).is_empty());
+    }
+
+    /// 测试服务器收到的请求，每项为一个请求的请求行和请求头
+    type Requests = Arc<std::sync::Mutex<Vec<String>>>;
+
+    /// 启动测试用的HTTP服务器，`respond` 按请求路径给出完整的原始响应；返回目标URL和收到的请求
+    ///
+    /// 响应中带有 `Connection: close` 时写完即关闭连接，否则复用连接继续读取下一个请求
+    async fn mock_server(respond: impl Fn(&str) -> Vec<u8> + Send + Sync + 'static) -> (String, Requests) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let respond = Arc::new(respond);
+        let requests = Requests::default();
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let respond = Arc::clone(&respond);
+                let recorded = Arc::clone(&recorded);
+                tokio::spawn(async move {
+                    let mut buffer = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    while let Ok(n) = socket.read(&mut chunk).await {
+                        if n == 0 {
+                            break;
+                        }
+                        buffer.extend_from_slice(&chunk[..n]);
+                        while let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+                            let head = String::from_utf8_lossy(&buffer[..end]).to_string();
+                            buffer.drain(..end + 4);
+                            let response = respond(request_path(&head));
+                            recorded.lock().unwrap().push(head);
+                            let close = String::from_utf8_lossy(&response).to_lowercase().contains("\r\nconnection: close\r\n");
+                            if socket.write_all(&response).await.is_err() || close {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        (format!("http://{}/", addr), requests)
+    }
+
+    /// 请求行中的路径
+    fn request_path(head: &str) -> &str {
+        head.split(' ').nth(1).unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn shuffles_header_order_per_request() {
+        let (url, requests) = mock_server(|_| b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec()).await;
+        let config = ScanConfig::builder()
+            .url(url)
+            .threads(1)
+            .interactive(false)
+            .header("X-A", "1")
+            .header("X-B", "2")
+            .header("X-C", "3")
+            .header("X-D", "4")
+            .shuffle_headers(true)
+            .build()
+            .unwrap();
+        let paths = (0..20).map(|i| format!("path-{}", i)).collect();
+        Scanner::new(config).unwrap().run(paths).await.unwrap();
+
+        // 每个请求的请求头名称，保持发送时的顺序
+        let orders: Vec<Vec<String>> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|head| head.lines().skip(1).filter_map(|line| line.split_once(':')).map(|(name, _)| name.to_lowercase()).collect())
+            .collect();
+        assert!(orders.len() >= 20);
+        let custom: HashSet<Vec<String>> = orders
+            .iter()
+            .map(|names| names.iter().filter(|name| name.starts_with("x-")).cloned().collect())
+            .collect();
+        // 自行设置的请求头顺序可以打乱，Host 总是由hyper追加在最后
+        assert!(custom.len() > 1, "{:?}", custom);
+        assert!(orders.iter().all(|names| names.last().map(String::as_str) == Some("host")));
+    }
+
+    #[tokio::test]
+    async fn reports_bodies_shorter_than_content_length() {
+        // 声明100字节，只发送5字节就关闭连接
+        let (url, _) = mock_server(|_| b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\nshort".to_vec()).await;
+
+        let config = ScanConfig::builder()
+            .url(url)
+            .threads(1)
+            .interactive(false)
+            .build()
+            .unwrap();
+        let report = Scanner::new(config).unwrap().run(vec!["cut".to_string()]).await.unwrap();
+        assert_eq!(report.errors, 0);
+        let finding = report.findings.iter().find(|finding| finding.url.path() == "/cut").unwrap();
+        assert_eq!(finding.length_mismatch, Some((100, 5)));
+        assert_eq!(finding.size, 5);
+    }
+
+    #[tokio::test]
+    async fn truncates_only_bodies_over_the_cap() {
+        let (url, _) = mock_server(|path| match path {
+            "/exact" => b"HTTP/1.1 200 OK\r\nContent-Length: 14\r\n\r\n1234567890abcd".to_vec(),
+            "/long" => b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\n\r\n1234567890abcdefghij".to_vec(),
+            // 分块传输，命中内容特征后还有剩余的块
+            _ => b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nd\r\nAccess Denied\r\n5\r\nmore!\r\n0\r\n\r\n".to_vec(),
+        })
+        .await;
+        let rules = std::env::temp_dir().join(format!("rdirscan-early-signature-{}.txt", std::process::id()));
+        std::fs::write(&rules, "Access Denied\n").unwrap();
+        let config = ScanConfig::builder()
+            .url(url)
+            .threads(1)
+            .interactive(false)
+            .max_body_size(14)
+            .filter_file(rules.to_str().unwrap())
+            .build()
+            .unwrap();
+        let report = Scanner::new(config).unwrap().run(vec!["exact".into(), "long".into(), "denied".into()]).await.unwrap();
+        std::fs::remove_file(&rules).unwrap();
+        let finding = |path: &str| report.findings.iter().find(|finding| finding.url.path() == path).unwrap();
+
+        // 恰好读到上限时连接上已没有剩余内容，不算截断
+        assert!(!finding("/exact").truncated);
+        assert_eq!(finding("/long").size, 20);
+        assert!(finding("/long").truncated);
+        // 命中内容特征提前停止读取不算截断
+        let denied = finding("/denied");
+        assert_eq!(denied.reason, Some(FilterReason::Signature("Access Denied".into())));
+        assert!(!denied.truncated);
+    }
+
+    /// 把除 `/a` 以外的请求都跳转到 `location` 加上原路径的服务器，`/a` 返回404
+    async fn upgrade_server(location: &'static str) -> (String, Requests) {
+        mock_server(move |path| match path {
+            "/a" => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec(),
+            _ => format!("HTTP/1.1 301 Moved Permanently\r\nLocation: {}{}\r\nContent-Length: 0\r\n\r\n", location, path).into_bytes(),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn upgrades_to_https_only_when_requested() {
+        // 非交互模式下没有 --auto-upgrade 时不探测也不改用https
+        let (url, requests) = upgrade_server("https://127.0.0.1:8443").await;
+        let config = ScanConfig::builder()
+            .url(url)
+            .threads(1)
+            .interactive(false)
+            .build()
+            .unwrap();
+        Scanner::new(config).unwrap().run(vec!["a".to_string()]).await.unwrap();
+        let paths: Vec<_> = requests.lock().unwrap().iter().map(|head| request_path(head).to_string()).collect();
+        assert_eq!(paths, ["/a"]);
+    }
+
+    #[tokio::test]
+    async fn detects_https_upgrades_from_the_location() {
+        let detect = |location| async move {
+            let (url, _) = upgrade_server(location).await;
+            let base = base(&format!("{}app/", url));
+            // 不跟随跳转时按 Location 判断，不必真的连接到https端口
+            let config = ScanConfig::builder().url(base.as_str()).interactive(false).follow_redirects(false).build().unwrap();
+            Scanner::new(config).unwrap().detect_https_upgrade(&base).await
+        };
+        // 协议和端口取自跳转目标，路径保持不变
+        let upgraded = detect("https://127.0.0.1:8443").await.unwrap();
+        assert_eq!(upgraded.as_str(), "https://127.0.0.1:8443/app/");
+        let upgraded = detect("https://127.0.0.1").await.unwrap();
+        assert_eq!(upgraded.as_str(), "https://127.0.0.1/app/");
+        // 跳到其他主机或仍是http时不改用
+        assert_eq!(detect("https://example.com").await, None);
+        assert_eq!(detect("http://127.0.0.1:8080").await, None);
+    }
+
+    #[tokio::test]
+    async fn stops_recursing_at_the_depth_limit() {
+        // 以斜杠结尾的路径都是目录，其余返回404
+        let (url, requests) = mock_server(|path| {
+            if path.ends_with('/') && path != "/" {
+                b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\ndir".to_vec()
+            } else {
+                b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec()
+            }
+        })
+        .await;
+
+        let config = ScanConfig::builder()
+            .url(url)
+            .threads(2)
+            .interactive(false)
+            .recursion_depth(2)
+            .recursion_paths(vec!["x/".to_string()])
+            .build()
+            .unwrap();
+        let report = Scanner::new(config).unwrap().run(vec!["a/".to_string()]).await.unwrap();
+        let mut found: Vec<_> = report.findings.iter().map(|finding| finding.url.path().to_string()).collect();
+        found.sort();
+        assert_eq!(found, ["/a/", "/a/x/", "/a/x/x/"]);
+        assert!(!requests.lock().unwrap().iter().any(|head| request_path(head).starts_with("/a/x/x/x")));
+    }
+
+    #[tokio::test]
+    async fn reports_hosts_without_addresses() {
+        let config = ScanConfig::builder().url("https://empty.example/").interactive(false).build().unwrap();
+        let mut scanner = Scanner::new(config).unwrap();
+        scanner.resolver = Arc::new(CachingResolver::default().with_addresses("empty.example", &[]));
+        let err = scanner.certificate().await.unwrap_err().to_string();
+        assert!(err.starts_with("解析主机失败"), "{}", err);
+    }
+
+    /// 未跳转、没有内容特征的响应，是否可报告按状态码默认规则
+    fn view(url: &Url, status: u16, size: usize) -> ResponseView<'_> {
+        ResponseView {
+            url,
+            path: url.path(),
+            status: StatusCode::from_u16(status).unwrap(),
+            reportable: (200..300).contains(&status),
+            location: None,
+            content: None,
+            size,
+            title: None,
+            early_signature: None,
+            partial_size: false,
+            redirect_loop: false,
+        }
+    }
+
+    fn scan_state() -> ScanState {
+        let mut state = ScanState::new();
+        state.set_interactive(false);
+        state
+    }
+
+    fn reason(classification: Classification) -> Option<FilterReason> {
+        match classification {
+            Classification::Reported(verdict) => verdict.reason,
+            Classification::Ignored(decision) => panic!("响应被忽略: {}", decision),
+        }
+    }
+
+    #[tokio::test]
+    async fn classifies_responses_by_status_and_content() {
+        let plain = config().build().unwrap();
+        let mut state = scan_state();
+        let admin = base("http://example.com/admin");
+        let Classification::Reported(verdict) = classify_response(&plain, &mut state, view(&admin, 200, 10)).await else {
+            panic!("200响应应当报告");
+        };
+        assert_eq!(verdict.reason, None);
+        assert!(!verdict.sensitive && !verdict.listing && !verdict.outlier);
+        assert_eq!(classify_response(&plain, &mut state, view(&admin, 404, 10)).await, Classification::Ignored("忽略"));
+
+        let signed = ResponseView { early_signature: Some("Access Denied".into()), ..view(&admin, 200, 10) };
+        assert_eq!(reason(classify_response(&plain, &mut state, signed).await), Some(FilterReason::Signature("Access Denied".into())));
+
+        // 提前停止读取、大小不完整的响应不参与大小统计
+        let outliers = config().outlier_threshold(10).build().unwrap();
+        let mut state = scan_state();
+        for _ in 0..5 {
+            let partial = ResponseView { early_signature: Some("Access Denied".into()), partial_size: true, ..view(&admin, 200, 100) };
+            classify_response(&outliers, &mut state, partial).await;
+        }
+        let Classification::Reported(verdict) = classify_response(&outliers, &mut state, view(&admin, 200, 1000)).await else { panic!() };
+        assert!(!verdict.outlier);
+
+        let files = base("http://example.com/files/");
+        let listing = ResponseView { content: Some("<html><title>Index of /files</title>"), ..view(&files, 200, 300) };
+        let Classification::Reported(verdict) = classify_response(&plain, &mut state, listing).await else { panic!() };
+        assert!(verdict.listing);
+
+        // 敏感扩展名只在200且非空时成立，严重性至少为高
+        let backup = base("http://example.com/site.bak");
+        let Classification::Reported(verdict) = classify_response(&plain, &mut state, view(&backup, 200, 5)).await else { panic!() };
+        assert!(verdict.sensitive && verdict.severity >= Severity::High);
+        let Classification::Reported(verdict) = classify_response(&plain, &mut state, view(&backup, 200, 0)).await else { panic!() };
+        assert!(!verdict.sensitive);
+    }
+
+    #[tokio::test]
+    async fn classifies_redirects() {
+        let url = base("http://example.com/admin");
+        let root = base("http://example.com/");
+        let redirect = |redirect_loop| ResponseView { location: Some(&root), redirect_loop, ..view(&url, 302, 0) };
+
+        // 跟随跳转时仍拿到的3xx
+        let following = config().build().unwrap();
+        let mut state = scan_state();
+        assert_eq!(reason(classify_response(&following, &mut state, redirect(true)).await), Some(FilterReason::RedirectLoop));
+        assert_eq!(reason(classify_response(&following, &mut state, redirect(false)).await), Some(FilterReason::TooManyRedirects));
+
+        let manual = config().follow_redirects(false).build().unwrap();
+        assert_eq!(reason(classify_response(&manual, &mut state, redirect(false)).await), Some(FilterReason::RootRedirect));
+    }
+
+    #[tokio::test]
+    async fn applies_configured_filters() {
+        let url = base("http://example.com/page");
+
+        let smart = config().smart_match(10).build().unwrap();
+        let mut state = scan_state();
+        let baseline = |status, size| Baseline { status: StatusCode::from_u16(status).unwrap(), size, spread: 0 };
+        state.set_baselines(baseline(404, 100), Some(baseline(200, 5000)));
+        assert_eq!(classify_response(&smart, &mut state, view(&url, 404, 105)).await, Classification::Ignored("忽略: 与基线一致"));
+        assert_eq!(reason(classify_response(&smart, &mut state, view(&url, 200, 5003)).await), Some(FilterReason::Homepage));
+
+        let small = config().min_content_length(20).build().unwrap();
+        assert_eq!(reason(classify_response(&small, &mut scan_state(), view(&url, 200, 5)).await), Some(FilterReason::TooSmall(20)));
+
+        let severe = config().min_severity(Severity::High).build().unwrap();
+        assert_eq!(
+            reason(classify_response(&severe, &mut scan_state(), view(&url, 200, 50)).await),
+            Some(FilterReason::BelowSeverity(Severity::Info))
+        );
+
+        // 被其他规则过滤的响应不占用标题
+        let unique = config().unique_titles(true).min_content_length(20).build().unwrap();
+        let mut state = scan_state();
+        let titled = |size| ResponseView { title: Some("Admin"), ..view(&url, 200, size) };
+        assert_eq!(reason(classify_response(&unique, &mut state, titled(5)).await), Some(FilterReason::TooSmall(20)));
+        assert_eq!(reason(classify_response(&unique, &mut state, titled(50)).await), None);
+        assert_eq!(reason(classify_response(&unique, &mut state, titled(60)).await), Some(FilterReason::DuplicateTitle("Admin".into())));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::io::Cursor;
+
+use anyhow::{ensure, Result};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use url::Url;
+
+use crate::config::{validate_url, with_port};
+use crate::dict;
+use crate::scanner::join_entry;
+use crate::state::parse_filter_rules;
+use crate::template::OutputTemplate;
+use crate::ua::{get_random_headers, get_random_user_agent};
+
+/// 一项自检，未通过时返回说明原因的错误
+type Check = fn() -> Result<()>;
+
+/// 一项自检的结果
+#[derive(Debug, Clone)]
+pub struct SelftestCheck {
+    pub name: &'static str,
+    /// 未通过时的原因
+    pub error: Option<String>,
+}
+
+impl SelftestCheck {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// `rdirscan selftest`：不访问网络，逐项检查URL校验、过滤规则解析、路径拼接、字典解析、UA随机性等内部逻辑
+///
+/// 用于确认当前编译出的程序工作正常，报告问题时可附上其输出
+pub fn run_selftest() -> Vec<SelftestCheck> {
+    let checks: &[(&'static str, Check)] = &[
+        ("URL校验", check_url_validation),
+        ("过滤规则解析", check_filter_rules),
+        ("路径拼接", check_path_join),
+        ("字典解析", check_dictionary),
+        ("UA随机性", check_user_agents),
+        ("输出模板", check_output_template),
+    ];
+    checks
+        .iter()
+        .map(|(name, check)| SelftestCheck { name, error: check().err().map(|e| e.to_string()) })
+        .collect()
+}
+
+fn check_url_validation() -> Result<()> {
+    ensure!(validate_url("http://example.com")? == "http://example.com/", "没有路径的URL应补上 /");
+    ensure!(validate_url("http://[::1]:8080/")? == "http://[::1]:8080/", "IPv6地址应保持不变");
+    ensure!(validate_url("http://example.com:80/")? == "http://example.com/", "默认端口应被去掉");
+    ensure!(validate_url("example.com").is_err(), "缺少协议的URL应被拒绝");
+    ensure!(validate_url("ftp://example.com/").is_err(), "非http协议应被拒绝");
+    ensure!(validate_url("http://::1:8080/").is_err_and(|e| e.to_string().contains("方括号")), "未加方括号的IPv6地址应提示方括号");
+    ensure!(validate_url("http://example.com:65536/").is_err_and(|e| e.to_string().contains("0-65535")), "超出范围的端口应被拒绝");
+    ensure!(with_port("http://example.com/", 8443, None)? == "https://example.com:8443/", "8443端口应改用https");
+    Ok(())
+}
+
+fn check_filter_rules() -> Result<()> {
+    let content = "# 注释\n\n  页面不存在  \n#!size 1234\nsize:abc\n";
+    let (signatures, sizes) = parse_filter_rules(Cursor::new(content))?;
+    let expected: HashSet<String> = ["页面不存在", "size:abc"].iter().map(|s| s.to_string()).collect();
+    ensure!(signatures == expected, "内容特征解析错误: {:?}", signatures);
+    ensure!(sizes == HashSet::from([1234]), "响应大小解析错误: {:?}", sizes);
+    Ok(())
+}
+
+fn check_path_join() -> Result<()> {
+    let join = |base: &str, entry: &str| -> Result<String> {
+        Ok(join_entry(&Url::parse(base)?, entry, false)?.to_string())
+    };
+    ensure!(join("http://example.com/", "admin")? == "http://example.com/admin", "根目录拼接错误");
+    ensure!(join("http://example.com/app", "admin")? == "http://example.com/app/admin", "没有结尾斜杠的目标路径应视为目录");
+    ensure!(join("http://example.com/app/", "/admin")? == "http://example.com/admin", "以 / 开头的字典项应从根目录开始");
+    ensure!(join("http://example.com/index.php", "?debug=1")? == "http://example.com/index.php?debug=1", "查询字符串应作用于目标URL本身");
+    ensure!(join("http://example.com/", "http://other.com/").is_err(), "完整URL的字典项默认应被拒绝");
+    Ok(())
+}
+
+fn check_dictionary() -> Result<()> {
+    let lines = ["# 注释", "", "admin   # 管理后台", "page#top", "backup%NUM:1-3%.zip"];
+    let paths = dict::parse_lines(lines.iter().map(|line| line.to_string()))?;
+    ensure!(
+        paths == ["admin", "page#top", "backup1.zip", "backup2.zip", "backup3.zip"],
+        "字典解析结果错误: {:?}",
+        paths
+    );
+    ensure!(dict::expand_numeric_ranges("a%NUM:08-10%")? == ["a08", "a09", "a10"], "数字范围应按起始值补零");
+    ensure!(!dict::builtin_dictionary().is_empty(), "内置字典为空");
+    let mut entries = ["/logout", "Logout/", "admin/shutdown.php", "admin"].map(String::from).to_vec();
+    let excluded = dict::exclude_entries(&mut entries, &["logout".to_string(), "*shutdown*".to_string()]);
+    ensure!(excluded == 3 && entries == ["admin"], "排除列表匹配错误: {:?}", entries);
+    Ok(())
+}
+
+fn check_user_agents() -> Result<()> {
+    let mut rng = StdRng::seed_from_u64(1);
+    let agents: HashSet<&str> = (0..100).map(|_| get_random_user_agent(&mut rng)).collect();
+    ensure!(agents.len() > 10, "100次随机只选出了 {} 种UA", agents.len());
+    let draw = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..10).map(|_| get_random_user_agent(&mut rng)).collect::<Vec<_>>()
+    };
+    ensure!(draw(7) == draw(7), "相同种子应得到相同的UA序列");
+    let firefox = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:89.0) Gecko/20100101 Firefox/89.0";
+    let headers = get_random_headers(firefox, &mut rng);
+    ensure!(headers.accept.starts_with("text/html") && !headers.accept_language.is_empty(), "UA配套的请求头不完整");
+    Ok(())
+}
+
+fn check_output_template() -> Result<()> {
+    ensure!("{status}\\t{url}".parse::<OutputTemplate>().is_ok(), "有效的模板被拒绝");
+    ensure!("{status} {length}".parse::<OutputTemplate>().is_err(), "未知占位符应被拒绝");
+    ensure!("{url".parse::<OutputTemplate>().is_err(), "未闭合的花括号应被拒绝");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_checks_pass() {
+        for check in run_selftest() {
+            assert!(check.passed(), "{}: {:?}", check.name, check.error);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use colored::*;
+
+/// 发现的严重性，用于着色、排序和 `--min-severity` 过滤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Severity {
+    #[default]
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// 路径中出现这些关键字时推断的严重性，按从高到低的顺序匹配
+///
+/// 关键字按单词边界匹配（见 [`contains_word`]），`log` 不匹配 `blog`，`.sql` 不匹配 `.sqlite`
+const KEYWORDS: &[(Severity, &[&str])] = &[
+    (Severity::Critical, &[".git", ".svn", ".hg", ".env", ".htpasswd", "id_rsa", "id_dsa", "wp-config"]),
+    (Severity::High, &["backup", "backups", "dump", ".sql", ".bak", "config", "configuration", "passwd", "secret", "secrets", "credential", "credentials", ".key", ".pem"]),
+    (Severity::Medium, &["admin", "administrator", "manager", "console", "debug", "phpinfo", "swagger", "actuator", ".ds_store", "phpmyadmin"]),
+    (Severity::Low, &["api", "test", "tests", "upload", "uploads", "log", "logs", "old", "tmp"]),
+];
+
+impl Severity {
+    /// 根据路径中的关键字推断严重性，没有命中时为 `Info`
+    pub fn infer(path: &str) -> Self {
+        let path = path.to_lowercase();
+        KEYWORDS
+            .iter()
+            .find(|(_, words)| words.iter().any(|word| contains_word(&path, word)))
+            .map_or(Severity::Info, |(severity, _)| *severity)
+    }
+
+    /// 终端中显示的彩色标签，`Info` 不显示
+    pub(crate) fn label(self) -> Option<ColoredString> {
+        let text = format!("[{}]", self);
+        match self {
+            Severity::Critical => Some(text.white().on_red().bold()),
+            Severity::High => Some(text.red().bold()),
+            Severity::Medium => Some(text.yellow().bold()),
+            Severity::Low => Some(text.blue()),
+            Severity::Info => None,
+        }
+    }
+}
+
+/// `path` 中是否有完整出现的 `word`：关键字首尾是字母或数字时，相邻的字符不能也是字母或数字
+///
+/// 以 `.` 开头的关键字（如扩展名 `.sql`）前面可以紧接文件名
+fn contains_word(path: &str, word: &str) -> bool {
+    let bounded = |keyword_edge: Option<char>, neighbour: Option<char>| {
+        !keyword_edge.is_some_and(|c| c.is_alphanumeric()) || !neighbour.is_some_and(|c| c.is_alphanumeric())
+    };
+    path.match_indices(word).any(|(start, _)| {
+        let before = path[..start].chars().next_back();
+        let after = path[start + word.len()..].chars().next();
+        bounded(word.chars().next(), before) && bounded(word.chars().next_back(), after)
+    })
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(anyhow!("不支持的严重性 '{}'，可选 info、low、medium、high、critical", s)),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "INFO"),
+            Severity::Low => write!(f, "LOW"),
+            Severity::Medium => write!(f, "MEDIUM"),
+            Severity::High => write!(f, "HIGH"),
+            Severity::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_severity_from_keywords() {
+        assert_eq!(Severity::infer("/.git/config"), Severity::Critical);
+        assert_eq!(Severity::infer("wp-config.php.bak"), Severity::Critical);
+        assert_eq!(Severity::infer("db_backup_2020.sql"), Severity::High);
+        assert_eq!(Severity::infer("Admin/index.php"), Severity::Medium);
+        assert_eq!(Severity::infer("api/v1/users"), Severity::Low);
+        assert_eq!(Severity::infer("logs/"), Severity::Low);
+        assert_eq!(Severity::infer("index.html"), Severity::Info);
+    }
+
+    #[test]
+    fn ignores_keywords_inside_words() {
+        for path in ["blog", "rapid", "folder", "latest", "catalog.php", "data.sqlite", "gold/", "contest"] {
+            assert_eq!(Severity::infer(path), Severity::Info, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use reqwest::Method;
+use sha2::Sha256;
+use url::{Position, Url};
+
+use crate::template::{parse_placeholders, Part};
+
+/// 未指定 `--sign-header` 时签名所在的请求头
+pub const DEFAULT_SIGN_HEADER: &str = "X-Signature";
+/// 未指定 `--sign-timestamp-header` 时时间戳所在的请求头
+pub const DEFAULT_TIMESTAMP_HEADER: &str = "X-Timestamp";
+/// 未指定 `--sign-template` 时的签名内容：方法、路径和时间戳，各占一行
+pub const DEFAULT_SIGN_TEMPLATE: &str = "{method}\\n{path}\\n{timestamp}";
+
+/// 签名模板支持的占位符
+const PLACEHOLDERS: &[(&str, Field)] = &[
+    ("method", Field::Method),
+    ("path", Field::Path),
+    ("host", Field::Host),
+    ("timestamp", Field::Timestamp),
+    ("body", Field::Body),
+];
+
+/// `--sign-template` 描述的待签名内容，如 `{method}\n{path}\n{timestamp}`
+///
+/// 占位符为 `{method}`、`{path}`（含查询字符串）、`{host}`、`{timestamp}`（Unix秒数）和 `{body}`，
+/// 字面的花括号写作 `{{`、`}}`，`\n`、`\t` 表示换行和制表符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignTemplate {
+    spec: String,
+    parts: Vec<Part<Field>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Method,
+    Path,
+    Host,
+    Timestamp,
+    Body,
+}
+
+impl SignTemplate {
+    fn uses_timestamp(&self) -> bool {
+        self.parts.contains(&Part::Field(Field::Timestamp))
+    }
+
+    fn render(&self, method: &Method, url: &Url, timestamp: &str, body: Option<&str>) -> String {
+        let mut text = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(literal) => text.push_str(literal),
+                Part::Field(Field::Method) => text.push_str(method.as_str()),
+                Part::Field(Field::Path) => text.push_str(&url[Position::BeforePath..Position::AfterQuery]),
+                Part::Field(Field::Host) => text.push_str(&url[Position::BeforeHost..Position::AfterPort]),
+                Part::Field(Field::Timestamp) => text.push_str(timestamp),
+                Part::Field(Field::Body) => text.push_str(body.unwrap_or_default()),
+            }
+        }
+        text
+    }
+}
+
+impl Default for SignTemplate {
+    fn default() -> Self {
+        DEFAULT_SIGN_TEMPLATE.parse().expect("默认签名模板有效")
+    }
+}
+
+impl FromStr for SignTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let parts = parse_placeholders("签名模板", spec, PLACEHOLDERS, &[('n', '\n'), ('t', '\t')])?;
+        Ok(Self { spec: spec.to_string(), parts })
+    }
+}
+
+impl fmt::Display for SignTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+/// 请求签名：按模板拼出待签名内容，用密钥计算 HMAC-SHA256，以小写十六进制放入签名头
+///
+/// 模板用到 `{timestamp}` 时同时发送时间戳头，网关据此重新计算签名并检查请求是否过期
+pub(crate) struct RequestSigner {
+    secret: Vec<u8>,
+    header: String,
+    timestamp_header: String,
+    template: SignTemplate,
+}
+
+impl RequestSigner {
+    pub(crate) fn new(secret: &str, header: &str, timestamp_header: &str, template: SignTemplate) -> Self {
+        Self {
+            secret: secret.as_bytes().to_vec(),
+            header: header.to_string(),
+            timestamp_header: timestamp_header.to_string(),
+            template,
+        }
+    }
+
+    /// 为一个请求生成签名相关的请求头，每个请求使用当前时间重新计算
+    pub(crate) fn headers(&self, method: &Method, url: &Url, body: Option<&str>) -> Vec<(&str, String)> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .to_string();
+        let signature = self.sign(&self.template.render(method, url, &timestamp, body));
+        let mut headers = vec![(self.header.as_str(), signature)];
+        if self.template.uses_timestamp() {
+            headers.push((self.timestamp_header.as_str(), timestamp));
+        }
+        headers
+    }
+
+    fn sign(&self, text: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC接受任意长度的密钥");
+        mac.update(text.as_bytes());
+        mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_rendered_template() {
+        // RFC 4231 测试用例2
+        let signer = RequestSigner::new("Jefe", DEFAULT_SIGN_HEADER, DEFAULT_TIMESTAMP_HEADER, SignTemplate::default());
+        assert_eq!(
+            signer.sign("what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let url = Url::parse("https://api.example.com:8443/v1/users?id=1").unwrap();
+        let template: SignTemplate = "{method} {host}{path}\\n{{{body}}}".parse().unwrap();
+        assert!(!template.uses_timestamp());
+        assert_eq!(
+            template.render(&Method::POST, &url, "0", Some("a=1")),
+            "POST api.example.com:8443/v1/users?id=1\n{a=1}"
+        );
+        assert_eq!(SignTemplate::default().render(&Method::GET, &url, "1700000000", None), "GET\n/v1/users?id=1\n1700000000");
+
+        assert!("{method} {uri}".parse::<SignTemplate>().is_err());
+        assert!("static".parse::<SignTemplate>().is_err());
+    }
+}
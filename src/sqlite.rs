@@ -0,0 +1,262 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+use crate::config::ScanConfig;
+use crate::output::OutputSink;
+
+/// 检查当前版本是否支持SQLite输出，在配置校验时调用
+#[cfg(feature = "sqlite")]
+pub(crate) fn check_available() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub(crate) fn check_available() -> Result<()> {
+    Err(anyhow!("当前版本未包含SQLite输出功能，请使用 cargo build --features sqlite 重新编译"))
+}
+
+/// 打开（必要时创建）数据库并插入本次扫描的记录，之后的发现都关联到这条记录
+#[cfg(feature = "sqlite")]
+pub(crate) fn create_sink(path: &Path, config: &ScanConfig) -> Result<Box<dyn OutputSink>> {
+    Ok(Box::new(SqliteSink::open(path, config)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub(crate) fn create_sink(_path: &Path, _config: &ScanConfig) -> Result<Box<dyn OutputSink>> {
+    Err(anyhow!("当前版本未包含SQLite输出功能，请使用 cargo build --features sqlite 重新编译"))
+}
+
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS scans (
+        id INTEGER PRIMARY KEY,
+        target TEXT,
+        started_at TEXT NOT NULL,
+        finished_at TEXT,
+        config TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS findings (
+        id INTEGER PRIMARY KEY,
+        scan_id INTEGER NOT NULL REFERENCES scans(id),
+        url TEXT NOT NULL,
+        status INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        content_type TEXT,
+        redirect TEXT,
+        final_url TEXT,
+        severity TEXT NOT NULL,
+        word TEXT,
+        title TEXT,
+        found_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS technologies (
+        finding_id INTEGER NOT NULL REFERENCES findings(id),
+        name TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS findings_scan ON findings(scan_id);
+    CREATE INDEX IF NOT EXISTS findings_url ON findings(url);
+    CREATE INDEX IF NOT EXISTS technologies_finding ON technologies(finding_id);
+";
+
+/// SQLite输出：`scans` 表每次扫描一行，`findings` 表每条发现一行，`technologies` 表记录发现的技术指纹
+///
+/// 数据库在专用的写入线程中操作，遇到其他任务持有写锁时只有该线程等待，不会阻塞扫描。
+/// 每条发现立即写入（不在整个扫描期间占用写锁），扫描中断时已写入的发现仍然保留，`finished_at` 为空
+#[cfg(feature = "sqlite")]
+pub(crate) struct SqliteSink {
+    commands: Option<std::sync::mpsc::Sender<Command>>,
+    writer: Option<std::thread::JoinHandle<Result<()>>>,
+}
+
+#[cfg(feature = "sqlite")]
+enum Command {
+    Finding(Box<crate::finding::Finding>),
+    Finish,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSink {
+    /// 打开数据库、插入扫描记录后启动写入线程；可能等待写锁，在异步代码中应通过 `spawn_blocking` 调用
+    pub(crate) fn open(path: &Path, config: &ScanConfig) -> Result<Self> {
+        let connection = rusqlite::Connection::open(path)
+            .map_err(|e| anyhow!("打开SQLite数据库 {} 失败: {}", path.display(), e))?;
+        // 任务文件中的多个任务可能同时写入同一个数据库，遇到写锁时等待而不是立即失败
+        connection.busy_timeout(std::time::Duration::from_secs(30))
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .map_err(|e| anyhow!("初始化SQLite数据库 {} 失败: {}", path.display(), e))?;
+        connection
+            .execute(
+                "INSERT INTO scans (target, started_at, config) VALUES (?1, ?2, ?3)",
+                rusqlite::params![config.base_url(), now_iso(), config.to_json().to_string()],
+            )
+            .map_err(|e| anyhow!("写入扫描记录到SQLite数据库失败: {}", e))?;
+        let scan_id = connection.last_insert_rowid();
+        let (commands, received) = std::sync::mpsc::channel();
+        let writer = std::thread::spawn(move || write_all(&connection, scan_id, received));
+        Ok(Self { commands: Some(commands), writer: Some(writer) })
+    }
+
+    /// 等待写入线程处理完已提交的发现，返回它遇到的第一个错误
+    fn join(&mut self) -> Result<()> {
+        self.commands = None;
+        match self.writer.take() {
+            Some(writer) => writer.join().map_err(|_| anyhow!("SQLite写入线程异常退出"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+/// 写入线程：依次写入收到的发现，遇到错误时停止；收到结束命令时记录结束时间，
+/// 命令通道关闭（扫描出错提前返回）时不记录，与中断的扫描一致
+#[cfg(feature = "sqlite")]
+fn write_all(connection: &rusqlite::Connection, scan_id: i64, commands: std::sync::mpsc::Receiver<Command>) -> Result<()> {
+    for command in commands {
+        match command {
+            Command::Finding(finding) => insert_finding(connection, scan_id, &finding)?,
+            Command::Finish => {
+                connection
+                    .execute("UPDATE scans SET finished_at = ?1 WHERE id = ?2", rusqlite::params![now_iso(), scan_id])
+                    .map_err(|e| anyhow!("写入扫描记录到SQLite数据库失败: {}", e))?;
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn insert_finding(connection: &rusqlite::Connection, scan_id: i64, finding: &crate::finding::Finding) -> Result<()> {
+    connection.execute(
+        "INSERT INTO findings (scan_id, url, status, size, content_type, redirect, final_url, severity, word, title, found_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            scan_id,
+            finding.url.as_str(),
+            finding.status.as_u16(),
+            finding.size as i64,
+            finding.content_type,
+            finding.redirect.as_ref().map(|url| url.as_str()),
+            finding.final_url.as_ref().map(|url| url.as_str()),
+            finding.severity.to_string(),
+            finding.word,
+            finding.title,
+            finding.timestamp_iso(),
+        ],
+    )
+    .map_err(|e| anyhow!("写入发现到SQLite数据库失败: {}", e))?;
+    let finding_id = connection.last_insert_rowid();
+    for name in &finding.technologies {
+        connection
+            .execute("INSERT INTO technologies (finding_id, name) VALUES (?1, ?2)", rusqlite::params![finding_id, name])
+            .map_err(|e| anyhow!("写入发现到SQLite数据库失败: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+impl OutputSink for SqliteSink {
+    /// 把发现交给写入线程后立即返回；写入线程已因错误退出时返回该错误
+    fn write_finding(&mut self, finding: &crate::finding::Finding) -> Result<()> {
+        let sent = self.commands.as_ref()
+            .is_some_and(|commands| commands.send(Command::Finding(Box::new(finding.clone()))).is_ok());
+        if sent {
+            return Ok(());
+        }
+        self.join()?;
+        Err(anyhow!("SQLite写入线程已经结束"))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(ref commands) = self.commands {
+            let _ = commands.send(Command::Finish);
+        }
+        self.join()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Drop for SqliteSink {
+    /// 没有调用 `finish` 就结束时（扫描出错）也等待已提交的发现写完，不记录结束时间
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+/// 当前时间，ISO-8601格式（UTC，精确到毫秒），与发现的时间格式一致
+#[cfg(feature = "sqlite")]
+fn now_iso() -> String {
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+
+    let now = OffsetDateTime::now_utc();
+    now.replace_nanosecond(now.millisecond() as u32 * 1_000_000).unwrap_or(now).format(&Rfc3339).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sqlite")]
+    fn database(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rdirscan-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn writes_scans_and_findings() {
+        use crate::finding::Finding;
+
+        let path = database("writes");
+        let config = ScanConfig::builder().url("http://example.com/").interactive(false).build().unwrap();
+        let mut sink = create_sink(&path, &config).unwrap();
+        let mut finding = Finding::sample("http://example.com/admin", 200, 10);
+        finding.technologies = vec!["nginx".into(), "PHP/8.1".into()];
+        sink.write_finding(&finding).unwrap();
+        sink.write_finding(&Finding::sample("http://example.com/api", 403, 0)).unwrap();
+        sink.finish().unwrap();
+        // 已存在的数据库追加新的扫描记录
+        let mut sink = create_sink(&path, &config).unwrap();
+        sink.write_finding(&Finding::sample("http://example.com/old", 200, 1)).unwrap();
+        drop(sink);
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let count = |sql: &str| connection.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM scans"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM findings WHERE scan_id = 1"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM technologies"), 2);
+        // 没有调用 finish 的扫描已提交的发现仍然写入，但没有结束时间
+        assert_eq!(count("SELECT COUNT(*) FROM findings WHERE scan_id = 2"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM scans WHERE finished_at IS NULL"), 1);
+        drop(connection);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn reports_write_errors() {
+        use crate::finding::Finding;
+
+        let path = database("errors");
+        let config = ScanConfig::builder().url("http://example.com/").interactive(false).build().unwrap();
+        let mut sink = create_sink(&path, &config).unwrap();
+        rusqlite::Connection::open(&path).unwrap().execute_batch("DROP TABLE findings").unwrap();
+        // 写入在后台进行，错误在之后的写入或结束时返回
+        let _ = sink.write_finding(&Finding::sample("http://example.com/admin", 200, 10));
+        let err = sink.finish().unwrap_err().to_string();
+        assert!(err.starts_with("写入发现到SQLite数据库失败"), "{}", err);
+        drop(sink);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn requires_the_sqlite_feature() {
+        let config = ScanConfig::builder().url("http://example.com/").interactive(false).build().unwrap();
+        let err = create_sink(Path::new("scans.db"), &config).err().unwrap().to_string();
+        assert!(err.contains("--features sqlite"), "{}", err);
+        assert!(check_available().is_err());
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::{anyhow, Result};
+use colored::*;
+use reqwest::StatusCode;
+use url::Url;
+
+use crate::baseline::Baseline;
+use crate::console;
+use crate::finding::FilterReason;
+
+/// 相同响应大小出现多少次后询问是否过滤的默认值
+pub const DEFAULT_FILTER_THRESHOLD: usize = 5;
+
+/// 过滤规则文件中表示响应大小的行前缀，如 `#!size 1234`
+///
+/// 以 `#` 开头的行在旧版本中都是注释，这样的写法不会改变已有规则文件中内容特征的含义
+const SIZE_PREFIX: &str = "#!size ";
+
+/// 登录页标题中常见的关键字（匹配时忽略大小写）
+const LOGIN_TITLE_KEYWORDS: &[&str] = &["登录", "登陆", "login", "log in", "sign in", "signin", "统一身份认证"];
+
+/// 同一登录标题至少占有标题响应的比例才询问是否过滤
+const LOGIN_TITLE_RATIO: f64 = 0.5;
+
+/// 跳转目标中常见的登录页路径关键字，按路径段中的单词匹配
+const LOGIN_KEYWORDS: &[&str] = &["login", "signin", "logon", "sso", "passport"];
+
+#[derive(Clone)]
+pub(crate) struct ScanState {
+    // 所有目标共享同一份内容特征，收到 SIGHUP 时整体替换
+    content_signatures: Arc<RwLock<HashSet<String>>>,
+    // 记录响应大小和连续出现次数
+    size_counter: HashMap<usize, usize>,
+    // 记录已确认为WAF/登录页面的响应大小
+    filtered_sizes: HashSet<usize>,
+    // 所有目标确认过滤（或从规则文件读入）的响应大小，供 --save-filters 保存
+    confirmed_sizes: Arc<Mutex<BTreeSet<usize>>>,
+    // 记录跳转目标和出现次数
+    redirect_counter: HashMap<String, usize>,
+    // 记录已确认需要过滤的跳转目标
+    filtered_redirects: HashSet<String>,
+    // 异常值模式下记录所有响应（不区分状态码）的大小分布
+    response_sizes: HashMap<usize, usize>,
+    // 为 false 时不询问用户，自动确认过滤
+    interactive: bool,
+    // 相同响应大小出现多少次后询问是否过滤
+    size_threshold: usize,
+    // 记录响应标题和出现次数
+    title_counter: HashMap<String, usize>,
+    // 有标题的响应总数
+    titled_responses: usize,
+    // 已确认为统一登录页的标题
+    filtered_titles: HashSet<String>,
+    // --unique-titles 模式下已报告过的标题（规范化后）
+    seen_titles: HashSet<String>,
+    // 智能匹配模式下校准得到的404基线
+    soft_404: Option<Baseline>,
+    // 智能匹配模式下目标URL本身的响应，用于识别回显首页的路径
+    homepage: Option<Baseline>,
+}
+
+impl ScanState {
+    pub(crate) fn new() -> Self {
+        Self {
+            content_signatures: Arc::new(RwLock::new(HashSet::new())),
+            size_counter: HashMap::new(),
+            filtered_sizes: HashSet::new(),
+            confirmed_sizes: Arc::new(Mutex::new(BTreeSet::new())),
+            redirect_counter: HashMap::new(),
+            filtered_redirects: HashSet::new(),
+            response_sizes: HashMap::new(),
+            interactive: true,
+            size_threshold: DEFAULT_FILTER_THRESHOLD,
+            title_counter: HashMap::new(),
+            titled_responses: 0,
+            filtered_titles: HashSet::new(),
+            seen_titles: HashSet::new(),
+            soft_404: None,
+            homepage: None,
+        }
+    }
+
+    pub(crate) fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+    }
+
+    pub(crate) fn set_size_threshold(&mut self, threshold: usize) {
+        self.size_threshold = threshold;
+    }
+
+    /// 设置校准得到的404基线和首页指纹
+    pub(crate) fn set_baselines(&mut self, soft_404: Baseline, homepage: Option<Baseline>) {
+        self.soft_404 = Some(soft_404);
+        self.homepage = homepage;
+    }
+
+    /// 响应与404基线一致，即路径不存在
+    pub(crate) fn is_soft_404(&self, status: StatusCode, size: usize, threshold: usize) -> bool {
+        self.soft_404.as_ref().is_some_and(|baseline| baseline.matches(status, size, threshold))
+    }
+
+    /// 响应与首页一致，路径只是回显了首页内容
+    pub(crate) fn echoes_homepage(&self, status: StatusCode, size: usize, threshold: usize) -> bool {
+        self.homepage.as_ref().is_some_and(|homepage| homepage.matches(status, size, threshold))
+    }
+
+    pub(crate) fn from_file(path: &str) -> Result<Self> {
+        let mut state = Self::new();
+        let (signatures, sizes) = read_filter_file(path)?;
+        *state.content_signatures.write().unwrap() = signatures;
+        state.confirmed_sizes.lock().unwrap().extend(&sizes);
+        state.filtered_sizes = sizes;
+        Ok(state)
+    }
+
+    /// 重新读取过滤规则文件并替换内容特征，之后的请求立即使用新规则；返回规则条数
+    ///
+    /// 文件中的响应大小只在扫描开始时读取，重新加载时忽略
+    pub(crate) fn reload_signatures(&self, path: &str) -> Result<usize> {
+        let (signatures, _) = read_filter_file(path)?;
+        let count = signatures.len();
+        *self.content_signatures.write().unwrap() = signatures;
+        Ok(count)
+    }
+
+    /// 把当前的内容特征和所有确认过滤的响应大小写入过滤规则文件，可再由 `--filter` 读取
+    pub(crate) fn save_filters(&self, path: &Path) -> Result<()> {
+        let mut signatures: Vec<String> = self.content_signatures.read().unwrap().iter().cloned().collect();
+        signatures.sort();
+        let mut content = String::from("# RDirScan 过滤规则，每行一个内容特征；#!size 开头的行为已确认过滤的响应大小\n");
+        for signature in signatures {
+            content.push_str(&signature);
+            content.push('\n');
+        }
+        for size in self.confirmed_sizes.lock().unwrap().iter() {
+            content.push_str(&format!("{}{}\n", SIZE_PREFIX, size));
+        }
+        fs::write(path, content)
+            .map_err(|e| anyhow!("保存过滤规则到 {} 失败: {}", path.display(), e))
+    }
+
+    /// 按内容特征和已确认的响应大小判断是否过滤，返回命中的规则；没有响应体（HEAD模式）时只按大小判断
+    pub(crate) fn matched_rule(&self, content: Option<&str>, size: usize) -> Option<FilterReason> {
+        // 检查内容特征
+        if let Some(content) = content {
+            for signature in self.content_signatures.read().unwrap().iter() {
+                if content.contains(signature) {
+                    return Some(FilterReason::Signature(signature.clone()));
+                }
+            }
+        }
+        // 检查响应大小
+        self.filtered_sizes.contains(&size).then_some(FilterReason::FilteredSize(size))
+    }
+
+    /// 在原始响应字节中查找内容特征，读取响应体的过程中逐块调用，命中即可提前停止读取
+    pub(crate) fn signature_in(&self, bytes: &[u8]) -> Option<String> {
+        self.content_signatures
+            .read()
+            .unwrap()
+            .iter()
+            .find(|signature| !signature.is_empty() && bytes.windows(signature.len()).any(|window| window == signature.as_bytes()))
+            .cloned()
+    }
+
+    /// 最长内容特征的字节数，逐块查找时需要与上一块重叠这么多字节
+    pub(crate) fn longest_signature(&self) -> usize {
+        self.content_signatures.read().unwrap().iter().map(String::len).max().unwrap_or(0)
+    }
+
+    pub(crate) async fn check_repeated_size(&mut self, size: usize) -> bool {
+        // 更新计数器
+        let count = self.size_counter.entry(size).or_insert(0);
+        *count += 1;
+
+        // 如果已经是已过滤的大小，直接返回true
+        if self.filtered_sizes.contains(&size) {
+            return true;
+        }
+
+        // 相同大小达到阈值次数时询问用户
+        if *count >= self.size_threshold && !self.filtered_sizes.contains(&size) {
+            console::print_line(format!("\n{}", format!("检测到连续{}次响应大小为 {} 字节的页面，这可能是WAF拦截或登录跳转页面。", self.size_threshold, size).yellow()));
+            if self.confirm("是否将该响应大小添加到过滤列表？(y/n): ") {
+                self.filtered_sizes.insert(size);
+                self.confirmed_sizes.lock().unwrap().insert(size);
+                console::print_line("已添加到过滤列表。".green());
+                return true;
+            } else {
+                // 如果用户选择不过滤，重置计数器
+                self.size_counter.remove(&size);
+                console::print_line("已取消过滤。".yellow());
+            }
+        }
+        false
+    }
+
+    /// 记录响应标题，当某个登录相关的标题占据多数响应时询问是否过滤所有该标题的页面
+    ///
+    /// 用于识别对任意路径都返回200和登录页内容的统一认证（SSO）拦截
+    pub(crate) async fn check_login_title(&mut self, title: Option<&str>) -> bool {
+        let Some(title) = title else {
+            return false;
+        };
+        if self.filtered_titles.contains(title) {
+            return true;
+        }
+        self.titled_responses += 1;
+        let lower = title.to_lowercase();
+        if !LOGIN_TITLE_KEYWORDS.iter().any(|keyword| lower.contains(keyword)) {
+            return false;
+        }
+
+        let count = self.title_counter.entry(title.to_string()).or_insert(0);
+        *count += 1;
+        let count = *count;
+        if count >= self.size_threshold && count as f64 >= self.titled_responses as f64 * LOGIN_TITLE_RATIO {
+            console::print_line(format!("\n{}", format!("检测到 {} 个响应的标题均为 “{}”，这可能是统一登录页面。", count, title).yellow()));
+            if self.confirm("是否过滤所有该标题的页面？(y/n): ") {
+                self.filtered_titles.insert(title.to_string());
+                console::print_line("已添加到过滤列表。".green());
+                return true;
+            }
+            self.title_counter.remove(title);
+            console::print_line("已取消过滤。".yellow());
+        }
+        false
+    }
+
+    /// 记录报告过的标题，同一标题（忽略大小写和空白差异）已经报告过时返回 `true`，没有标题的响应不参与判断
+    pub(crate) fn check_duplicate_title(&mut self, title: Option<&str>) -> bool {
+        let Some(title) = title else {
+            return false;
+        };
+        let normalized = title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        !self.seen_titles.insert(normalized)
+    }
+
+    /// 记录响应大小，并判断其是否明显偏离占主导的响应大小
+    ///
+    /// 某个大小至少出现5次后才视为主导大小，在此之前不判定异常值
+    pub(crate) fn check_outlier(&mut self, size: usize, threshold: usize) -> bool {
+        *self.response_sizes.entry(size).or_insert(0) += 1;
+
+        let dominant = self.response_sizes
+            .iter()
+            .filter(|(_, count)| **count >= 5)
+            .max_by_key(|(_, count)| **count)
+            .map(|(size, _)| *size);
+        match dominant {
+            Some(dominant) => size.abs_diff(dominant) > threshold,
+            None => false,
+        }
+    }
+
+    /// 判断跳转是否为噪音：跳回自身、跳到站点根目录或登录页，以及大量路径跳往同一目标
+    pub(crate) async fn check_redirect(&mut self, url: &Url, location: &Url) -> Option<FilterReason> {
+        let mut target = location.clone();
+        target.set_fragment(None);
+
+        if target == *url {
+            return Some(FilterReason::SelfRedirect);
+        }
+        if target.path() == "/" {
+            return Some(FilterReason::RootRedirect);
+        }
+        if is_login_path(target.path()) {
+            return Some(FilterReason::LoginRedirect);
+        }
+
+        let target = target.to_string();
+        if self.filtered_redirects.contains(&target) {
+            return Some(FilterReason::RepeatedRedirect);
+        }
+
+        // 与响应大小计数相同，连续达到阈值次数跳往同一目标时询问用户
+        let count = self.redirect_counter.entry(target.clone()).or_insert(0);
+        *count += 1;
+        if *count >= self.size_threshold {
+            console::print_line(format!("\n{}", format!("检测到连续{}个路径跳转到 {}，这可能是统一的登录或拦截页面。", self.size_threshold, target).yellow()));
+            if self.confirm("是否将该跳转目标添加到过滤列表？(y/n): ") {
+                self.filtered_redirects.insert(target);
+                console::print_line("已添加到过滤列表。".green());
+                return Some(FilterReason::RepeatedRedirect);
+            }
+            self.redirect_counter.remove(&target);
+            console::print_line("已取消过滤。".yellow());
+        }
+        None
+    }
+
+    /// 在终端询问用户，输入 y 时返回 true；非交互模式下自动确认
+    pub(crate) fn confirm(&self, prompt: &str) -> bool {
+        if !self.interactive {
+            console::print_line("非交互模式，自动确认。".yellow());
+            return true;
+        }
+
+        console::prompt(prompt).eq_ignore_ascii_case("y")
+    }
+}
+
+/// 路径中是否有登录页的路径段，如 `/cas/login`、`/Login.aspx`、`/user/sign-in`、`/sso_auth`
+///
+/// 按路径段中以 `-`、`_`、`.` 分隔的单词匹配，`/lessons`、`/blogin` 这类只是包含关键字的路径不算
+fn is_login_path(path: &str) -> bool {
+    path.split('/').any(|segment| {
+        let segment = segment.to_lowercase();
+        let stem = segment.split('.').next().unwrap_or_default();
+        // sign-in、log_on 等写法去掉分隔符后整段匹配
+        let joined: String = stem.chars().filter(|c| !matches!(c, '-' | '_')).collect();
+        LOGIN_KEYWORDS.contains(&joined.as_str())
+            || stem.split(['-', '_']).any(|word| LOGIN_KEYWORDS.contains(&word))
+    })
+}
+
+fn read_filter_file(path: &str) -> Result<(HashSet<String>, HashSet<usize>)> {
+    let file = File::open(path)
+        .map_err(|e| anyhow!("打开过滤规则文件失败: {}", e))?;
+    parse_filter_rules(io::BufReader::new(file))
+}
+
+/// 解析过滤规则：每行一个内容特征，`#!size` 开头的行为需要过滤的响应大小，空行和其余 `#` 注释被忽略
+pub(crate) fn parse_filter_rules(reader: impl BufRead) -> Result<(HashSet<String>, HashSet<usize>)> {
+    let mut signatures = HashSet::new();
+    let mut sizes = HashSet::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(size) = line.strip_prefix(SIZE_PREFIX) {
+            let size = size.trim().parse::<usize>()
+                .map_err(|_| anyhow!("过滤规则中的响应大小无效: {}", line))?;
+            sizes.insert(size);
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        signatures.insert(line.to_string());
+    }
+    Ok((signatures, sizes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_saved_filters_back() {
+        let path = std::env::temp_dir().join(format!("rdirscan-filters-{}.txt", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        fs::write(&path, "# 注释\n页面不存在\nsize:1234\n#!size 512\n").unwrap();
+        ScanState::from_file(path_str).unwrap().save_filters(&path).unwrap();
+        let (signatures, sizes) = read_filter_file(path_str).unwrap();
+        fs::remove_file(&path).unwrap();
+        // 旧格式中 size: 开头的行仍是内容特征
+        assert_eq!(signatures, HashSet::from(["页面不存在".to_string(), "size:1234".to_string()]));
+        assert_eq!(sizes, HashSet::from([512]));
+        assert!(parse_filter_rules(io::Cursor::new("#!size abc\n")).is_err());
+    }
+
+    #[test]
+    fn detects_duplicate_titles() {
+        let mut state = ScanState::new();
+        assert!(!state.check_duplicate_title(Some("Admin Panel")));
+        // 忽略大小写和空白差异
+        assert!(state.check_duplicate_title(Some("  admin   PANEL ")));
+        assert!(!state.check_duplicate_title(Some("Dashboard")));
+        // 没有标题的响应不参与判断
+        assert!(!state.check_duplicate_title(None));
+        assert!(!state.check_duplicate_title(None));
+    }
+
+    #[test]
+    fn matches_login_paths_by_segment() {
+        for path in ["/login", "/cas/login", "/Login.aspx", "/user/sign-in", "/sign_in/", "/sso_auth", "/account/log-on", "/passport/"] {
+            assert!(is_login_path(path), "{}", path);
+        }
+        for path in ["/", "/lessons", "/blogin", "/assoc/list", "/passports.txt", "/api/loginhistory"] {
+            assert!(!is_login_path(path), "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn classifies_redirect_targets() {
+        let mut state = ScanState::new();
+        state.set_interactive(false);
+        let url = Url::parse("http://example.com/admin").unwrap();
+        let check = |location: &str| Url::parse(location).unwrap();
+        assert_eq!(state.check_redirect(&url, &check("http://example.com/admin#top")).await, Some(FilterReason::SelfRedirect));
+        assert_eq!(state.check_redirect(&url, &check("http://example.com/")).await, Some(FilterReason::RootRedirect));
+        assert_eq!(state.check_redirect(&url, &check("http://example.com/sso/login?next=/admin")).await, Some(FilterReason::LoginRedirect));
+        assert_eq!(state.check_redirect(&url, &check("http://example.com/lessons/")).await, None);
+
+        // 跳往同一目标的次数达到阈值后过滤
+        state.set_size_threshold(3);
+        let wall = check("http://example.com/blocked");
+        assert_eq!(state.check_redirect(&url, &wall).await, None);
+        assert_eq!(state.check_redirect(&url, &wall).await, None);
+        assert_eq!(state.check_redirect(&url, &wall).await, Some(FilterReason::RepeatedRedirect));
+        assert_eq!(state.check_redirect(&url, &wall).await, Some(FilterReason::RepeatedRedirect));
+    }
+}
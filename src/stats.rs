@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::finding::Finding;
+
+/// 最近发现列表保留的条数
+const RECENT_FINDINGS: usize = 20;
+
+/// 计算平均延迟时使用的最近请求数
+const LATENCY_WINDOW: usize = 200;
+
+/// 扫描进行中的实时统计，供界面等外部观察者随时读取
+#[derive(Default)]
+pub struct LiveStats {
+    requests: AtomicUsize,
+    found: AtomicUsize,
+    filtered: AtomicUsize,
+    errors: AtomicUsize,
+    concurrency: AtomicUsize,
+    statuses: Mutex<BTreeMap<u16, usize>>,
+    recent: Mutex<VecDeque<String>>,
+    // 已知的计划请求数，目标到达或递归展开目录时增加
+    total: AtomicUsize,
+    latency: Mutex<LatencyWindow>,
+}
+
+/// 最近若干请求的延迟及其总和
+#[derive(Default)]
+struct LatencyWindow {
+    samples: VecDeque<Duration>,
+    sum: Duration,
+}
+
+/// 某一时刻的统计快照
+#[derive(Debug, Clone, Default)]
+pub struct StatsSnapshot {
+    pub requests: usize,
+    pub found: usize,
+    pub filtered: usize,
+    pub errors: usize,
+    /// 当前并发数
+    pub concurrency: usize,
+    /// 各状态码出现的次数（包括被忽略的响应）
+    pub statuses: Vec<(u16, usize)>,
+    /// 最近的有效发现，最新的在最后
+    pub recent: Vec<String>,
+    /// 已知的计划请求数（从标准输入读取目标时只包括已到达的目标）
+    pub total: usize,
+    /// 最近请求（最多200个）的平均延迟
+    pub mean_latency: Option<Duration>,
+}
+
+impl StatsSnapshot {
+    /// 尚未发出的计划请求数
+    pub fn remaining(&self) -> usize {
+        self.total.saturating_sub(self.requests)
+    }
+
+    /// 按请求计数估算剩余时间：剩余请求数除以观测到的每秒请求数
+    pub fn eta_by_rate(&self, rate: f64) -> Option<Duration> {
+        (rate > 0.0).then(|| Duration::from_secs_f64(self.remaining() as f64 / rate))
+    }
+
+    /// 按最近请求的平均延迟估算剩余时间：剩余请求数 × 平均延迟 ÷ 并发数
+    ///
+    /// 只反映最近的响应速度，快速404与慢速下载交替时比按计数估算更平稳
+    pub fn eta_by_latency(&self) -> Option<Duration> {
+        let latency = self.mean_latency?;
+        Some(latency.mul_f64(self.remaining() as f64 / self.concurrency.max(1) as f64))
+    }
+}
+
+impl LiveStats {
+    /// 开始新一轮扫描时清空统计
+    pub(crate) fn reset(&self, concurrency: usize) {
+        self.requests.store(0, Ordering::SeqCst);
+        self.found.store(0, Ordering::SeqCst);
+        self.filtered.store(0, Ordering::SeqCst);
+        self.errors.store(0, Ordering::SeqCst);
+        self.concurrency.store(concurrency, Ordering::SeqCst);
+        self.statuses.lock().unwrap().clear();
+        self.recent.lock().unwrap().clear();
+        self.total.store(0, Ordering::SeqCst);
+        *self.latency.lock().unwrap() = LatencyWindow::default();
+    }
+
+    pub(crate) fn add_total(&self, requests: usize) {
+        self.total.fetch_add(requests, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_latency(&self, latency: Duration) {
+        let mut window = self.latency.lock().unwrap();
+        if window.samples.len() == LATENCY_WINDOW {
+            let oldest = window.samples.pop_front().unwrap_or_default();
+            window.sum -= oldest;
+        }
+        window.samples.push_back(latency);
+        window.sum += latency;
+    }
+
+    pub(crate) fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_status(&self, status: u16) {
+        *self.statuses.lock().unwrap().entry(status).or_insert(0) += 1;
+    }
+
+    pub(crate) fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_finding(&self, finding: &Finding) {
+        if finding.filtered {
+            self.filtered.fetch_add(1, Ordering::SeqCst);
+            return;
+        }
+        self.found.fetch_add(1, Ordering::SeqCst);
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_FINDINGS {
+            recent.pop_front();
+        }
+        recent.push_back(format!("{} {} ({} 字节)", finding.status.as_u16(), finding.url, finding.size));
+    }
+
+    pub(crate) fn set_concurrency(&self, concurrency: usize) {
+        self.concurrency.store(concurrency, Ordering::SeqCst);
+    }
+
+    /// 读取当前统计
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            requests: self.requests.load(Ordering::SeqCst),
+            found: self.found.load(Ordering::SeqCst),
+            filtered: self.filtered.load(Ordering::SeqCst),
+            errors: self.errors.load(Ordering::SeqCst),
+            concurrency: self.concurrency.load(Ordering::SeqCst),
+            statuses: self.statuses.lock().unwrap().iter().map(|(status, count)| (*status, *count)).collect(),
+            recent: self.recent.lock().unwrap().iter().cloned().collect(),
+            total: self.total.load(Ordering::SeqCst),
+            mean_latency: {
+                let window = self.latency.lock().unwrap();
+                (!window.samples.is_empty()).then(|| window.sum / window.samples.len() as u32)
+            },
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::finding::Finding;
+
+/// 模板支持的占位符
+const PLACEHOLDERS: &[(&str, Field)] = &[
+    ("url", Field::Url),
+    ("status", Field::Status),
+    ("size", Field::Size),
+    ("time", Field::Time),
+    ("content_type", Field::ContentType),
+    ("title", Field::Title),
+];
+
+/// `--output-template` 的行格式，如 `{status} {size} {url}`，解析一次后用于每条发现
+///
+/// 占位符为 `{url}`、`{status}`、`{size}`、`{time}`（收到响应的时间，ISO-8601）、`{content_type}` 和 `{title}`，
+/// 字面的花括号写作 `{{`、`}}`，`\t` 表示制表符（命令行中不便直接输入）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    spec: String,
+    parts: Vec<Part<Field>>,
+}
+
+/// 模板解析后的片段：字面文本或占位符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Part<F> {
+    Text(String),
+    Field(F),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Url,
+    Status,
+    Size,
+    Time,
+    ContentType,
+    Title,
+}
+
+impl OutputTemplate {
+    /// 按模板生成一条发现的输出行
+    pub fn render(&self, finding: &Finding) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(Field::Url) => line.push_str(finding.url.as_str()),
+                Part::Field(Field::Status) => line.push_str(&finding.status.as_u16().to_string()),
+                Part::Field(Field::Size) => line.push_str(&finding.size.to_string()),
+                Part::Field(Field::Time) => line.push_str(&finding.timestamp_iso()),
+                Part::Field(Field::ContentType) => line.push_str(finding.content_type.as_deref().unwrap_or_default()),
+                Part::Field(Field::Title) => line.push_str(finding.title.as_deref().unwrap_or_default()),
+            }
+        }
+        line
+    }
+}
+
+impl FromStr for OutputTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let parts = parse_placeholders("输出模板", spec, PLACEHOLDERS, &[('t', '\t')])?;
+        Ok(Self { spec: spec.to_string(), parts })
+    }
+}
+
+impl fmt::Display for OutputTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+/// 把带占位符的模板拆成片段，`kind` 用于错误信息（如"输出模板"）
+///
+/// 占位符写作 `{name}`，字面的花括号写作 `{{`、`}}`；`escapes` 列出支持的反斜杠转义，如 `('t', '\t')`。
+/// 占位符未知、花括号不成对或没有任何占位符时返回错误
+pub(crate) fn parse_placeholders<F: Copy>(
+    kind: &str,
+    spec: &str,
+    placeholders: &[(&str, F)],
+    escapes: &[(char, char)],
+) -> Result<Vec<Part<F>>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = spec.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                if !closed {
+                    return Err(anyhow!("{} '{}' 中的 '{{' 没有闭合，字面的花括号写作 '{{{{'", kind, spec));
+                }
+                let field = placeholders
+                    .iter()
+                    .find(|(placeholder, _)| *placeholder == name.trim())
+                    .map(|(_, field)| *field)
+                    .ok_or_else(|| anyhow!("{} '{}' 中的占位符 '{{{}}}' 无效，可用: {}", kind, spec, name, placeholder_list(placeholders)))?;
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Part::Field(field));
+            }
+            '\\' => match chars.peek().and_then(|next| escapes.iter().find(|(escape, _)| escape == next)) {
+                Some(&(_, replacement)) => {
+                    chars.next();
+                    text.push(replacement);
+                }
+                None => text.push(c),
+            },
+            '}' => return Err(anyhow!("{} '{}' 中有多余的 '}}'，字面的花括号写作 '}}}}'", kind, spec)),
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    if !parts.iter().any(|part| matches!(part, Part::Field(_))) {
+        return Err(anyhow!("{} '{}' 没有任何占位符，可用: {}", kind, spec, placeholder_list(placeholders)));
+    }
+    Ok(parts)
+}
+
+fn placeholder_list<F>(placeholders: &[(&str, F)]) -> String {
+    placeholders.iter().map(|(name, _)| format!("{{{}}}", name)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_placeholders_and_escapes() {
+        let template: OutputTemplate = "{{{status}}}\\t{size} {url}".parse().unwrap();
+        assert_eq!(
+            template.parts,
+            vec![
+                Part::Text("{".into()),
+                Part::Field(Field::Status),
+                Part::Text("}\t".into()),
+                Part::Field(Field::Size),
+                Part::Text(" ".into()),
+                Part::Field(Field::Url),
+            ]
+        );
+
+        let err = "{url} {length}".parse::<OutputTemplate>().unwrap_err().to_string();
+        assert!(err.contains("'{length}'") && err.contains("{content_type}"), "{}", err);
+        assert!("{url".parse::<OutputTemplate>().is_err());
+        assert!("url}".parse::<OutputTemplate>().is_err());
+        assert!("no placeholders".parse::<OutputTemplate>().is_err());
+    }
+
+    #[test]
+    fn tokenizes_with_the_given_escapes() {
+        let placeholders = [("a", 1), ("b", 2)];
+        let parts = parse_placeholders("测试模板", "{a}\\n{ b }\\x", &placeholders, &[('n', '\n')]).unwrap();
+        assert_eq!(parts, vec![Part::Field(1), Part::Text("\n".into()), Part::Field(2), Part::Text("\\x".into())]);
+        // 未列出的转义按字面保留
+        let parts = parse_placeholders("测试模板", "{a}\\n", &placeholders, &[]).unwrap();
+        assert_eq!(parts, vec![Part::Field(1), Part::Text("\\n".into())]);
+
+        let err = parse_placeholders("测试模板", "{c}", &placeholders, &[]).unwrap_err().to_string();
+        assert!(err.starts_with("测试模板 '{c}'") && err.contains("{a} {b}"), "{}", err);
+    }
+}
@@ -0,0 +1,53 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::sync::Mutex;
+
+/// 单个请求的跟踪记录
+pub(crate) struct TraceRecord<'a> {
+    pub(crate) method: &'a str,
+    pub(crate) url: &'a str,
+    pub(crate) user_agent: &'a str,
+    pub(crate) status: Option<u16>,
+    pub(crate) size: Option<usize>,
+    pub(crate) elapsed: Duration,
+    pub(crate) decision: &'a str,
+}
+
+/// 请求跟踪日志，每个请求一行，用于事后分析过滤行为
+pub(crate) struct TraceLog {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl TraceLog {
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow!("创建跟踪文件失败: {}", e))?;
+        Ok(Self { writer: Mutex::new(BufWriter::new(file)) })
+    }
+
+    pub(crate) async fn record(&self, record: TraceRecord<'_>) {
+        let status = record.status.map_or("-".to_string(), |status| status.to_string());
+        let size = record.size.map_or("-".to_string(), |size| size.to_string());
+        let mut writer = self.writer.lock().await;
+        let _ = writeln!(
+            writer,
+            "{}\t{}\tstatus={}\tsize={}\ttime={}ms\tua=\"{}\"\tdecision={}",
+            record.method,
+            record.url,
+            status,
+            size,
+            record.elapsed.as_millis(),
+            record.user_agent,
+            record.decision,
+        );
+    }
+
+    pub(crate) async fn flush(&self) -> Result<()> {
+        self.writer.lock().await.flush()
+            .map_err(|e| anyhow!("写入跟踪文件失败: {}", e))
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::finding::Finding;
+
+#[derive(Default)]
+struct TreeNode {
+    children: BTreeMap<String, TreeNode>,
+    // 该节点本身是一条发现时记录其状态码和大小
+    found: Option<(u16, usize)>,
+    // 路径以 `/` 结尾或包含子节点时按目录显示
+    is_dir: bool,
+}
+
+impl TreeNode {
+    fn insert(&mut self, segments: &[&str], trailing_slash: bool, finding: &Finding) {
+        let Some((first, rest)) = segments.split_first() else {
+            // `admin` 跳转到 `admin/` 时落在同一节点，优先保留非跳转的结果
+            if self.found.is_none_or(|(status, _)| (300..400).contains(&status)) {
+                self.found = Some((finding.status.as_u16(), finding.size));
+            }
+            return;
+        };
+        let child = self.children.entry(first.to_string()).or_default();
+        if !rest.is_empty() || trailing_slash {
+            child.is_dir = true;
+        }
+        child.insert(rest, trailing_slash, finding);
+    }
+
+    fn render(&self, prefix: &str, out: &mut String) {
+        let count = self.children.len();
+        for (i, (name, child)) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            let branch = if last { "└── " } else { "├── " };
+            let slash = if child.is_dir { "/" } else { "" };
+            let _ = write!(out, "{}{}{}{}", prefix, branch, name, slash);
+            if let Some((status, size)) = child.found {
+                let _ = write!(out, " [{}, {} 字节]", status, size);
+            }
+            out.push('\n');
+
+            let next_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            child.render(&next_prefix, out);
+        }
+    }
+}
+
+/// 将未过滤的发现按站点和路径层级渲染为树状结构
+pub fn render_tree(findings: &[Finding]) -> String {
+    let mut sites: BTreeMap<String, TreeNode> = BTreeMap::new();
+    for finding in findings.iter().filter(|f| !f.filtered) {
+        let root = sites.entry(finding.url.origin().ascii_serialization()).or_default();
+        let path = finding.url.path();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        root.insert(&segments, path.ends_with('/'), finding);
+    }
+
+    let mut out = String::new();
+    for (origin, root) in &sites {
+        let _ = writeln!(out, "{}/", origin);
+        root.render("", &mut out);
+    }
+    out
+}
@@ -0,0 +1,244 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::Result;
+
+/// 标准输出是终端时才能使用界面
+pub(crate) fn supported() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// 检查当前版本是否支持 `--tui`，在开始扫描前调用
+#[cfg(feature = "tui")]
+pub(crate) fn check_available() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+const UNAVAILABLE: &str = "当前版本未包含实时仪表盘功能，请使用 cargo build --features tui 重新编译";
+
+#[cfg(not(feature = "tui"))]
+pub(crate) fn check_available() -> Result<()> {
+    Err(anyhow::anyhow!(UNAVAILABLE))
+}
+
+#[cfg(feature = "tui")]
+pub(crate) use dashboard::Dashboard;
+
+/// 未包含界面功能时不会启动仪表盘，开始扫描前即报错
+#[cfg(not(feature = "tui"))]
+pub(crate) enum Dashboard {}
+
+#[cfg(not(feature = "tui"))]
+impl Dashboard {
+    pub(crate) fn start(
+        _stats: std::sync::Arc<rdirscan::LiveStats>,
+        _pause: rdirscan::PauseControl,
+        _target: String,
+        _latency_eta: bool,
+    ) -> Result<Self> {
+        Err(anyhow::anyhow!(UNAVAILABLE))
+    }
+
+    pub(crate) fn stop(self) -> Result<()> {
+        match self {}
+    }
+}
+
+#[cfg(feature = "tui")]
+mod dashboard {
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use anyhow::{anyhow, Result};
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Color, Style, Stylize};
+    use ratatui::text::Line;
+    use ratatui::widgets::{Bar, BarChart, BarGroup, Block, List, ListItem, Paragraph};
+    use ratatui::{DefaultTerminal, Frame};
+    use rdirscan::{LiveStats, PauseControl, StatsSnapshot};
+
+    /// 界面刷新间隔
+    const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// `--tui` 模式的实时仪表盘，在单独的线程中绘制，扫描结束后恢复终端
+    pub(crate) struct Dashboard {
+        stop: Arc<AtomicBool>,
+        handle: thread::JoinHandle<io::Result<()>>,
+    }
+
+    impl Dashboard {
+        /// `latency_eta` 为真时按最近请求的平均延迟估算剩余时间，否则按每秒请求数估算
+        pub(crate) fn start(stats: Arc<LiveStats>, pause: PauseControl, target: String, latency_eta: bool) -> Result<Self> {
+            let stop = Arc::new(AtomicBool::new(false));
+            let handle = {
+                let stop = Arc::clone(&stop);
+                thread::spawn(move || {
+                    let mut terminal = ratatui::init();
+                    let result = render_loop(&mut terminal, &stats, &pause, &stop, &target, latency_eta);
+                    ratatui::restore();
+                    result
+                })
+            };
+            Ok(Self { stop, handle })
+        }
+
+        /// 停止绘制并恢复终端
+        pub(crate) fn stop(self) -> Result<()> {
+            self.stop.store(true, Ordering::SeqCst);
+            self.handle.join()
+                .map_err(|_| anyhow!("界面线程异常退出"))?
+                .map_err(|e| anyhow!("界面绘制失败: {}", e))
+        }
+    }
+
+    fn render_loop(
+        terminal: &mut DefaultTerminal,
+        stats: &LiveStats,
+        pause: &PauseControl,
+        stop: &AtomicBool,
+        target: &str,
+        latency_eta: bool,
+    ) -> io::Result<()> {
+        let started = Instant::now();
+        let (mut sampled_at, mut sampled_requests, mut rate) = (Instant::now(), 0, 0.0);
+        while !stop.load(Ordering::SeqCst) {
+            let snapshot = stats.snapshot();
+            // 每秒更新一次速率，避免数字跳动
+            let since = sampled_at.elapsed();
+            if since >= Duration::from_secs(1) {
+                rate = snapshot.requests.saturating_sub(sampled_requests) as f64 / since.as_secs_f64();
+                sampled_at = Instant::now();
+                sampled_requests = snapshot.requests;
+            }
+            let eta = if latency_eta { snapshot.eta_by_latency() } else { snapshot.eta_by_rate(rate) };
+            let state = if pause.is_cancelled() {
+                Some("正在停止，等待在途请求完成")
+            } else if pause.is_paused() {
+                Some("已暂停")
+            } else {
+                None
+            };
+            terminal.draw(|frame| draw(frame, &snapshot, rate, eta, started.elapsed(), target, state))?;
+
+            if event::poll(REFRESH_INTERVAL)? {
+                if let Event::Key(key) = event::read()? {
+                    let interrupt = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                    // 原始模式下 Ctrl-C 不再产生信号，需要自行处理：停止派发新请求，
+                    // 在途请求完成后扫描照常结束，输出文件也会正常写完
+                    if key.kind == KeyEventKind::Press && (interrupt || key.code == KeyCode::Char('q')) {
+                        pause.cancel();
+                    }
+                    if key.kind == KeyEventKind::Press {
+                        match key.code {
+                            KeyCode::Char('p') => {
+                                pause.pause();
+                            }
+                            KeyCode::Char('r') => {
+                                pause.resume();
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 剩余时间显示为 `1h02m`、`3m05s` 或 `12s`
+    fn format_duration(duration: Duration) -> String {
+        let secs = duration.as_secs();
+        match secs {
+            3600.. => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+            60.. => format!("{}m{:02}s", secs / 60, secs % 60),
+            _ => format!("{}s", secs),
+        }
+    }
+
+    fn draw(
+        frame: &mut Frame,
+        snapshot: &StatsSnapshot,
+        rate: f64,
+        eta: Option<Duration>,
+        elapsed: Duration,
+        target: &str,
+        state: Option<&str>,
+    ) {
+        let [header, summary, body] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(4),
+            Constraint::Min(5),
+        ])
+        .areas(frame.area());
+        let [statuses, recent] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(body);
+
+        let mut title = Line::from(format!(" RDirScan  {}  已运行 {}s  (p 暂停  r 继续  q 退出)", target, elapsed.as_secs())).bold();
+        if let Some(state) = state {
+            title.push_span(format!("  {}", state).yellow());
+        }
+        frame.render_widget(title, header);
+
+        let summary_text = Line::from(vec![
+            format!("请求 {}  ", snapshot.requests).into(),
+            format!("{:.0} 请求/秒  ", rate).cyan(),
+            format!("有效发现 {}  ", snapshot.found).red().bold(),
+            format!("已过滤 {}  ", snapshot.filtered).magenta(),
+            format!("出错 {}  ", snapshot.errors).yellow(),
+            format!("并发 {}", snapshot.concurrency).into(),
+        ]);
+        let percent = match snapshot.total {
+            0 => 0.0,
+            total => snapshot.requests.min(total) as f64 * 100.0 / total as f64,
+        };
+        let progress_text = Line::from(vec![
+            format!("进度 {}/{} ({:.1}%)  ", snapshot.requests, snapshot.total, percent).into(),
+            format!("预计剩余 {}  ", eta.map_or_else(|| "-".to_string(), format_duration)).green(),
+            match snapshot.mean_latency {
+                Some(latency) => format!("平均延迟 {}ms", latency.as_millis()).into(),
+                None => "".into(),
+            },
+        ]);
+        frame.render_widget(
+            Paragraph::new(vec![summary_text, progress_text]).block(Block::bordered().title("统计")),
+            summary,
+        );
+
+        let bars: Vec<Bar> = snapshot.statuses
+            .iter()
+            .map(|(status, count)| {
+                let color = match status {
+                    200..=299 => Color::Green,
+                    300..=399 => Color::Cyan,
+                    400..=499 => Color::Yellow,
+                    _ => Color::Red,
+                };
+                Bar::default()
+                    .label(Line::from(status.to_string()))
+                    .value(*count as u64)
+                    .style(Style::default().fg(color))
+            })
+            .collect();
+        frame.render_widget(
+            BarChart::default()
+                .block(Block::bordered().title("状态码分布"))
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(5)
+                .bar_gap(1),
+            statuses,
+        );
+
+        // 只显示放得下的最新几条
+        let visible = recent.height.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = snapshot.recent
+            .iter()
+            .rev()
+            .take(visible)
+            .map(|line| ListItem::new(line.as_str()).red())
+            .collect();
+        frame.render_widget(List::new(items).block(Block::bordered().title("最近发现")), recent);
+    }
+}
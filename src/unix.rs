@@ -0,0 +1,276 @@
+use std::error::Error as _;
+use std::fmt;
+
+use anyhow::Result;
+use reqwest::{RequestBuilder, Response};
+
+/// 检查当前版本是否支持 `--unix-socket`，在配置校验时调用
+#[cfg(all(unix, feature = "unix-socket"))]
+pub(crate) fn check_available() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(all(unix, feature = "unix-socket")))]
+const UNAVAILABLE: &str = "当前版本未包含Unix域套接字功能，请在类Unix系统上使用 cargo build --features unix-socket 重新编译";
+
+#[cfg(not(all(unix, feature = "unix-socket")))]
+pub(crate) fn check_available() -> Result<()> {
+    Err(anyhow::anyhow!(UNAVAILABLE))
+}
+
+/// 发送请求：通过Unix域套接字扫描时交给 `unix` 发送，否则由reqwest客户端直接发送
+pub(crate) async fn send(request: RequestBuilder, unix: Option<&UnixClient>) -> Result<Response, SendError> {
+    match unix {
+        Some(unix) => {
+            let request = request.build().map_err(SendError::Http)?;
+            unix.execute(request).await.map_err(SendError::Unix)
+        }
+        None => request.send().await.map_err(SendError::Http),
+    }
+}
+
+/// 发送请求时的错误，来自reqwest客户端或Unix域套接字上的hyper客户端
+#[derive(Debug)]
+pub(crate) enum SendError {
+    Http(reqwest::Error),
+    Unix(hyper::Error),
+}
+
+impl SendError {
+    pub(crate) fn is_connect(&self) -> bool {
+        match self {
+            Self::Http(e) => e.is_connect(),
+            Self::Unix(e) => e.is_connect(),
+        }
+    }
+
+    pub(crate) fn is_timeout(&self) -> bool {
+        match self {
+            Self::Http(e) => e.is_timeout(),
+            Self::Unix(e) => e.is_timeout(),
+        }
+    }
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(e) => e.fmt(f),
+            // hyper的连接错误只说明"连接出错"，附上套接字连接失败的具体原因
+            Self::Unix(e) => match e.source() {
+                Some(cause) if e.is_connect() => write!(f, "{}: {}", e, cause),
+                _ => e.fmt(f),
+            },
+        }
+    }
+}
+
+impl std::error::Error for SendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            Self::Unix(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "unix-socket"))]
+pub(crate) use connector::UnixClient;
+
+/// 未包含Unix域套接字功能时不会创建客户端，配置校验阶段即报错
+#[cfg(not(all(unix, feature = "unix-socket")))]
+pub(crate) enum UnixClient {}
+
+#[cfg(not(all(unix, feature = "unix-socket")))]
+impl UnixClient {
+    pub(crate) fn new(_config: &crate::config::ScanConfig, _user_agent: &str) -> Result<Self> {
+        Err(anyhow::anyhow!(UNAVAILABLE))
+    }
+
+    async fn execute(&self, _request: reqwest::Request) -> Result<Response, hyper::Error> {
+        match *self {}
+    }
+}
+
+#[cfg(all(unix, feature = "unix-socket"))]
+mod connector {
+    use std::future::Future;
+    use std::io;
+    use std::path::PathBuf;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    use anyhow::{anyhow, Result};
+    use hyper::client::connect::{Connected, Connection};
+    use hyper::header::{HeaderValue, ACCEPT, USER_AGENT};
+    use hyper::service::Service;
+    use hyper::{Body, Client, Uri};
+    use reqwest::{ResponseBuilderExt, Response};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use tokio::net::UnixStream;
+
+    use crate::config::ScanConfig;
+
+    /// 经Unix域套接字发送HTTP请求的客户端
+    ///
+    /// 请求仍由reqwest构造，这里只负责发送：连接器每次直接连接套接字，不经过任何TCP端口，
+    /// 响应再转换回reqwest的类型，扫描的其余部分（过滤、重试、统计等）照常工作。不跟随跳转，也不解压响应
+    pub(crate) struct UnixClient {
+        client: Client<UnixConnector>,
+        // reqwest客户端上设置的默认请求头不在构造好的请求中，需要补上
+        user_agent: HeaderValue,
+    }
+
+    impl UnixClient {
+        pub(crate) fn new(config: &ScanConfig, user_agent: &str) -> Result<Self> {
+            let path = config.unix_socket.clone().ok_or_else(|| anyhow!("未设置Unix套接字"))?;
+            // 提前连接一次，套接字不存在或无权限时立即报错
+            std::os::unix::net::UnixStream::connect(&path)
+                .map_err(|e| anyhow!("无法连接Unix套接字 {}: {}", path.display(), e))?;
+            let mut builder = Client::builder();
+            if let Some(max) = config.pool_max_idle_per_host {
+                builder.pool_max_idle_per_host(max);
+            }
+            if let Some(timeout) = config.pool_idle_timeout {
+                builder.pool_idle_timeout(timeout);
+            }
+            let connector = UnixConnector { path: Arc::new(path), timeout: config.connect_timeout };
+            Ok(Self {
+                client: builder.build(connector),
+                user_agent: HeaderValue::from_str(user_agent).map_err(|e| anyhow!("UA无效: {}", e))?,
+            })
+        }
+
+        pub(crate) async fn execute(&self, request: reqwest::Request) -> Result<Response, hyper::Error> {
+            let url = request.url().clone();
+            // hyper要求完整的URL，主机部分只用于Host头，连接总是指向套接字
+            let mut builder = hyper::Request::builder()
+                .method(request.method().clone())
+                .uri(url.as_str());
+            let headers = builder.headers_mut().expect("请求构造器有效");
+            headers.extend(request.headers().clone());
+            headers.entry(USER_AGENT).or_insert_with(|| self.user_agent.clone());
+            headers.entry(ACCEPT).or_insert_with(|| HeaderValue::from_static("*/*"));
+            let body = request.body()
+                .and_then(reqwest::Body::as_bytes)
+                .map_or_else(Body::empty, |bytes| Body::from(bytes.to_vec()));
+            let request = builder.body(body).expect("请求由reqwest校验过");
+            let (parts, body) = self.client.request(request).await?.into_parts();
+            let mut builder = hyper::Response::builder()
+                .status(parts.status)
+                .version(parts.version)
+                .url(url);
+            *builder.headers_mut().expect("响应构造器有效") = parts.headers;
+            Ok(Response::from(builder.body(body).expect("响应来自hyper")))
+        }
+    }
+
+    /// 每次调用都新建一条到套接字的连接，忽略请求URL中的主机和端口
+    #[derive(Clone)]
+    struct UnixConnector {
+        path: Arc<PathBuf>,
+        timeout: Duration,
+    }
+
+    impl Service<Uri> for UnixConnector {
+        type Response = UnixConnection;
+        type Error = io::Error;
+        type Future = Pin<Box<dyn Future<Output = io::Result<UnixConnection>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _uri: Uri) -> Self::Future {
+            let (path, timeout) = (Arc::clone(&self.path), self.timeout);
+            Box::pin(async move {
+                let stream = tokio::time::timeout(timeout, UnixStream::connect(&*path)).await
+                    .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "连接Unix套接字超时"))??;
+                Ok(UnixConnection(stream))
+            })
+        }
+    }
+
+    struct UnixConnection(UnixStream);
+
+    impl Connection for UnixConnection {
+        fn connected(&self) -> Connected {
+            Connected::new()
+        }
+    }
+
+    impl AsyncRead for UnixConnection {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for UnixConnection {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+}
+
+#[cfg(all(test, unix, feature = "unix-socket"))]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixListener;
+
+    use super::*;
+    use crate::config::ScanConfig;
+
+    #[tokio::test]
+    async fn sends_requests_over_the_socket() {
+        let path = std::env::temp_dir().join(format!("rdirscan-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            // 创建客户端时的连接检查不发送任何内容，直接关闭
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buffer = Vec::new();
+                let mut chunk = [0u8; 1024];
+                while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match socket.read(&mut chunk).await.unwrap() {
+                        0 => break,
+                        n => buffer.extend_from_slice(&chunk[..n]),
+                    }
+                }
+                if buffer.is_empty() {
+                    continue;
+                }
+                socket.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 4\r\n\r\ndeny").await.unwrap();
+                return String::from_utf8_lossy(&buffer).to_lowercase();
+            }
+        });
+
+        let config = ScanConfig::builder()
+            .url("http://docker/")
+            .unix_socket(&path)
+            .interactive(false)
+            .build()
+            .unwrap();
+        let unix = UnixClient::new(&config, "test-agent").unwrap();
+        let request = reqwest::Client::new().get("http://docker/v1/info?all=1");
+        let resp = send(request, Some(&unix)).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::FORBIDDEN);
+        assert_eq!(resp.url().as_str(), "http://docker/v1/info?all=1");
+        assert_eq!(resp.text().await.unwrap(), "deny");
+
+        let head = server.await.unwrap();
+        assert!(head.starts_with("get /v1/info?all=1 http/1.1\r\n"), "{}", head);
+        assert!(head.contains("host: docker\r\n") && head.contains("user-agent: test-agent\r\n"), "{}", head);
+        let _ = std::fs::remove_file(&path);
+    }
+}
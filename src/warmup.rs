@@ -0,0 +1,97 @@
+use std::error::Error as _;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use reqwest::header::SERVER;
+use reqwest::RequestBuilder;
+use url::Url;
+
+use crate::config::ScanConfig;
+use crate::unix::{self, SendError, UnixClient};
+
+/// 扫描前对目标的一次预热请求的结果
+#[derive(Debug, Clone)]
+pub struct WarmupOutcome {
+    pub url: Url,
+    pub status: u16,
+    pub size: usize,
+    /// 响应的 `Server` 头
+    pub server: Option<String>,
+    /// 是否通过TLS连接（https）
+    pub tls: bool,
+    /// 请求经过的代理
+    pub proxy: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// 请求目标URL一次，确认网络、TLS和代理都能正常工作；失败时返回说明原因的错误
+///
+/// `request` 是已带上Host头和UA的GET请求，这里再加上自定义请求头和签名
+pub(crate) async fn warmup(
+    mut request: RequestBuilder,
+    unix: Option<&UnixClient>,
+    config: &ScanConfig,
+    url: &Url,
+    signature: &[(&str, String)],
+) -> Result<WarmupOutcome> {
+    let proxy = config.proxied(url).then(|| config.effective_proxy(url.scheme())).flatten();
+    for (name, value) in &config.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    for (name, value) in signature {
+        request = request.header(*name, value.as_str());
+    }
+
+    let started = Instant::now();
+    let resp = match tokio::time::timeout(config.timeout, unix::send(request, unix)).await {
+        Ok(Ok(resp)) => resp,
+        Ok(Err(SendError::Http(e))) => return Err(describe_error(&e, url, proxy.as_deref())),
+        Ok(Err(e)) => return Err(anyhow!("通过Unix套接字请求 {} 失败: {}", url, e)),
+        Err(_) => return Err(anyhow!("请求 {} 超时（{}秒），目标可能无法访问", url, config.timeout.as_secs_f64())),
+    };
+    let status = resp.status().as_u16();
+    let server = resp.headers()
+        .get(SERVER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let size = tokio::time::timeout(config.timeout, resp.bytes()).await
+        .map_err(|_| anyhow!("读取 {} 的响应超时", url))?
+        .map_err(|e| anyhow!("读取 {} 的响应失败: {}", url, e))?
+        .len();
+    Ok(WarmupOutcome {
+        url: url.clone(),
+        status,
+        size,
+        server,
+        tls: url.scheme() == "https",
+        proxy,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// 根据错误链判断失败的环节，给出可操作的提示
+fn describe_error(error: &reqwest::Error, url: &Url, proxy: Option<&str>) -> anyhow::Error {
+    let mut chain = String::new();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        chain.push_str(&cause.to_string().to_lowercase());
+        chain.push(' ');
+        source = cause.source();
+    }
+    let tls = ["certificate", "tls", "ssl", "handshake"].iter().any(|word| chain.contains(word));
+    if tls && url.scheme() == "https" {
+        return anyhow!("与 {} 的TLS握手失败: {}（内部CA签发的证书可通过 --ca-cert 信任，或使用 -k 跳过验证）", url, error);
+    }
+    if let Some(proxy) = proxy {
+        if error.is_connect() {
+            return anyhow!("通过代理 {} 连接 {} 失败: {}（请检查代理是否可用）", proxy, url, error);
+        }
+    }
+    if chain.contains("dns") || chain.contains("resolve") || chain.contains("lookup") {
+        return anyhow!("无法解析 {} 的主机名: {}", url, error);
+    }
+    if error.is_connect() {
+        return anyhow!("无法连接到 {}: {}", url, error);
+    }
+    anyhow!("请求 {} 失败: {}", url, error)
+}
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::finding::Finding;
+
+/// 累积到这么多条发现时立即发送一批，也是每个请求最多包含的条数
+const BATCH_SIZE: usize = 50;
+/// 队列中最多保留的发现，收集服务持续不可用时丢弃最早的发现，计入未送达
+const MAX_PENDING: usize = 20 * BATCH_SIZE;
+/// 不足一批时最多等待这么久就发送
+const BATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// 每批最多尝试的次数，之间按指数退避等待
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `--webhook`：把有效发现以JSON批量POST到收集服务，在后台发送，不阻塞扫描
+///
+/// 请求体为 `{"findings": [...]}`，每条发现与JSON输出的格式相同；一批发送失败时保留在队列中稍后重试，
+/// 队列超过上限时丢弃最早的发现，扫描结束时仍未送达的条数记入日志和扫描报告
+pub(crate) struct Webhook {
+    sender: UnboundedSender<Value>,
+    task: JoinHandle<usize>,
+}
+
+impl Webhook {
+    /// 启动后台发送任务
+    ///
+    /// 使用单独的默认配置客户端：扫描客户端带有目标站点的Cookie、固定解析、跳转范围限制等设置，
+    /// 不适用于收集服务，也不应把这些信息发给它
+    pub(crate) fn start(url: &str) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow!("webhook客户端创建失败: {}", e))?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(deliver_loop(url.to_string(), client, receiver));
+        Ok(Self { sender, task })
+    }
+
+    /// 把一条发现放入发送队列，立即返回
+    pub(crate) fn send(&self, finding: &Finding) {
+        let _ = self.sender.send(finding.to_json());
+    }
+
+    /// 发送队列中剩余的发现，返回最终未能送达的条数
+    pub(crate) async fn finish(self) -> usize {
+        drop(self.sender);
+        self.task.await.unwrap_or_default()
+    }
+}
+
+async fn deliver_loop(url: String, client: Client, mut receiver: UnboundedReceiver<Value>) -> usize {
+    let mut pending = VecDeque::new();
+    // 队列已满时丢弃的条数
+    let mut dropped = 0;
+    let mut ticker = tokio::time::interval(BATCH_INTERVAL);
+    loop {
+        tokio::select! {
+            // 先取完已到达的发现，再按定时器重试
+            biased;
+            received = receiver.recv() => match received {
+                Some(finding) => {
+                    if pending.len() == MAX_PENDING {
+                        if dropped == 0 {
+                            warn!(url = %url, limit = MAX_PENDING, "webhook积压的发现过多，开始丢弃最早的发现");
+                        }
+                        pending.pop_front();
+                        dropped += 1;
+                    }
+                    pending.push_back(finding);
+                    // 有积压时由定时器重试，不必每收到一条就重发
+                    if pending.len() != BATCH_SIZE {
+                        continue;
+                    }
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {
+                if pending.is_empty() {
+                    continue;
+                }
+            }
+        }
+        // 逐批发送积压的发现，失败时等下次定时再试
+        while !pending.is_empty() && deliver_batch(&client, &url, &mut pending).await {}
+    }
+    // 发送剩余的发现，收集服务仍不可用时不再逐批重试
+    while !pending.is_empty() && deliver_batch(&client, &url, &mut pending).await {}
+    let undelivered = dropped + pending.len();
+    if undelivered > 0 {
+        warn!(url = %url, count = undelivered, "webhook持续失败，部分发现未能发送");
+    }
+    undelivered
+}
+
+/// 发送队列开头的一批发现，送达后移出队列
+async fn deliver_batch(client: &Client, url: &str, pending: &mut VecDeque<Value>) -> bool {
+    let count = pending.len().min(BATCH_SIZE);
+    let delivered = deliver(client, url, &pending.make_contiguous()[..count]).await;
+    if delivered {
+        pending.drain(..count);
+    }
+    delivered
+}
+
+/// 发送一批发现，失败时退避重试，返回是否送达
+async fn deliver(client: &Client, url: &str, findings: &[Value]) -> bool {
+    let body = json!({ "findings": findings });
+    for attempt in 1..=MAX_ATTEMPTS {
+        let error = match client.post(url).json(&body).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                debug!(url = %url, count = findings.len(), "已发送到webhook");
+                return true;
+            }
+            Ok(resp) => format!("状态码 {}", resp.status()),
+            Err(e) => e.to_string(),
+        };
+        warn!(url = %url, attempt, error = %error, "发送到webhook失败");
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(RETRY_DELAY * 2u32.pow(attempt - 1)).await;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 启动收集服务，返回其URL和收到的每个请求（请求头和请求体）
+    async fn collector() -> (String, std::sync::Arc<std::sync::Mutex<Vec<(String, Value)>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let recorded = std::sync::Arc::clone(&recorded);
+                tokio::spawn(async move {
+                    let mut buffer = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        // 按 Content-Length 读出完整的请求再应答，连接关闭时结束
+                        let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") else {
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                            }
+                            continue;
+                        };
+                        let head = String::from_utf8_lossy(&buffer[..end]).to_string();
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|value| value.trim().parse::<usize>().unwrap()))
+                            .unwrap_or(0);
+                        while buffer.len() < end + 4 + length {
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                            }
+                        }
+                        let body = serde_json::from_slice(&buffer[end + 4..end + 4 + length]).unwrap();
+                        buffer.drain(..end + 4 + length);
+                        recorded.lock().unwrap().push((head, body));
+                        if socket.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        (format!("http://{}/hook", addr), requests)
+    }
+
+    #[tokio::test]
+    async fn delivers_findings_in_batches() {
+        let (url, requests) = collector().await;
+        let webhook = Webhook::start(&url).unwrap();
+        for i in 0..120 {
+            webhook.send(&Finding::sample(&format!("http://example.com/{}", i), 200, 10));
+        }
+        assert_eq!(webhook.finish().await, 0);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests.iter().all(|(head, _)| head.starts_with("POST /hook ")));
+        // 每个请求最多一批，按发送顺序送达所有发现
+        let batches: Vec<usize> = requests.iter().map(|(_, body)| body["findings"].as_array().unwrap().len()).collect();
+        assert!(batches.iter().all(|count| *count <= BATCH_SIZE), "{:?}", batches);
+        let urls: Vec<&str> = requests.iter().flat_map(|(_, body)| body["findings"].as_array().unwrap()).map(|finding| finding["url"].as_str().unwrap()).collect();
+        let expected: Vec<String> = (0..120).map(|i| format!("http://example.com/{}", i)).collect();
+        assert_eq!(urls, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn counts_undelivered_findings() {
+        // 绑定后立即释放的端口，连接会被拒绝
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let webhook = Webhook::start(&format!("http://{}/hook", addr)).unwrap();
+        for path in ["a", "b", "c"] {
+            webhook.send(&Finding::sample(&format!("http://example.com/{}", path), 200, 1));
+        }
+        assert_eq!(webhook.finish().await, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_the_oldest_findings_beyond_the_limit() {
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let webhook = Webhook::start(&format!("http://{}/hook", addr)).unwrap();
+        for i in 0..MAX_PENDING + 5 {
+            webhook.send(&Finding::sample(&format!("http://example.com/{}", i), 200, 1));
+        }
+        // 丢弃的发现同样计入未送达
+        assert_eq!(webhook.finish().await, MAX_PENDING + 5);
+    }
+}